@@ -0,0 +1,31 @@
+// Time to generate the orientations of the 369 free octominoes.
+//
+// Run with `cargo bench --bench orientations`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use polyomino::enumerate::free_polyominoes;
+use polyomino::orientation::OrientationCache;
+
+const ROUNDS: u32 = 200;
+
+fn main() {
+    let octominoes = free_polyominoes(8);
+    assert_eq!(octominoes.len(), 369);
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let mut cache = OrientationCache::new();
+        for tile in &octominoes {
+            black_box(cache.orientations(tile));
+        }
+        assert_eq!(cache.generated(), 2725);
+    }
+    let elapsed = start.elapsed() / ROUNDS;
+    println!(
+        "orientations of {} octominoes: {:?} per round",
+        octominoes.len(),
+        elapsed
+    );
+}
@@ -0,0 +1,294 @@
+use crate::shape::Point;
+use crate::shape::Tile;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Boundary step directions (screen coordinates, y pointing down).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Right,
+    Down,
+    Left,
+    Up,
+}
+
+impl Step {
+    pub(crate) fn delta(&self) -> Point {
+        match self {
+            Step::Right => Point::new(1, 0),
+            Step::Down => Point::new(0, 1),
+            Step::Left => Point::new(-1, 0),
+            Step::Up => Point::new(0, -1),
+        }
+    }
+
+    fn opposite(&self) -> Step {
+        match self {
+            Step::Right => Step::Left,
+            Step::Down => Step::Up,
+            Step::Left => Step::Right,
+            Step::Up => Step::Down,
+        }
+    }
+
+    fn right_turn(&self) -> Step {
+        match self {
+            Step::Right => Step::Down,
+            Step::Down => Step::Left,
+            Step::Left => Step::Up,
+            Step::Up => Step::Right,
+        }
+    }
+}
+
+// Clockwise boundary word of a tile.
+//
+// Returns `None` when the tile is empty, or when its boundary is not a single
+// closed curve (disconnected tiles and tiles with holes).
+pub fn boundary_word(tile: &Tile) -> Option<Vec<Step>> {
+    let cells: HashSet<&Point> = tile.points.iter().collect();
+
+    // directed boundary edges keyed by their start vertex, interior on the right
+    let mut edges: HashMap<Point, Vec<Step>> = HashMap::new();
+    let mut count = 0;
+    for p in &tile.points {
+        let sides = [
+            (Step::Up, Step::Right, Point::new(p.x, p.y)),
+            (Step::Right, Step::Down, Point::new(p.x + 1, p.y)),
+            (Step::Down, Step::Left, Point::new(p.x + 1, p.y + 1)),
+            (Step::Left, Step::Up, Point::new(p.x, p.y + 1)),
+        ];
+        for (side, step, start) in sides {
+            let neighbor = p.clone() + side.delta();
+            if !cells.contains(&neighbor) {
+                edges.entry(start).or_default().push(step);
+                count += 1;
+            }
+        }
+    }
+
+    let start = tile.points.iter().min_by_key(|p| (p.y, p.x))?;
+    let mut vertex = start.clone();
+    let mut word: Vec<Step> = Vec::with_capacity(count);
+    let mut heading = Step::Up;
+
+    loop {
+        let steps = edges.get_mut(&vertex)?;
+        // at pinch points keep hugging the same cell by turning right first
+        let preference = [
+            heading.right_turn(),
+            heading,
+            heading.right_turn().opposite(),
+        ];
+        let position = preference
+            .iter()
+            .find_map(|s| steps.iter().position(|t| t == s))?;
+        let step = steps.swap_remove(position);
+
+        word.push(step);
+        vertex += step.delta();
+        heading = step;
+
+        if vertex == *start && word.len() > 1 && edges.get(&vertex).is_none_or(|s| s.is_empty()) {
+            break;
+        }
+    }
+
+    if word.len() != count {
+        return None;
+    }
+    Some(word)
+}
+
+// Closed outlines of a tile, as the grid vertices where they turn.
+//
+// Every outline runs with the tile on its right: clockwise around the outside
+// and counterclockwise around holes. Outlines start at their top left vertex
+// and come in the order of their starts, row by row. Cells touching only at a
+// corner get outlines of their own.
+pub fn outlines(tile: &Tile) -> Vec<Vec<Point>> {
+    let cells: HashSet<&Point> = tile.points.iter().collect();
+
+    // directed boundary edges keyed by their start vertex, interior on the right
+    let mut edges: HashMap<Point, Vec<Step>> = HashMap::new();
+    for p in &tile.points {
+        let sides = [
+            (Step::Up, Step::Right, Point::new(p.x, p.y)),
+            (Step::Right, Step::Down, Point::new(p.x + 1, p.y)),
+            (Step::Down, Step::Left, Point::new(p.x + 1, p.y + 1)),
+            (Step::Left, Step::Up, Point::new(p.x, p.y + 1)),
+        ];
+        for (side, step, start) in sides {
+            if !cells.contains(&(p.clone() + side.delta())) {
+                edges.entry(start).or_default().push(step);
+            }
+        }
+    }
+
+    let mut outlines = Vec::new();
+    loop {
+        let start = edges
+            .iter()
+            .filter(|(_, steps)| !steps.is_empty())
+            .map(|(vertex, _)| vertex)
+            .min_by_key(|v| (v.y, v.x))
+            .cloned();
+        let Some(start) = start else {
+            return outlines;
+        };
+        let mut vertex = start.clone();
+        let mut corners: Vec<Point> = Vec::new();
+        let mut heading: Option<Step> = None;
+        loop {
+            let steps = edges.get_mut(&vertex).unwrap();
+            // at pinch points keep hugging the same cell by turning right first
+            let position = match heading {
+                Some(heading) => [
+                    heading.right_turn(),
+                    heading,
+                    heading.right_turn().opposite(),
+                ]
+                .iter()
+                .find_map(|s| steps.iter().position(|t| t == s))
+                .unwrap(),
+                None => 0,
+            };
+            let step = steps.swap_remove(position);
+            if heading != Some(step) {
+                corners.push(vertex.clone());
+            }
+            vertex += step.delta();
+            heading = Some(step);
+            if vertex == start {
+                break;
+            }
+        }
+        outlines.push(corners);
+    }
+}
+
+// reversed and complemented factor: the same path walked backwards.
+fn hat(word: &[Step]) -> Vec<Step> {
+    word.iter().rev().map(|s| s.opposite()).collect()
+}
+
+// Whether the tile tiles the plane by translations only.
+//
+// Implements the Beauquier–Nivat criterion: the boundary word must factor,
+// up to rotation, as `A B C Â B̂ Ĉ` where `X̂` is `X` reversed and complemented,
+// with at most one of the factors empty.
+pub fn tiles_plane(tile: &Tile) -> bool {
+    let word = match boundary_word(tile) {
+        Some(word) => word,
+        None => return false,
+    };
+
+    let n = word.len();
+    let half = n / 2;
+    for s in 0..n {
+        let w: Vec<Step> = word[s..].iter().chain(word[..s].iter()).cloned().collect();
+        let (first, second) = w.split_at(half);
+
+        for a in 0..=half {
+            for b in 0..=(half - a) {
+                let c = half - a - b;
+                if [a, b, c].iter().filter(|l| **l == 0).count() > 1 {
+                    continue;
+                }
+                let factors = [&first[..a], &first[a..a + b], &first[a + b..]];
+                let matches = factors
+                    .iter()
+                    .flat_map(|f| hat(f))
+                    .eq(second.iter().cloned());
+                if matches {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::Step;
+    use super::boundary_word;
+    use super::outlines;
+    use super::tiles_plane;
+    use crate::shape::Point;
+    use crate::shape::Tile;
+
+    #[test]
+    fn boundary() {
+        let tile = Tile::from_str("X", "x");
+        assert_eq!(
+            boundary_word(&tile),
+            Some(vec![Step::Right, Step::Down, Step::Left, Step::Up])
+        );
+
+        let tile = Tile::from_str("L", "x\nxx");
+        assert_eq!(boundary_word(&tile).unwrap().len(), 8);
+
+        // disconnected
+        let tile = Tile::from_str("X", "x x");
+        assert_eq!(boundary_word(&tile), None);
+
+        // hole
+        let tile = Tile::from_str("O", "xxx\nx x\nxxx");
+        assert_eq!(boundary_word(&tile), None);
+    }
+
+    #[test]
+    fn tiling() {
+        // rectangles, L-tromino, S/T/L tetrominoes, P/X/V/W pentominoes tile by translation
+        for contents in [
+            "x",
+            "xxxx\nxxxx",
+            "x\nxx",
+            "xx\n xx",
+            "xxx\n x",
+            "x\nx\nxx",
+            "xx\nxx\nx",
+            " x\nxxx\n x",
+            "x\nx\nxxx",
+            "x\nxx\n xx",
+        ] {
+            assert!(tiles_plane(&Tile::from_str("T", contents)), "{}", contents);
+        }
+
+        // U and F pentominoes and the ring do not
+        for contents in ["x x\nxxx", " xx\nxx\n x", "xxx\nx x\nxxx"] {
+            assert!(!tiles_plane(&Tile::from_str("T", contents)), "{}", contents);
+        }
+    }
+
+    #[test]
+    fn outline() {
+        let points = |corners: &[(isize, isize)]| {
+            corners
+                .iter()
+                .map(|(x, y)| Point::new(*x, *y))
+                .collect::<Vec<_>>()
+        };
+        let l = Tile::from_str("L", "x\nxx");
+        assert_eq!(
+            outlines(&l),
+            [points(&[(0, 0), (1, 0), (1, 1), (2, 1), (2, 2), (0, 2)])]
+        );
+
+        // the hole runs the other way round
+        let ring = Tile::from_str("O", "xxx\nx x\nxxx");
+        assert_eq!(
+            outlines(&ring),
+            [
+                points(&[(0, 0), (3, 0), (3, 3), (0, 3)]),
+                points(&[(1, 1), (1, 2), (2, 2), (2, 1)]),
+            ]
+        );
+
+        // cells meeting at a corner are outlined apart
+        let diagonal = Tile::from_str("D", "x\n x");
+        assert_eq!(outlines(&diagonal).len(), 2);
+        assert!(outlines(&Tile::new("Empty")).is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+// Adapters over the exact cover engines, and a comparison between them.
+//
+// The external `algox` solver is the legacy backend, the in-crate `dlx` solver
+// the native one. Both enumerate the same matrix, so they must agree on the number
+// of solutions. Solution filters (like no crossroads) are not applied.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use algox::algox::IterativeSolver;
+use algox::algox::Matrix;
+
+use crate::dlx;
+use crate::polyomino::Game;
+
+// An exact cover engine yielding solutions as sorted solver row ids.
+pub trait Backend {
+    fn name(&self) -> &'static str;
+
+    fn next_solution(&mut self) -> Option<Vec<usize>>;
+}
+
+// The external dancing links solver.
+pub struct Legacy {
+    solver: IterativeSolver,
+}
+
+impl Legacy {
+    pub fn new(game: &Game) -> Self {
+        let (n_cols, rows) = game.matrix();
+        let mut m = Matrix::new(n_cols);
+        for index in 0..rows.len() {
+            m.add_row(&rows.to_vec(index));
+        }
+        Self {
+            solver: IterativeSolver::new(m),
+        }
+    }
+}
+
+impl Backend for Legacy {
+    fn name(&self) -> &'static str {
+        "algox"
+    }
+
+    fn next_solution(&mut self) -> Option<Vec<usize>> {
+        let mut solution = self.solver.next()?;
+        solution.sort();
+        Some(solution)
+    }
+}
+
+// The in-crate dancing links solver.
+pub struct Native {
+    solver: dlx::Solver,
+}
+
+impl Native {
+    pub fn new(game: &Game) -> Self {
+        let (n_cols, rows) = game.matrix();
+        let mut m = dlx::Matrix::new(n_cols);
+        for index in 0..rows.len() {
+            m.add_row(&rows.to_vec(index));
+        }
+        Self {
+            solver: dlx::Solver::new(m),
+        }
+    }
+}
+
+impl Backend for Native {
+    fn name(&self) -> &'static str {
+        "dlx"
+    }
+
+    fn next_solution(&mut self) -> Option<Vec<usize>> {
+        self.solver.next()
+    }
+}
+
+// Number of solutions a backend found and how long it took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub backend: &'static str,
+    pub count: usize,
+    pub elapsed: Duration,
+}
+
+impl Run {
+    // enumerate all solutions of the backend.
+    pub fn new(backend: &mut dyn Backend) -> Self {
+        let start = Instant::now();
+        let mut count = 0;
+        while backend.next_solution().is_some() {
+            count += 1;
+        }
+        Self {
+            backend: backend.name(),
+            count,
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub legacy: Run,
+    pub native: Run,
+}
+
+impl Comparison {
+    // whether both backends found the same number of solutions.
+    pub fn agree(&self) -> bool {
+        self.legacy.count == self.native.count
+    }
+}
+
+// time the legacy and the native backend on all solutions of the game.
+pub fn compare(game: &Game) -> Comparison {
+    Comparison {
+        legacy: Run::new(&mut Legacy::new(game)),
+        native: Run::new(&mut Native::new(game)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Backend;
+    use super::Legacy;
+    use super::Native;
+    use super::compare;
+    use crate::polyomino::Game;
+
+    const YAML: &str = "Board: |\n    xxx\n    xxx\nL: |\n    xx\n    x\nJ: |\n    xx\n     x\n";
+
+    #[test]
+    fn compare_backends() {
+        let game = Game::from_yaml(YAML).unwrap();
+        let comparison = compare(&game);
+
+        assert!(comparison.agree());
+        assert_eq!(comparison.legacy.backend, "algox");
+        assert_eq!(comparison.native.backend, "dlx");
+        assert_eq!(comparison.native.count, 4);
+
+        // the same solutions, in terms of solver row ids
+        let mut legacy = Legacy::new(&game);
+        let mut native = Native::new(&game);
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        while let Some(solution) = legacy.next_solution() {
+            a.push(solution);
+        }
+        while let Some(solution) = native.next_solution() {
+            b.push(solution);
+        }
+        a.sort();
+        b.sort();
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,177 @@
+// Solving many puzzles at once, like the candidates of a puzzle generator.
+//
+// Puzzles read together share one orientation cache, so a piece set used by
+// thousands of candidates has its orientations computed once. With the
+// `parallel` feature, and unless the options turn it off, the games are solved
+// on the thread pool: a `Game` is not `Send`, so every game hands its rows to
+// a worker and gets the solution back.
+// Games with solution filters (no crossroads, solution rules) or running the
+// in-crate search (for dead region pruning) need the game itself and are
+// solved on the calling thread.
+
+use crate::error::GameError;
+use crate::options::SolveOptions;
+use crate::orientation::OrientationCache;
+use crate::polyomino::Game;
+use crate::polyomino::Solution;
+
+// read the puzzles, sharing the orientations of their piece shapes. Every
+// text that is not a puzzle gets its error, the others are still read.
+pub fn read_batch(yamls: &[&str]) -> Vec<Result<Game, GameError>> {
+    let mut orientations = OrientationCache::new();
+    yamls
+        .iter()
+        .map(|yaml| Game::from_yaml_sharing(yaml, &mut orientations))
+        .collect()
+}
+
+// the first solution of every game with the options, in the order of the games.
+pub fn solve_batch(games: Vec<Game>, options: &SolveOptions) -> Vec<Option<Solution>> {
+    let mut games = games;
+    for game in games.iter_mut() {
+        game.set_options(options);
+    }
+    match options.parallel {
+        true => solve(games, options),
+        false => solve_each(games),
+    }
+}
+
+fn solve_each(games: Vec<Game>) -> Vec<Option<Solution>> {
+    games.into_iter().map(|mut game| game.solve()).collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn solve(games: Vec<Game>, _options: &SolveOptions) -> Vec<Option<Solution>> {
+    solve_each(games)
+}
+
+#[cfg(feature = "parallel")]
+fn solve(games: Vec<Game>, options: &SolveOptions) -> Vec<Option<Solution>> {
+    use rayon::prelude::*;
+
+    use crate::dancing_cells::DancingCells;
+    use crate::dlx::Matrix;
+    use crate::dlx::Solver;
+    use crate::exact_cover::ExactCover;
+    use crate::options::Algorithm;
+    use crate::rows::Rows;
+
+    // what a worker needs of a game
+    struct Task {
+        n_cols: usize,
+        rows: Rows,
+        ids: Vec<usize>,
+    }
+
+    let mut results: Vec<Option<Solution>> = vec![None; games.len()];
+    let mut tasks = Vec::new();
+    for (index, mut game) in games.into_iter().enumerate() {
+        if game.filters_solutions() || game.algorithm() == Algorithm::Search {
+            results[index] = game.solve();
+            continue;
+        }
+        let (n_cols, rows) = game.matrix();
+        let task = Task {
+            n_cols,
+            rows: rows.clone(),
+            ids: game.row_ids().to_vec(),
+        };
+        tasks.push((index, task));
+    }
+
+    let solved: Vec<(usize, Option<Solution>)> = tasks
+        .into_par_iter()
+        .map(|(index, task)| {
+            let solution = match options.algorithm {
+                // the same rows in the same order give the game's row ids
+                Algorithm::DancingLinks => {
+                    let mut m = Matrix::new(task.n_cols);
+                    for row in task.rows.iter() {
+                        let row: Vec<usize> = row.iter().map(|c| *c as usize).collect();
+                        m.add_row(&row);
+                    }
+                    Solver::with_heuristic(m, options.heuristic.clone()).next()
+                }
+                Algorithm::DancingCells => {
+                    let mut cells = Box::new(DancingCells::new(task.n_cols));
+                    for row in task.rows.iter() {
+                        let row: Vec<usize> = row.iter().map(|c| *c as usize).collect();
+                        cells.add_row(&row);
+                    }
+                    cells.solve_iter().next().map(|solution| {
+                        let mut solution: Solution =
+                            solution.iter().map(|r| task.ids[*r]).collect();
+                        solution.sort();
+                        solution
+                    })
+                }
+                Algorithm::Search => unreachable!("solved on the calling thread"),
+            };
+            (index, solution)
+        })
+        .collect();
+    for (index, solution) in solved {
+        results[index] = solution;
+    }
+    results
+}
+
+#[cfg(test)]
+mod test {
+    use super::read_batch;
+    use super::solve_batch;
+    use crate::error::GameError;
+    use crate::options::Algorithm;
+    use crate::options::SolveOptions;
+
+    const SOLVABLE: &str =
+        "Board: |\n    xxx\n    xxx\nL: |\n    xx\n    x\nJ: |\n    xx\n     x\n";
+    const UNSOLVABLE: &str = "Board: |\n    xxx\n    xxx\nL: |\n    xx\n    x\nI: |\n    xxx\n";
+
+    #[test]
+    fn batch() {
+        let games: Vec<_> = read_batch(&[SOLVABLE, UNSOLVABLE, SOLVABLE])
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        // the shapes of the later games were known from the first
+        assert!(games[0].metrics().orientations > 0);
+        assert_eq!(games[2].metrics().orientations, 0);
+
+        let mut expected = games.iter().map(|_| None).collect::<Vec<_>>();
+        for (i, yaml) in [SOLVABLE, UNSOLVABLE, SOLVABLE].iter().enumerate() {
+            expected[i] = crate::polyomino::Game::from_yaml(yaml).unwrap().solve();
+        }
+        let results = solve_batch(games, &SolveOptions::default());
+        assert_eq!(results, expected);
+        assert!(results[0].is_some());
+        assert!(results[1].is_none());
+
+        // filtered games are solved in place
+        let mut games: Vec<_> = read_batch(&[SOLVABLE, SOLVABLE])
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        games[1].set_no_crossroads(true);
+        let options = SolveOptions {
+            algorithm: Algorithm::DancingCells,
+            ..SolveOptions::default()
+        };
+        let results = solve_batch(games, &options);
+        assert!(
+            results
+                .iter()
+                .all(|r| r.as_ref().is_some_and(|s| s.len() == 2))
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        // a bad candidate does not stop the others, which still share shapes
+        let games = read_batch(&[SOLVABLE, "Board: |\n    xx\n", SOLVABLE]);
+        assert_eq!(games[1].as_ref().err(), Some(&GameError::NoPieces));
+        let last = games[2].as_ref().unwrap();
+        assert_eq!(last.metrics().orientations, 0);
+    }
+}
@@ -0,0 +1,271 @@
+// Bit mask representations for small problems.
+//
+// Boards whose bounding box has at most 128 cells are a single u128, with bit
+// `y * width + x` for the cell at (x, y). Placement tests become a shift and a
+// mask instead of point lookups. `BitCover` is an exact cover engine for up to
+// 128 columns that works on row masks instead of dancing links.
+
+use crate::exact_cover::ExactCover;
+use crate::shape::Tile;
+
+// Cells of a board anchored at the origin.
+pub struct Bitboard {
+    width: usize,
+    height: usize,
+    cells: u128,
+}
+
+// A tile shape at the origin, with its bounding box.
+pub struct Shape {
+    mask: u128,
+    width: usize,
+    height: usize,
+}
+
+impl Bitboard {
+    // None when the board is empty, has negative coordinates or does not fit in 128 bits.
+    pub fn new(board: &Tile) -> Option<Self> {
+        if board.points.is_empty() {
+            return None;
+        }
+        let size = board.size();
+        let width = size.width + usize::try_from(board.offset().x).ok()?;
+        let height = size.height + usize::try_from(board.offset().y).ok()?;
+        if width * height > 128 {
+            return None;
+        }
+        let mut bits = Self {
+            width,
+            height,
+            cells: 0,
+        };
+        bits.cells = bits.shape(board)?.mask;
+        Some(bits)
+    }
+
+    // mask of a tile with non-negative coordinates, within the board width.
+    pub fn shape(&self, tile: &Tile) -> Option<Shape> {
+        let mut shape = Shape {
+            mask: 0,
+            width: 0,
+            height: 0,
+        };
+        for p in &tile.points {
+            let x = usize::try_from(p.x).ok()?;
+            let y = usize::try_from(p.y).ok()?;
+            if x >= self.width || y >= self.height {
+                return None;
+            }
+            shape.mask |= 1 << (y * self.width + x);
+            shape.width = shape.width.max(x + 1);
+            shape.height = shape.height.max(y + 1);
+        }
+        Some(shape)
+    }
+
+    // whether the shape moved by (x, y) lies on the board.
+    pub fn contains(&self, shape: &Shape, x: usize, y: usize) -> bool {
+        if x + shape.width > self.width || y + shape.height > self.height {
+            return false;
+        }
+        let moved = shape.mask << (y * self.width + x);
+        moved & !self.cells == 0
+    }
+}
+
+// Exact cover over at most 128 columns, rows stored as bit masks.
+//
+// Branches on the lowest uncovered column, which for board cells numbered row
+// by row is the classic first empty cell strategy.
+pub struct BitCover {
+    n_cols: usize,
+    rows: Vec<u128>,
+    covered: u128,
+}
+
+impl BitCover {
+    // None for more than 128 columns.
+    pub fn new(n_cols: usize) -> Option<Self> {
+        if n_cols > 128 {
+            return None;
+        }
+        Some(Self {
+            n_cols,
+            rows: Vec::new(),
+            covered: 0,
+        })
+    }
+}
+
+impl ExactCover for BitCover {
+    fn add_row(&mut self, row: &[usize]) -> usize {
+        let mut mask = 0;
+        for c in row {
+            assert!(*c < self.n_cols, "column {} out of range", c);
+            mask |= 1u128 << c;
+        }
+        self.rows.push(mask);
+        self.rows.len() - 1
+    }
+
+    fn cover(&mut self, col: usize) {
+        self.covered |= 1 << col;
+    }
+
+    fn uncover(&mut self, col: usize) {
+        self.covered &= !(1 << col);
+    }
+
+    fn solve_iter(self: Box<Self>) -> Box<dyn Iterator<Item = Vec<usize>>> {
+        let all = match self.n_cols {
+            128 => u128::MAX,
+            n => (1 << n) - 1,
+        };
+        // rows are removed with the columns covered up front
+        let mut by_col: Vec<Vec<usize>> = vec![Vec::new(); self.n_cols];
+        for (r, mask) in self.rows.iter().enumerate() {
+            if mask & self.covered != 0 {
+                continue;
+            }
+            for (c, rows) in by_col.iter_mut().enumerate() {
+                if mask & (1 << c) != 0 {
+                    rows.push(r);
+                }
+            }
+        }
+        Box::new(BitSolutions {
+            rows: self.rows,
+            by_col,
+            all,
+            covered: self.covered,
+            stack: Vec::new(),
+            chosen: Vec::new(),
+            started: false,
+            done: false,
+        })
+    }
+}
+
+// a branching column and the index of its next candidate row.
+struct Frame {
+    col: usize,
+    next: usize,
+}
+
+struct BitSolutions {
+    rows: Vec<u128>,
+    by_col: Vec<Vec<usize>>,
+    all: u128,
+    covered: u128,
+    stack: Vec<Frame>,
+    chosen: Vec<usize>,
+    started: bool,
+    done: bool,
+}
+
+impl BitSolutions {
+    // place the next fitting candidate of the top frame, popping exhausted frames.
+    fn advance(&mut self) -> bool {
+        while !self.stack.is_empty() {
+            if self.chosen.len() == self.stack.len() {
+                let row = self.chosen.pop().unwrap();
+                self.covered ^= self.rows[row];
+            }
+            let frame = self.stack.last_mut().unwrap();
+            let candidates = &self.by_col[frame.col];
+            while frame.next < candidates.len() {
+                let row = candidates[frame.next];
+                frame.next += 1;
+                if self.rows[row] & self.covered == 0 {
+                    self.covered |= self.rows[row];
+                    self.chosen.push(row);
+                    return true;
+                }
+            }
+            self.stack.pop();
+        }
+        false
+    }
+}
+
+impl Iterator for BitSolutions {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        if self.started && !self.advance() {
+            self.done = true;
+            return None;
+        }
+        self.started = true;
+
+        loop {
+            let open = self.all & !self.covered;
+            if open == 0 {
+                let mut solution = self.chosen.clone();
+                solution.sort();
+                return Some(solution);
+            }
+            let col = open.trailing_zeros() as usize;
+            self.stack.push(Frame { col, next: 0 });
+            if !self.advance() {
+                self.done = true;
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BitCover;
+    use super::Bitboard;
+    use crate::exact_cover::ExactCover;
+    use crate::polyomino::Game;
+    use crate::shape::Tile;
+
+    #[test]
+    fn bitboard() {
+        let board = Bitboard::new(&Tile::from_str("Board", "xxx\nx x")).unwrap();
+        let domino = board.shape(&Tile::from_str("D", "xx")).unwrap();
+        let bar = board.shape(&Tile::from_str("I", "x\nx")).unwrap();
+
+        assert!(board.contains(&domino, 0, 0));
+        assert!(board.contains(&domino, 1, 0));
+        assert!(!board.contains(&domino, 0, 1));
+        assert!(!board.contains(&domino, 2, 0));
+        assert!(board.contains(&bar, 2, 0));
+        assert!(!board.contains(&bar, 1, 0));
+
+        let large = Tile::from_str("Board", &"x".repeat(129));
+        assert!(Bitboard::new(&large).is_none());
+    }
+
+    #[test]
+    fn cover() {
+        let mut m = BitCover::new(4).unwrap();
+        for row in [vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3], vec![0]] {
+            m.add_row(&row);
+        }
+        let solutions: Vec<Vec<usize>> = ExactCover::solve_iter(Box::new(m)).collect();
+        assert_eq!(solutions, vec![vec![0, 1], vec![2, 3]]);
+        assert!(BitCover::new(129).is_none());
+    }
+
+    #[test]
+    fn game() {
+        let yaml = "Board: |\n    xxxx\n    xxxx\nL: |\n    xxx\n    x\nJ: |\n    xxx\n      x\n";
+        let mut game = Game::from_yaml(yaml).unwrap();
+        let count = game.count_solutions();
+        assert!(count > 0);
+
+        game.set_backend(Box::new(BitCover::new(game.n_cols()).unwrap()));
+        let mut solutions = 0;
+        while game.solve().is_some() {
+            solutions += 1;
+        }
+        assert_eq!(solutions, count);
+    }
+}
@@ -0,0 +1,211 @@
+// Classic polyomino puzzles, ready to solve.
+//
+// Every challenge is a configured `Game` together with what is asked: any
+// tiling, the number of tilings, or the tiling enclosing the largest area
+// (farm puzzles, where the pieces form a fence around empty cells).
+
+use std::collections::VecDeque;
+
+use crate::error::GameError;
+use crate::grid::Cell;
+use crate::polyomino::Game;
+use crate::shape::PENTOMINOES;
+use crate::shape::Tile;
+
+// What a challenge asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    // any tiling
+    Exists,
+    // the number of tilings
+    Count,
+    // the tiling enclosing the most cells no piece covers
+    MaximizeEnclosedArea,
+}
+
+pub struct Challenge {
+    pub name: String,
+    pub game: Game,
+    pub objective: Objective,
+}
+
+// yaml of a puzzle from drawings of the board and the pieces.
+fn yaml(board: &str, pieces: &[(&str, &str)]) -> String {
+    let mut yaml = String::new();
+    for (name, drawing) in [("Board", board)].iter().chain(pieces) {
+        yaml.push_str(&format!("{}: |\n", name));
+        for line in drawing.lines() {
+            yaml.push_str(&format!("    {}\n", line));
+        }
+    }
+    yaml
+}
+
+// board drawing of a rectangle, with the cells in `holes` blocked.
+fn drawing(width: usize, height: usize, holes: &[(usize, usize)]) -> String {
+    let mut drawing = String::new();
+    for y in 0..height {
+        for x in 0..width {
+            drawing.push(if holes.contains(&(x, y)) { '#' } else { 'x' });
+        }
+        drawing.push('\n');
+    }
+    drawing
+}
+
+pub fn pentominoes() -> Vec<Tile> {
+    PENTOMINOES
+        .iter()
+        .map(|(name, drawing)| Tile::from_str(name, drawing))
+        .collect()
+}
+
+// Dana Scott's problem: the 8x8 board minus the 2x2 center, with the twelve
+// pentominoes.
+pub fn scott() -> Challenge {
+    let board = drawing(8, 8, &[(3, 3), (4, 3), (3, 4), (4, 4)]);
+    Challenge {
+        name: "8x8 minus 2x2 center, 12 pentominoes".to_string(),
+        game: Game::from_yaml(&yaml(&board, &PENTOMINOES)).expect("built-in puzzle"),
+        objective: Objective::Count,
+    }
+}
+
+// the 6x10, 5x12, 4x15 and 3x20 rectangles with the twelve pentominoes.
+pub fn pentomino_rectangles() -> Vec<Challenge> {
+    [(10, 6), (12, 5), (15, 4), (20, 3)]
+        .iter()
+        .map(|(width, height)| Challenge {
+            name: format!("{}x{} rectangle, 12 pentominoes", height, width),
+            game: Game::from_yaml(&yaml(&drawing(*width, *height, &[]), &PENTOMINOES))
+                .expect("built-in puzzle"),
+            objective: Objective::Count,
+        })
+        .collect()
+}
+
+// a farm puzzle: the pieces fill the fence cells ('x') of the layout, the
+// enclosed area is what the fence keeps away from the outside. Fails when the
+// layout or the pieces do not make a puzzle.
+pub fn farm(name: &str, layout: &str, pieces: &[(&str, &str)]) -> Result<Challenge, GameError> {
+    Ok(Challenge {
+        name: name.to_string(),
+        game: Game::from_yaml(&yaml(layout, pieces))?,
+        objective: Objective::MaximizeEnclosedArea,
+    })
+}
+
+// a farm puzzle fenced with the twelve pentominoes.
+pub fn pentomino_farm(layout: &str) -> Result<Challenge, GameError> {
+    farm("pentomino farm", layout, &PENTOMINOES)
+}
+
+impl Challenge {
+    // value of a solution for the objective, higher is better. Cells not
+    // covered by a piece are enclosed unless an orthogonal path of such cells
+    // leads out of the board.
+    pub fn score(&self, solution: &[usize]) -> usize {
+        if self.objective != Objective::MaximizeEnclosedArea {
+            return 1;
+        }
+        let grid = self.game.solution_grid(solution);
+        let open = |x: usize, y: usize| !matches!(grid.get(x, y), Cell::Piece(_));
+
+        let mut outside = vec![false; grid.cells.len()];
+        let mut queue = VecDeque::new();
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                let border = x == 0 || y == 0 || x + 1 == grid.width || y + 1 == grid.height;
+                if border && open(x, y) {
+                    outside[y * grid.width + x] = true;
+                    queue.push_back((x, y));
+                }
+            }
+        }
+        while let Some((x, y)) = queue.pop_front() {
+            let neighbors = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for (nx, ny) in neighbors {
+                if nx < grid.width && ny < grid.height && open(nx, ny) {
+                    let i = ny * grid.width + nx;
+                    if !outside[i] {
+                        outside[i] = true;
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+        }
+
+        (0..grid.cells.len())
+            .filter(|i| open(i % grid.width, i / grid.width) && !outside[*i])
+            .count()
+    }
+
+    // the best solution with its score, the first one unless the objective
+    // asks for a maximum.
+    pub fn best(&mut self) -> Option<(Vec<usize>, usize)> {
+        let mut best: Option<(Vec<usize>, usize)> = None;
+        while let Some(solution) = self.game.solve() {
+            let score = self.score(&solution);
+            if best.as_ref().is_none_or(|(_, s)| score > *s) {
+                best = Some((solution, score));
+            }
+            if self.objective != Objective::MaximizeEnclosedArea {
+                break;
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Objective;
+    use super::farm;
+    use super::pentomino_rectangles;
+    use super::pentominoes;
+    use super::scott;
+
+    #[test]
+    fn classics() {
+        let tiles = pentominoes();
+        assert_eq!(tiles.len(), 12);
+        assert!(tiles.iter().all(|t| t.len() == 5 && t.is_connected()));
+
+        let challenge = scott();
+        assert_eq!(challenge.objective, Objective::Count);
+        assert_eq!(challenge.game.len(), 60);
+        assert!(challenge.game.warnings().is_empty());
+
+        // the 3x20 rectangle has two tilings, up to symmetry
+        let mut rectangles = pentomino_rectangles();
+        assert_eq!(rectangles.len(), 4);
+        let narrow = rectangles.pop().unwrap();
+        assert_eq!(narrow.game.count_solutions(), 8);
+    }
+
+    #[test]
+    fn farms() {
+        let corners = [
+            ("A", "xx\nx"),
+            ("B", "xx\nx"),
+            ("C", "xx\nx"),
+            ("D", "xx\nx"),
+        ];
+        let mut challenge = farm("square", "xxxx\nx..x\nx..x\nxxxx", &corners).unwrap();
+        let (solution, area) = challenge.best().unwrap();
+        assert_eq!(solution.len(), 4);
+        assert_eq!(area, 4);
+
+        // a gap in the fence lets everything out
+        let mut challenge = farm("open", "xx\nx\n\n   x\n  xx", &corners[..2]).unwrap();
+        let (_, area) = challenge.best().unwrap();
+        assert_eq!(area, 0);
+
+        assert!(farm("empty", "..", &corners).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// Conformance checks for the JS-facing API.
+//
+// Runs every `JsGame` method on an embedded puzzle and compares the results
+// against expected outputs, so a deployed wasm bundle can be verified quickly.
+use crate::JsGame;
+
+const PUZZLE: &str = "
+---
+Board: |
+    xxx
+    xxx
+A: |
+    xx
+    x
+B: |
+    xx
+    x
+";
+
+// record a failed check.
+fn check(failures: &mut Vec<String>, name: &str, ok: bool) {
+    if !ok {
+        failures.push(format!("{} failed", name));
+    }
+}
+
+// names of the failed checks, empty when everything conforms.
+pub fn run() -> Vec<String> {
+    let mut failures = Vec::new();
+
+    let json = r#"{ "board": ["xxx", "xxx"], "tiles": { "A": ["xx", "x"], "B": ["xx", "x"] } }"#;
+    let mut from_json = JsGame::fromJson(json).unwrap();
+    check(&mut failures, "fromJson", from_json.solve() == 1);
+
+    let mut game = JsGame::fromYaml(PUZZLE).unwrap();
+    check(&mut failures, "warnings", game.warnings().is_empty());
+    check(&mut failures, "isParityFeasible", game.isParityFeasible());
+    check(
+        &mut failures,
+        "unsolvableReason",
+        game.unsolvableReason().is_none(),
+    );
+    check(&mut failures, "boardSizes", game.boardSizes() == [6]);
+    check(&mut failures, "solve", game.solve() == 1);
+    check(
+        &mut failures,
+        "solution",
+        game.solution(0) == [0, 0, 1, 0, 1, 1],
+    );
+    check(&mut failures, "gridSize", game.gridSize() == [3, 2]);
+    check(
+        &mut failures,
+        "labelPositions",
+        game.labelPositions(0) == [0.0, 0.5, 0.5, 1.0, 2.5, 1.5],
+    );
+    let outlines = game.pieceOutlines(0);
+    check(
+        &mut failures,
+        "pieceOutlines",
+        outlines.len() == 60 && outlines[..5] == [0, 0, 0, 2, 0],
+    );
+    check(&mut failures, "cellIndex", game.cellIndex(2, 1) == Some(5));
+    check(&mut failures, "cellIndex", game.cellIndex(3, 0).is_none());
+    check(&mut failures, "cellCoords", game.cellCoords(4) == [1, 1]);
+    check(&mut failures, "cellWeights", game.cellWeights() == [1; 6]);
+    check(&mut failures, "solutionWeight", game.solutionWeight(0) == 6);
+    let memory = game.memoryUsage();
+    check(
+        &mut failures,
+        "memoryUsage",
+        memory.len() == 5 && memory[3] > 0 && memory[4] == memory[..4].iter().sum(),
+    );
+    check(
+        &mut failures,
+        "solutionGrid",
+        game.solutionGrid(0) == [0, 0, 1, 0, 1, 1],
+    );
+    check(&mut failures, "solveAll", game.solveAll() == 4);
+    check(
+        &mut failures,
+        "solution",
+        game.solution(3) == [1, 0, 0, 1, 1, 0],
+    );
+
+    let dot = game.solutionGraph(2, "dot");
+    check(
+        &mut failures,
+        "solutionGraph",
+        dot.matches(" -- ").count() == 6,
+    );
+    let graphml = game.solutionGraph(2, "graphml");
+    check(
+        &mut failures,
+        "solutionGraph",
+        graphml.matches("<edge ").count() == 6,
+    );
+
+    check(
+        &mut failures,
+        "tileableRectangles",
+        game.tileableRectangles(100) == [2, 3],
+    );
+
+    let estimate = game.estimateSolutionCount(10);
+    check(
+        &mut failures,
+        "estimateSolutionCount",
+        estimate.len() == 2 && estimate[0] > 0.0 && estimate[1] >= 0.0,
+    );
+
+    let svg = game.nestingSvg(3, 4, 1, 1, 10.0);
+    check(
+        &mut failures,
+        "nestingSvg",
+        svg.is_some_and(|svg| svg.matches("<path ").count() == 2),
+    );
+    check(
+        &mut failures,
+        "nestingSvg",
+        game.nestingSvg(3, 3, 1, 1, 10.0).is_none(),
+    );
+
+    let hint = game.hint("decisive");
+    check(&mut failures, "hint", hint.len() == 4 && hint[0] < 2);
+
+    let matrix = JsGame::fromYaml(PUZZLE).unwrap().matrixBytes();
+    let mut game = JsGame::fromYamlWithMatrix(PUZZLE, &matrix).unwrap();
+    check(
+        &mut failures,
+        "fromYamlWithMatrix",
+        !matrix.is_empty() && game.solveAll() == 4,
+    );
+
+    let mut game = JsGame::fromYaml(PUZZLE).unwrap();
+    game.solve();
+    game.setCountOnly(true);
+    check(
+        &mut failures,
+        "setCountOnly",
+        game.solveAll() == 4 && game.solution(0).len() == 6,
+    );
+
+    let game = JsGame::fromYaml("Board: |\n    xxx\nA: |\n    xxxx\n").unwrap();
+    check(
+        &mut failures,
+        "unsolvableReason",
+        game.unsolvableReason().is_some(),
+    );
+
+    // optional cells come after the others
+    let mut game = JsGame::fromYaml("Board: |\n    oxx\nA: |\n    xx\n").unwrap();
+    game.solve();
+    check(
+        &mut failures,
+        "solution",
+        game.solution(0) == [0, 0, -3] && game.solutionGrid(0) == [-3, 0, 0],
+    );
+
+    let mut game = JsGame::fromYaml(PUZZLE).unwrap();
+    game.setNoCrossroads(true);
+    check(&mut failures, "setNoCrossroads", game.solveAll() == 4);
+
+    let mut game = JsGame::fromYaml(PUZZLE).unwrap();
+    game.setDeadRegionPruning(true);
+    check(&mut failures, "setDeadRegionPruning", game.solveAll() == 4);
+
+    failures
+}
+
+#[cfg(test)]
+mod test {
+    use super::run;
+
+    #[test]
+    fn conformance() {
+        assert_eq!(run(), Vec::<String>::new());
+    }
+}
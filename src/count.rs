@@ -0,0 +1,176 @@
+// Counting exact covers with a transposition table.
+//
+// The search always fills the lowest uncovered column. With board cells
+// numbered row by row, this sweeps a frontier along the board, and different
+// ways of reaching the same covered set (like the tilings of the start of a
+// strip) continue identically. Each covered set is counted once: it is looked
+// up by its Zobrist hash, an xor of random keys of the covered columns, and
+// compared in full so hash collisions cannot corrupt the count.
+//
+// The search keeps its levels on an explicit stack rather than recursing, so
+// boards with hundreds of pieces do not overflow the (small, on wasm) stack.
+
+use std::collections::HashMap;
+
+use crate::estimate::Rng;
+use crate::rows::Rows;
+
+// a state being counted: the column it fills, the next of its rows to try,
+// the row placed for the state below, and the count so far.
+struct Frame {
+    col: usize,
+    next: usize,
+    row: Option<usize>,
+    total: u128,
+}
+
+struct Memo<'a> {
+    n_cols: usize,
+    rows: &'a Rows,
+    by_col: Vec<Vec<usize>>,
+    keys: Vec<u64>,
+    // covered columns as bits, and their hash
+    covered: Vec<u64>,
+    hash: u64,
+    table: HashMap<u64, Vec<(Vec<u64>, u128)>>,
+}
+
+impl Memo<'_> {
+    fn is_covered(&self, col: usize) -> bool {
+        self.covered[col / 64] & (1 << (col % 64)) != 0
+    }
+
+    fn toggle(&mut self, row: usize) {
+        for c in self.rows.get(row) {
+            let c = *c as usize;
+            self.covered[c / 64] ^= 1 << (c % 64);
+            self.hash ^= self.keys[c];
+        }
+    }
+
+    // the count of the current state when known without searching, else the
+    // column to fill.
+    fn lookup(&self) -> Result<u128, usize> {
+        let col = match (0..self.n_cols).find(|c| !self.is_covered(*c)) {
+            Some(col) => col,
+            None => return Ok(1),
+        };
+        let known = self
+            .table
+            .get(&self.hash)
+            .and_then(|states| states.iter().find(|(s, _)| *s == self.covered));
+        match known {
+            Some((_, count)) => Ok(*count),
+            None => Err(col),
+        }
+    }
+
+    fn count(&mut self) -> u128 {
+        let mut stack: Vec<Frame> = Vec::new();
+        // count of the state just left, None after entering a new one
+        let mut done: Option<u128> = None;
+        loop {
+            if done.is_none() {
+                match self.lookup() {
+                    Ok(count) => done = Some(count),
+                    Err(col) => stack.push(Frame {
+                        col,
+                        next: 0,
+                        row: None,
+                        total: 0,
+                    }),
+                }
+            }
+            let Some(frame) = stack.last_mut() else {
+                return done.unwrap_or(0);
+            };
+            if let Some(row) = frame.row.take() {
+                frame.total += done.take().unwrap_or(0);
+                self.toggle(row);
+            }
+
+            let rows = &self.by_col[frame.col];
+            let free = (frame.next..rows.len()).find(|k| {
+                self.rows
+                    .get(rows[*k])
+                    .iter()
+                    .all(|c| !self.is_covered(*c as usize))
+            });
+            match free {
+                Some(k) => {
+                    let row = rows[k];
+                    frame.next = k + 1;
+                    frame.row = Some(row);
+                    self.toggle(row);
+                    done = None;
+                }
+                None => {
+                    let total = frame.total;
+                    stack.pop();
+                    self.table
+                        .entry(self.hash)
+                        .or_default()
+                        .push((self.covered.clone(), total));
+                    done = Some(total);
+                }
+            }
+        }
+    }
+}
+
+// number of exact covers of the `n_cols` columns by the rows.
+pub fn count(n_cols: usize, rows: &Rows) -> u128 {
+    let mut by_col: Vec<Vec<usize>> = vec![Vec::new(); n_cols];
+    for (r, row) in rows.iter().enumerate() {
+        for c in row {
+            by_col[*c as usize].push(r);
+        }
+    }
+    let mut rng = Rng::new(0x2B0B);
+    let mut memo = Memo {
+        n_cols,
+        rows,
+        by_col,
+        keys: (0..n_cols).map(|_| rng.next_u64()).collect(),
+        covered: vec![0; n_cols.div_ceil(64)],
+        hash: 0,
+        table: HashMap::new(),
+    };
+    memo.count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::count;
+    use crate::rows::Rows;
+
+    #[test]
+    fn strips() {
+        // tilings of a 1xn strip by monominoes and dominoes are Fibonacci numbers
+        let n = 90;
+        let mut rows = Rows::new();
+        for i in 0..n {
+            rows.push(&[i]);
+            if i + 1 < n {
+                rows.push(&[i, i + 1]);
+            }
+        }
+        assert_eq!(count(n, &rows), 4660046610375530309);
+
+        let mut rows = Rows::new();
+        rows.push(&[0, 1]);
+        rows.push(&[0]);
+        assert_eq!(count(3, &rows), 0);
+    }
+
+    #[test]
+    fn deep() {
+        // thousands of levels, one per monomino
+        let n = 3000;
+        let mut rows = Rows::new();
+        for i in 0..n {
+            rows.push(&[i]);
+        }
+        assert_eq!(count(n, &rows), 1);
+    }
+}
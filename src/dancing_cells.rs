@@ -0,0 +1,296 @@
+// Exact cover with Knuth's dancing cells: sparse sets instead of linked nodes.
+//
+// Every column keeps the rows covering it in its own segment of one flat array,
+// the first `size` of them still available. Removing a row swaps it behind the
+// available ones, so undoing a removal only has to grow `size` again: nothing is
+// relinked, and all state lives in a handful of flat vectors.
+
+use crate::exact_cover::ExactCover;
+
+pub struct DancingCells {
+    n_cols: usize,
+    // rows as (0-based) columns, in order of addition
+    rows: Vec<Vec<usize>>,
+    // columns removed with `cover` before solving
+    removed: Vec<usize>,
+}
+
+impl DancingCells {
+    pub fn new(n_cols: usize) -> Self {
+        Self {
+            n_cols,
+            rows: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
+
+impl ExactCover for DancingCells {
+    fn add_row(&mut self, row: &[usize]) -> usize {
+        self.rows.push(row.to_vec());
+        self.rows.len() - 1
+    }
+
+    fn cover(&mut self, col: usize) {
+        self.removed.push(col);
+    }
+
+    fn uncover(&mut self, col: usize) {
+        if let Some(i) = self.removed.iter().rposition(|c| *c == col) {
+            self.removed.remove(i);
+        }
+    }
+
+    fn solve_iter(self: Box<Self>) -> Box<dyn Iterator<Item = Vec<usize>>> {
+        let mut cells = Cells::new(self.n_cols, &self.rows);
+        for col in self.removed {
+            if cells.is_active(col) {
+                cells.cover(col);
+            }
+        }
+        Box::new(cells)
+    }
+}
+
+// The sparse sets, and the search over them as an iterator of solutions.
+struct Cells {
+    // first node of each row, and one past the last node of the last row
+    starts: Vec<usize>,
+    // column, row and position in the column's set of each node
+    col: Vec<usize>,
+    row: Vec<usize>,
+    pos: Vec<usize>,
+    // the column sets (node ids), where each column's segment begins, and how
+    // many of its nodes are available
+    set: Vec<usize>,
+    base: Vec<usize>,
+    size: Vec<usize>,
+    // the uncovered columns are the first `n_active` of `active`, `slot` is
+    // the position of a column in it
+    active: Vec<usize>,
+    slot: Vec<usize>,
+    n_active: usize,
+    // the covered column and the position of the chosen row in its set, per level
+    stack: Vec<(usize, usize)>,
+    started: bool,
+    done: bool,
+}
+
+impl Cells {
+    fn new(n_cols: usize, rows: &[Vec<usize>]) -> Self {
+        let mut starts = Vec::with_capacity(rows.len() + 1);
+        let mut col = Vec::new();
+        let mut row = Vec::new();
+        let mut counts = vec![0; n_cols];
+        for (r, cols) in rows.iter().enumerate() {
+            starts.push(col.len());
+            for c in cols {
+                col.push(*c);
+                row.push(r);
+                counts[*c] += 1;
+            }
+        }
+        starts.push(col.len());
+
+        let mut base = Vec::with_capacity(n_cols);
+        let mut next = 0;
+        for count in &counts {
+            base.push(next);
+            next += count;
+        }
+        let mut set = vec![0; col.len()];
+        let mut pos = vec![0; col.len()];
+        let mut size = vec![0; n_cols];
+        for (node, c) in col.iter().enumerate() {
+            pos[node] = base[*c] + size[*c];
+            set[pos[node]] = node;
+            size[*c] += 1;
+        }
+
+        Self {
+            starts,
+            col,
+            row,
+            pos,
+            set,
+            base,
+            size,
+            active: (0..n_cols).collect(),
+            slot: (0..n_cols).collect(),
+            n_active: n_cols,
+            stack: Vec::new(),
+            started: false,
+            done: false,
+        }
+    }
+
+    fn is_active(&self, c: usize) -> bool {
+        self.slot[c] < self.n_active
+    }
+
+    // the other nodes of the row of `node`.
+    fn others(&self, node: usize) -> impl DoubleEndedIterator<Item = usize> + use<> {
+        let r = self.row[node];
+        (self.starts[r]..self.starts[r + 1]).filter(move |q| *q != node)
+    }
+
+    // take the row of `node` out of the sets of its other columns.
+    fn hide(&mut self, node: usize) {
+        for q in self.others(node) {
+            let c = self.col[q];
+            let last = self.base[c] + self.size[c] - 1;
+            let other = self.set[last];
+            self.set.swap(self.pos[q], last);
+            self.pos[other] = self.pos[q];
+            self.pos[q] = last;
+            self.size[c] -= 1;
+        }
+    }
+
+    // undo `hide`: the removed nodes sit right behind the available ones.
+    fn unhide(&mut self, node: usize) {
+        for q in self.others(node).rev() {
+            self.size[self.col[q]] += 1;
+        }
+    }
+
+    fn cover(&mut self, c: usize) {
+        for i in self.base[c]..self.base[c] + self.size[c] {
+            self.hide(self.set[i]);
+        }
+        let last = self.active[self.n_active - 1];
+        self.active.swap(self.slot[c], self.n_active - 1);
+        self.slot[last] = self.slot[c];
+        self.slot[c] = self.n_active - 1;
+        self.n_active -= 1;
+    }
+
+    fn uncover(&mut self, c: usize) {
+        self.n_active += 1;
+        for i in (self.base[c]..self.base[c] + self.size[c]).rev() {
+            self.unhide(self.set[i]);
+        }
+    }
+
+    // cover the other columns of the row of `node`.
+    fn select(&mut self, node: usize) {
+        for q in self.others(node) {
+            self.cover(self.col[q]);
+        }
+    }
+
+    fn unselect(&mut self, node: usize) {
+        for q in self.others(node).rev() {
+            self.uncover(self.col[q]);
+        }
+    }
+
+    // the uncovered column with the fewest available rows.
+    fn choose(&self) -> usize {
+        self.active[..self.n_active]
+            .iter()
+            .copied()
+            .min_by_key(|c| self.size[*c])
+            .unwrap()
+    }
+
+    // go deeper until a solution is found (true) or a column has no rows left.
+    fn descend(&mut self) -> bool {
+        loop {
+            if self.n_active == 0 {
+                return true;
+            }
+            let c = self.choose();
+            if self.size[c] == 0 {
+                return false;
+            }
+            self.cover(c);
+            self.stack.push((c, 0));
+            self.select(self.set[self.base[c]]);
+        }
+    }
+
+    // move to the next row at the deepest level that has one left, returns
+    // false when the search is exhausted.
+    fn advance(&mut self) -> bool {
+        while let Some((c, i)) = self.stack.pop() {
+            self.unselect(self.set[self.base[c] + i]);
+            if i + 1 < self.size[c] {
+                self.stack.push((c, i + 1));
+                self.select(self.set[self.base[c] + i + 1]);
+                return true;
+            }
+            self.uncover(c);
+        }
+        false
+    }
+}
+
+impl Iterator for Cells {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        if self.started && !self.advance() {
+            self.done = true;
+            return None;
+        }
+        self.started = true;
+        while !self.descend() {
+            if !self.advance() {
+                self.done = true;
+                return None;
+            }
+        }
+        let mut solution: Vec<usize> = self
+            .stack
+            .iter()
+            .map(|(c, i)| self.row[self.set[self.base[*c] + i]])
+            .collect();
+        solution.sort();
+        Some(solution)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DancingCells;
+    use crate::exact_cover::ExactCover;
+
+    fn solve(n_cols: usize, rows: &[Vec<usize>], removed: &[usize]) -> Vec<Vec<usize>> {
+        let mut cells = Box::new(DancingCells::new(n_cols));
+        for row in rows {
+            cells.add_row(row);
+        }
+        for col in removed {
+            cells.cover(*col);
+        }
+        let mut solutions: Vec<Vec<usize>> = cells.solve_iter().collect();
+        solutions.sort();
+        solutions
+    }
+
+    #[test]
+    fn exact_covers() {
+        // Knuth's example
+        let rows = vec![
+            vec![2, 4],
+            vec![0, 3, 6],
+            vec![1, 2, 5],
+            vec![0, 3, 5],
+            vec![1, 6],
+            vec![3, 4, 6],
+        ];
+        assert_eq!(solve(7, &rows, &[]), vec![vec![0, 3, 4]]);
+
+        let rows = vec![vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3], vec![0]];
+        assert_eq!(solve(4, &rows, &[]), vec![vec![0, 1], vec![2, 3]]);
+        // without column 0 only rows avoiding it remain
+        assert_eq!(solve(4, &rows, &[0]), Vec::<Vec<usize>>::new());
+        assert_eq!(solve(4, &rows[1..], &[0, 1]), vec![vec![0]]);
+        assert_eq!(solve(1, &[], &[]), Vec::<Vec<usize>>::new());
+        assert_eq!(solve(0, &[], &[]), vec![Vec::<usize>::new()]);
+    }
+}
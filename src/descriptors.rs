@@ -0,0 +1,140 @@
+// Geometric measures of a shape, for ranking and filtering pieces.
+//
+// All of them only depend on the shape, not on its position or orientation,
+// except `width` and `height`, which follow the shape as given.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::shape::Point;
+use crate::shape::Tile;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Descriptors {
+    // number of cells, and of cell edges on the outline
+    pub area: usize,
+    pub perimeter: usize,
+    // size of the bounding box, and its longer side over its shorter side
+    pub width: usize,
+    pub height: usize,
+    pub aspect_ratio: f64,
+    // share of the bounding box covered by the cells
+    pub fill: f64,
+    // share of the orthogonal hull (every row and column filled in between
+    // its ends) covered by the cells, 1 for a shape without notches
+    pub convexity: f64,
+    // corners of the outline turning outward and inward
+    pub corners: usize,
+    pub concavities: usize,
+    // enclosed empty regions
+    pub holes: usize,
+    // number of rotations and reflections mapping the shape onto itself
+    pub symmetry: usize,
+}
+
+// the descriptors of a tile, None for an empty one.
+pub fn describe(tile: &Tile) -> Option<Descriptors> {
+    if tile.points.is_empty() {
+        return None;
+    }
+    let size = tile.size();
+    let (long, short) = match size.width >= size.height {
+        true => (size.width, size.height),
+        false => (size.height, size.width),
+    };
+    let (corners, concavities) = corners(tile);
+    Some(Descriptors {
+        area: tile.len(),
+        perimeter: tile.perimeter(),
+        width: size.width,
+        height: size.height,
+        aspect_ratio: long as f64 / short as f64,
+        fill: tile.len() as f64 / (size.width * size.height) as f64,
+        convexity: tile.len() as f64 / hull_area(tile) as f64,
+        corners,
+        concavities,
+        holes: tile.holes().len(),
+        symmetry: tile.symmetries().order(),
+    })
+}
+
+// outward and inward corners, from the cells around every grid vertex: one
+// cell makes an outward corner, three an inward one, and two touching only
+// at the vertex two outward ones.
+fn corners(tile: &Tile) -> (usize, usize) {
+    let points: HashSet<&Point> = tile.points.iter().collect();
+    let (offset, size) = tile.bounding_box();
+    let (mut outward, mut inward) = (0, 0);
+    for y in offset.y..=offset.y + size.height as isize {
+        for x in offset.x..=offset.x + size.width as isize {
+            let around = [(x - 1, y - 1), (x, y - 1), (x - 1, y), (x, y)]
+                .map(|(x, y)| points.contains(&Point::new(x, y)));
+            match around.iter().filter(|c| **c).count() {
+                1 => outward += 1,
+                2 if around[0] == around[3] => outward += 2,
+                3 => inward += 1,
+                _ => {}
+            }
+        }
+    }
+    (outward, inward)
+}
+
+// cells of the orthogonal hull: the gaps between the ends of every row and
+// column filled in, over and over until none are left.
+fn hull_area(tile: &Tile) -> usize {
+    let mut cells: HashSet<(isize, isize)> = tile.points.iter().map(|p| (p.x, p.y)).collect();
+    loop {
+        // the ends of every row (false, y) and column (true, x)
+        let mut ends: HashMap<(bool, isize), (isize, isize)> = HashMap::new();
+        for (x, y) in cells.iter() {
+            for (line, v) in [((false, *y), *x), ((true, *x), *y)] {
+                let end = ends.entry(line).or_insert((v, v));
+                *end = (end.0.min(v), end.1.max(v));
+            }
+        }
+        let before = cells.len();
+        for ((column, line), (first, last)) in ends {
+            for v in first..=last {
+                cells.insert(match column {
+                    true => (line, v),
+                    false => (v, line),
+                });
+            }
+        }
+        if cells.len() == before {
+            return before;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::describe;
+    use crate::shape::Tile;
+
+    #[test]
+    fn measures() {
+        let l = describe(&Tile::from_str("L", "x\nxx")).unwrap();
+        assert_eq!((l.area, l.perimeter, l.width, l.height), (3, 8, 2, 2));
+        assert_eq!((l.aspect_ratio, l.fill, l.convexity), (1.0, 0.75, 1.0));
+        assert_eq!(
+            (l.corners, l.concavities, l.holes, l.symmetry),
+            (5, 1, 0, 2)
+        );
+
+        // the notch of the U is filled in by its hull
+        let u = describe(&Tile::from_str("U", "x x\nxxx")).unwrap();
+        assert_eq!(u.convexity, 5.0 / 6.0);
+        assert_eq!((u.corners, u.concavities), (6, 2));
+
+        // the ring has a hole, whose corners turn inward
+        let ring = describe(&Tile::from_str("O", "xxx\nx x\nxxx")).unwrap();
+        assert_eq!((ring.corners, ring.concavities, ring.holes), (4, 4, 1));
+        assert_eq!(ring.symmetry, 8);
+
+        let bar = describe(&Tile::from_str("I", "x\nx\nx\nx")).unwrap();
+        assert_eq!((bar.width, bar.height, bar.aspect_ratio), (1, 4, 4.0));
+        assert!(describe(&Tile::new("Empty")).is_none());
+    }
+}
@@ -0,0 +1,123 @@
+use crate::shape::Point;
+use crate::shape::Tile;
+use std::collections::HashSet;
+use std::fmt;
+
+// Differences between two versions of a puzzle.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Diff {
+    // board cells only in the new version.
+    pub added_cells: Vec<Point>,
+    // board cells only in the old version.
+    pub removed_cells: Vec<Point>,
+    // piece names only in the new version.
+    pub added_pieces: Vec<String>,
+    // piece names only in the old version.
+    pub removed_pieces: Vec<String>,
+    // pieces present in both versions with a different shape.
+    pub changed_pieces: Vec<String>,
+}
+
+// points of a tile in a comparable order.
+fn sorted(tile: &Tile) -> Vec<Point> {
+    let mut points = tile.points.to_vec();
+    points.sort();
+    points
+}
+
+impl Diff {
+    pub fn new(board: &Tile, tiles: &[Tile], other_board: &Tile, other_tiles: &[Tile]) -> Self {
+        let old: HashSet<&Point> = board.points.iter().collect();
+        let new: HashSet<&Point> = other_board.points.iter().collect();
+
+        let mut added_cells: Vec<Point> = new.difference(&old).map(|p| (*p).clone()).collect();
+        let mut removed_cells: Vec<Point> = old.difference(&new).map(|p| (*p).clone()).collect();
+        added_cells.sort();
+        removed_cells.sort();
+
+        let mut diff = Diff {
+            added_cells,
+            removed_cells,
+            ..Default::default()
+        };
+
+        for tile in tiles {
+            match other_tiles.iter().find(|t| t.name == tile.name) {
+                None => diff.removed_pieces.push(tile.name.clone()),
+                Some(other) => {
+                    if sorted(tile) != sorted(other) {
+                        diff.changed_pieces.push(tile.name.clone());
+                    }
+                }
+            }
+        }
+        for tile in other_tiles {
+            if !tiles.iter().any(|t| t.name == tile.name) {
+                diff.added_pieces.push(tile.name.clone());
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Diff::default()
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for p in &self.added_cells {
+            writeln!(f, "+ cell ({}, {})", p.x, p.y)?;
+        }
+        for p in &self.removed_cells {
+            writeln!(f, "- cell ({}, {})", p.x, p.y)?;
+        }
+        for name in &self.added_pieces {
+            writeln!(f, "+ piece {}", name)?;
+        }
+        for name in &self.removed_pieces {
+            writeln!(f, "- piece {}", name)?;
+        }
+        for name in &self.changed_pieces {
+            writeln!(f, "~ piece {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Diff;
+    use crate::shape::Point;
+    use crate::shape::Tile;
+
+    #[test]
+    fn basics() {
+        let board = Tile::from_str("Board", "xxx\nxxx");
+        let tiles = vec![
+            Tile::from_str("A", "xx\nx"),
+            Tile::from_str("B", "xx\nx"),
+            Tile::from_str("C", "x"),
+        ];
+
+        let other_board = Tile::from_str("Board", "xxxx\nxx");
+        let other_tiles = vec![
+            Tile::from_str("A", "xx\nx"),
+            Tile::from_str("B", "xxx"),
+            Tile::from_str("D", "x"),
+        ];
+
+        let diff = Diff::new(&board, &tiles, &other_board, &other_tiles);
+        assert_eq!(diff.added_cells, vec![Point::new(3, 0)]);
+        assert_eq!(diff.removed_cells, vec![Point::new(2, 1)]);
+        assert_eq!(diff.added_pieces, vec!["D"]);
+        assert_eq!(diff.removed_pieces, vec!["C"]);
+        assert_eq!(diff.changed_pieces, vec!["B"]);
+        assert_eq!(
+            diff.to_string(),
+            "+ cell (3, 0)\n- cell (2, 1)\n+ piece D\n- piece C\n~ piece B\n"
+        );
+
+        assert!(Diff::new(&board, &tiles, &board, &tiles).is_empty());
+    }
+}
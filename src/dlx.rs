@@ -0,0 +1,612 @@
+// Knuth's Algorithm X with dancing links.
+//
+// Nodes live in flat arrays: node 0 is the root, nodes 1..=n_cols the column
+// headers and the row nodes follow in insertion order. A row is identified by
+// the id of its first node, which keeps ids stable for a given matrix.
+//
+// Primary columns must be covered exactly once. Secondary columns are covered
+// at most once, they are never chosen for branching.
+//
+// A primary column can instead be given a multiplicity, the number of rows
+// covering it, for interchangeable copies of a piece. Such columns are not
+// branched on either while they need more than one row: their rows are taken
+// through the other columns they cover, so every set of rows is found once,
+// and every row of such a column must cover a column without one.
+
+use crate::exact_cover::ExactCover;
+use crate::search::Trace;
+use crate::search::TraceEvent;
+
+// Counters for the work done by the solver.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stats {
+    // rows added to the partial solution
+    pub nodes: usize,
+    // nodes unlinked while covering columns
+    pub updates: usize,
+    // columns covered and uncovered
+    pub covers: usize,
+    pub uncovers: usize,
+    pub solutions: usize,
+}
+
+// How the solver picks the column to branch on.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum Heuristic {
+    // the first uncovered primary column
+    First,
+    // the column with the fewest rows left
+    #[default]
+    MinimumRemaining,
+    // the column with the fewest rows left per unit of weight, with a weight
+    // for each (0-based) column. Heavy columns are chosen earlier.
+    Weighted(Vec<f64>),
+    // the first uncovered of the given (0-based) columns, then the first
+    // uncovered primary column
+    Order(Vec<usize>),
+}
+
+#[derive(Clone)]
+pub struct Matrix {
+    n_cols: usize,
+    // links and columns are u32 node indices, halving the memory of big matrices
+    l: Vec<u32>,
+    r: Vec<u32>,
+    u: Vec<u32>,
+    d: Vec<u32>,
+    // column of each node, and number of rows of each column
+    c: Vec<u32>,
+    s: Vec<u32>,
+    // first node of the row of each node
+    start: Vec<u32>,
+    // rows each column still needs, 1 unless given a multiplicity
+    need: Vec<u32>,
+    multiple: bool,
+    updates: usize,
+    covers: usize,
+    uncovers: usize,
+}
+
+// node index as stored in the links.
+fn node(i: usize) -> u32 {
+    u32::try_from(i).expect("too many matrix nodes")
+}
+
+impl Matrix {
+    // matrix with only primary columns.
+    pub fn new(n_cols: usize) -> Self {
+        Self::with_secondary(n_cols, 0)
+    }
+
+    // matrix with `n_primary` primary columns followed by `n_secondary` secondary ones.
+    pub fn with_secondary(n_primary: usize, n_secondary: usize) -> Self {
+        let n_cols = n_primary + n_secondary;
+        let n = node(n_cols + 1);
+        let mut m = Self {
+            n_cols,
+            l: (0..n).collect(),
+            r: (0..n).collect(),
+            u: (0..n).collect(),
+            d: (0..n).collect(),
+            c: (0..n).collect(),
+            s: vec![0; n as usize],
+            start: (0..n).collect(),
+            need: vec![1; n as usize],
+            multiple: false,
+            updates: 0,
+            covers: 0,
+            uncovers: 0,
+        };
+        // link the root and the primary headers into a circular list
+        for i in 0..=n_primary {
+            m.r[i] = node((i + 1) % (n_primary + 1));
+            m.l[(i + 1) % (n_primary + 1)] = node(i);
+        }
+        m
+    }
+
+    pub fn n_cols(&self) -> usize {
+        self.n_cols
+    }
+
+    // bytes of the node arrays.
+    pub fn bytes(&self) -> usize {
+        [
+            &self.l,
+            &self.r,
+            &self.u,
+            &self.d,
+            &self.c,
+            &self.s,
+            &self.start,
+            &self.need,
+        ]
+        .iter()
+        .map(|v| v.capacity() * std::mem::size_of::<u32>())
+        .sum()
+    }
+
+    // add a row covering the given (0-based) columns, returns its id.
+    pub fn add_row(&mut self, row: &[usize]) -> usize {
+        let first = self.l.len();
+        for (k, col) in row.iter().enumerate() {
+            let c = col + 1;
+            assert!(c <= self.n_cols, "column {} out of range", col);
+
+            let id = self.l.len();
+            let left = if k == 0 { id } else { id - 1 };
+            self.l.push(node(left));
+            self.r.push(node(first));
+            self.r[left] = node(id);
+            self.l[first] = node(id);
+
+            let up = self.u[c];
+            self.u.push(up);
+            self.d.push(node(c));
+            self.d[up as usize] = node(id);
+            self.u[c] = node(id);
+
+            self.c.push(node(c));
+            self.s[c] += 1;
+            self.start.push(node(first));
+        }
+        first
+    }
+
+    // the number of rows that must cover a (0-based) primary column, zero to
+    // drop it. Set before solving.
+    pub fn set_multiplicity(&mut self, col: usize, multiplicity: usize) {
+        let c = col + 1;
+        self.need[c] = u32::try_from(multiplicity).expect("multiplicity too large");
+        self.multiple |= multiplicity > 1;
+        let (l, r) = (self.l[c] as usize, self.r[c] as usize);
+        if multiplicity == 0 && self.r[l] as usize == c {
+            self.r[l] = node(r);
+            self.l[r] = node(l);
+        }
+    }
+
+    // columns (1-based) of the row containing the node, starting at its first node.
+    pub fn row(&self, node: usize) -> Vec<usize> {
+        let first = self.start[node] as usize;
+        let mut cols = vec![self.c[first] as usize];
+        let mut j = self.r[first] as usize;
+        while j != first {
+            cols.push(self.c[j] as usize);
+            j = self.r[j] as usize;
+        }
+        cols
+    }
+
+    fn cover(&mut self, c: usize) {
+        self.covers += 1;
+        let (l, r) = (self.l[c], self.r[c]);
+        self.r[l as usize] = r;
+        self.l[r as usize] = l;
+
+        let mut i = self.d[c] as usize;
+        while i != c {
+            let mut j = self.r[i] as usize;
+            while j != i {
+                let (u, d) = (self.u[j], self.d[j]);
+                self.d[u as usize] = d;
+                self.u[d as usize] = u;
+                self.s[self.c[j] as usize] -= 1;
+                self.updates += 1;
+                j = self.r[j] as usize;
+            }
+            i = self.d[i] as usize;
+        }
+    }
+
+    fn uncover(&mut self, c: usize) {
+        self.uncovers += 1;
+        let mut i = self.u[c] as usize;
+        while i != c {
+            let mut j = self.l[i] as usize;
+            while j != i {
+                let (u, d) = (self.u[j] as usize, self.d[j] as usize);
+                self.d[u] = node(j);
+                self.u[d] = node(j);
+                self.s[self.c[j] as usize] += 1;
+                j = self.l[j] as usize;
+            }
+            i = self.u[i] as usize;
+        }
+
+        let (l, r) = (self.l[c] as usize, self.r[c] as usize);
+        self.r[l] = node(c);
+        self.l[r] = node(c);
+    }
+}
+
+impl ExactCover for Matrix {
+    fn add_row(&mut self, row: &[usize]) -> usize {
+        Matrix::add_row(self, row)
+    }
+
+    fn cover(&mut self, col: usize) {
+        Matrix::cover(self, col + 1);
+    }
+
+    fn uncover(&mut self, col: usize) {
+        Matrix::uncover(self, col + 1);
+    }
+
+    fn solve_iter(self: Box<Self>) -> Box<dyn Iterator<Item = Vec<usize>>> {
+        Box::new(Solver::new(*self))
+    }
+}
+
+// Resumable enumeration of all exact covers.
+pub struct Solver {
+    m: Matrix,
+    // the chosen node of each level of the search
+    stack: Vec<usize>,
+    started: bool,
+    done: bool,
+    // stopped before finishing, resume without advancing
+    interrupted: bool,
+    stats: Stats,
+    heuristic: Heuristic,
+    // the most recent decisions, rows by id
+    trace: Trace,
+}
+
+impl Solver {
+    pub fn new(m: Matrix) -> Self {
+        Self {
+            m,
+            stack: Vec::new(),
+            started: false,
+            done: false,
+            interrupted: false,
+            stats: Stats::default(),
+            heuristic: Heuristic::default(),
+            trace: Trace::default(),
+        }
+    }
+
+    pub fn with_heuristic(m: Matrix, heuristic: Heuristic) -> Self {
+        Self {
+            heuristic,
+            ..Self::new(m)
+        }
+    }
+
+    pub fn row(&self, node: usize) -> Vec<usize> {
+        self.m.row(node)
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            updates: self.m.updates,
+            covers: self.m.covers,
+            uncovers: self.m.uncovers,
+            ..self.stats.clone()
+        }
+    }
+
+    // zero the counters, the search itself goes on.
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::default();
+        self.m.updates = 0;
+        self.m.covers = 0;
+        self.m.uncovers = 0;
+    }
+
+    // keep the last `capacity` search decisions, zero disables tracing.
+    pub fn set_trace_capacity(&mut self, capacity: usize) {
+        self.trace.set_capacity(capacity);
+    }
+
+    // the most recent search decisions, oldest first.
+    pub fn trace_tail(&self) -> Vec<TraceEvent> {
+        self.trace.tail()
+    }
+
+    // enter the row of node `i`, the next choice for its column.
+    fn enter(&mut self, i: usize) {
+        self.stack.push(i);
+        self.apply(i);
+        let c = self.m.c[i] as usize;
+        self.trace.record(TraceEvent::Enter {
+            row: self.m.start[i] as usize,
+            depth: self.stack.len(),
+            options: self.m.s[c] as usize,
+        });
+    }
+
+    // whether the last call stopped before finding a solution or exhausting the search.
+    pub fn interrupted(&self) -> bool {
+        self.interrupted
+    }
+
+    // row ids of the current partial solution, in search order.
+    pub fn partial(&self) -> Vec<usize> {
+        self.stack
+            .iter()
+            .map(|i| self.m.start[*i] as usize)
+            .collect()
+    }
+
+    // primary column to branch on.
+    fn choose(&self) -> Option<usize> {
+        let first = self.m.r[0] as usize;
+        if first == 0 {
+            return None;
+        }
+        // skip the columns needing several rows, but end the branch on one
+        // that cannot get them any more, or when only those are left
+        let mut plain = first;
+        if self.m.multiple {
+            let mut found = None;
+            let mut c = first;
+            while c != 0 {
+                if self.m.s[c] < self.m.need[c] {
+                    return Some(c);
+                }
+                if found.is_none() && self.m.need[c] == 1 {
+                    found = Some(c);
+                }
+                c = self.m.r[c] as usize;
+            }
+            plain = found.unwrap_or(first);
+        }
+        if let Heuristic::Order(order) = &self.heuristic {
+            // a covered header is unlinked from its neighbors
+            let uncovered = order.iter().map(|c| c + 1).find(|c| {
+                *c <= self.m.n_cols
+                    && self.m.r[self.m.l[*c] as usize] as usize == *c
+                    && self.m.need[*c] == 1
+            });
+            return Some(uncovered.unwrap_or(plain));
+        }
+        let mut best = plain;
+        let mut c = plain;
+        while c != 0 {
+            let better = match &self.heuristic {
+                _ if self.m.need[c] != 1 => false,
+                Heuristic::First | Heuristic::Order(_) => return Some(plain),
+                Heuristic::MinimumRemaining => self.m.s[c] < self.m.s[best],
+                Heuristic::Weighted(weights) => {
+                    let cost = |c: usize| {
+                        let weight = weights.get(c - 1).copied().unwrap_or(1.0);
+                        f64::from(self.m.s[c]) / weight
+                    };
+                    cost(c) < cost(best)
+                }
+            };
+            if better {
+                best = c;
+            }
+            c = self.m.r[c] as usize;
+        }
+        Some(best)
+    }
+
+    // cover the other columns of the row of node `i`, those needing more
+    // rows only count it.
+    fn apply(&mut self, i: usize) {
+        self.stats.nodes += 1;
+        let mut j = self.m.r[i] as usize;
+        while j != i {
+            let c = self.m.c[j] as usize;
+            self.m.need[c] -= 1;
+            if self.m.need[c] == 0 {
+                self.m.cover(c);
+            }
+            j = self.m.r[j] as usize;
+        }
+    }
+
+    fn unapply(&mut self, i: usize) {
+        let mut j = self.m.l[i] as usize;
+        while j != i {
+            let c = self.m.c[j] as usize;
+            if self.m.need[c] == 0 {
+                self.m.uncover(c);
+            }
+            self.m.need[c] += 1;
+            j = self.m.l[j] as usize;
+        }
+    }
+
+    // move to the next row of the deepest level with one left, false when exhausted.
+    fn backtrack(&mut self) -> bool {
+        while let Some(i) = self.stack.pop() {
+            self.unapply(i);
+            self.trace.record(TraceEvent::Backtrack {
+                row: self.m.start[i] as usize,
+                depth: self.stack.len() + 1,
+            });
+            let c = self.m.c[i] as usize;
+            let next = self.m.d[i] as usize;
+            if next != c {
+                self.enter(next);
+                return true;
+            }
+            self.m.uncover(c);
+        }
+        false
+    }
+
+    // find the next solution, stopping early when `stop` returns true for the
+    // work done so far. An interrupted search resumes on the next call.
+    pub fn next_until(&mut self, stop: &mut dyn FnMut(&Stats) -> bool) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        if self.started && !self.interrupted && !self.backtrack() {
+            self.done = true;
+            return None;
+        }
+        self.started = true;
+        self.interrupted = false;
+
+        loop {
+            if stop(&self.stats()) {
+                self.interrupted = true;
+                return None;
+            }
+            match self.choose() {
+                None => {
+                    self.stats.solutions += 1;
+                    self.trace.record(TraceEvent::Solution {
+                        depth: self.stack.len(),
+                    });
+                    let mut solution = self.partial();
+                    solution.sort();
+                    return Some(solution);
+                }
+                Some(c) if self.m.s[c] < self.m.need[c] || self.m.need[c] > 1 => {
+                    self.trace.record(TraceEvent::DeadEnd {
+                        depth: self.stack.len(),
+                    });
+                    if !self.backtrack() {
+                        self.done = true;
+                        return None;
+                    }
+                }
+                Some(c) => {
+                    self.m.cover(c);
+                    self.enter(self.m.d[c] as usize);
+                }
+            }
+        }
+    }
+}
+
+// Solutions as sorted row ids.
+impl Iterator for Solver {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        self.next_until(&mut |_| false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Heuristic;
+    use super::Matrix;
+    use super::Solver;
+    use crate::search::TraceEvent;
+
+    fn solutions(solver: Solver) -> Vec<Vec<usize>> {
+        solver.collect()
+    }
+
+    #[test]
+    fn solve() {
+        let mut m = Matrix::new(4);
+        let ids: Vec<usize> = [vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3], vec![0]]
+            .iter()
+            .map(|row| m.add_row(row))
+            .collect();
+        assert_eq!(ids, vec![5, 7, 9, 11, 13]);
+        assert_eq!(m.row(8), vec![3, 4]);
+
+        let solver = Solver::new(m);
+        assert_eq!(solutions(solver), vec![vec![5, 7], vec![9, 11]]);
+    }
+
+    #[test]
+    fn secondary() {
+        // column 2 may stay uncovered, but not be covered twice
+        let mut m = Matrix::with_secondary(2, 1);
+        let a = m.add_row(&[0, 2]);
+        m.add_row(&[1, 2]);
+        let c = m.add_row(&[1]);
+        assert_eq!(solutions(Solver::new(m)), vec![vec![a, c]]);
+    }
+
+    #[test]
+    fn heuristics() {
+        let rows = [vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3], vec![0]];
+        let weights = Heuristic::Weighted(vec![1.0, 1.0, 1.0, 4.0]);
+        let order = Heuristic::Order(vec![3, 1]);
+        for heuristic in [
+            Heuristic::First,
+            Heuristic::MinimumRemaining,
+            weights,
+            order,
+        ] {
+            let mut m = Matrix::new(4);
+            for row in &rows {
+                m.add_row(row);
+            }
+            let mut solutions = solutions(Solver::with_heuristic(m, heuristic));
+            solutions.sort();
+            assert_eq!(solutions, vec![vec![5, 7], vec![9, 11]]);
+        }
+    }
+
+    #[test]
+    fn interrupted() {
+        let mut m = Matrix::new(4);
+        for row in [vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3], vec![0]] {
+            m.add_row(&row);
+        }
+        let mut solver = Solver::new(m);
+
+        assert_eq!(solver.next_until(&mut |stats| stats.nodes >= 1), None);
+        assert!(solver.interrupted());
+        assert_eq!(solver.partial().len(), 1);
+
+        assert_eq!(solver.next(), Some(vec![5, 7]));
+        assert_eq!(solver.next(), Some(vec![9, 11]));
+        assert_eq!(solver.next(), None);
+
+        let stats = solver.stats();
+        assert_eq!(stats.solutions, 2);
+        assert!(stats.nodes >= 4);
+        assert!(stats.updates > 0);
+    }
+
+    #[test]
+    fn trace() {
+        // the same rows as the search's trace test, with ids 3, 5 and 6
+        let mut m = Matrix::new(2);
+        for row in [vec![0, 1], vec![1], vec![0]] {
+            m.add_row(&row);
+        }
+        let mut solver = Solver::new(m);
+        solver.set_trace_capacity(3);
+
+        while solver.next().is_some() {}
+
+        assert_eq!(
+            solver.trace_tail(),
+            vec![
+                TraceEvent::Solution { depth: 2 },
+                TraceEvent::Backtrack { row: 5, depth: 2 },
+                TraceEvent::Backtrack { row: 6, depth: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn multiplicity() {
+        // two copies of a domino on four cells in a row, and a monomino
+        // column dropped
+        let mut m = Matrix::new(6);
+        m.set_multiplicity(4, 2);
+        m.set_multiplicity(5, 0);
+        let ids: Vec<usize> = [vec![0, 1, 4], vec![1, 2, 4], vec![2, 3, 4]]
+            .iter()
+            .map(|row| m.add_row(row))
+            .collect();
+        for heuristic in [Heuristic::First, Heuristic::MinimumRemaining] {
+            let solver = Solver::with_heuristic(m.clone(), heuristic);
+            assert_eq!(solutions(solver), vec![vec![ids[0], ids[2]]]);
+        }
+
+        // the copies cannot both fit
+        let mut m = Matrix::new(4);
+        m.set_multiplicity(3, 2);
+        m.add_row(&[0, 1, 3]);
+        m.add_row(&[1, 2, 3]);
+        assert!(solutions(Solver::new(m)).is_empty());
+    }
+}
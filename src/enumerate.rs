@@ -0,0 +1,122 @@
+// Enumerating polyominoes by the number of cells.
+//
+// Shapes grow one cell at a time: every shape of order n - 1 gets each of its
+// free neighbouring cells in turn, and the results are kept in canonical form,
+// so every shape appears exactly once. Free, one-sided and fixed polyominoes
+// only differ in which orientations count as the same shape.
+
+use std::collections::BTreeSet;
+
+use crate::orientation::OrientationCache;
+use crate::shape::Point;
+use crate::shape::Points;
+use crate::shape::Tile;
+
+// every free polyomino of `n` cells, named after their size, in the order of
+// their canonical cells.
+pub fn free_polyominoes(n: usize) -> Vec<Tile> {
+    grow(n, |tile| tile.canonical().points)
+}
+
+// every one-sided polyomino of `n` cells: mirror images are told apart,
+// rotations are not.
+pub fn one_sided_polyominoes(n: usize) -> Vec<Tile> {
+    let mut cache = OrientationCache::new();
+    grow(n, move |tile| {
+        cache
+            .orientations(tile)
+            .iter()
+            .filter(|(_, mask)| mask & 0x0f != 0)
+            .map(|(points, _)| points)
+            .min()
+            .cloned()
+            .unwrap_or_default()
+    })
+}
+
+// every fixed polyomino of `n` cells: all rotations and mirror images are
+// told apart, only translations are not.
+pub fn fixed_polyominoes(n: usize) -> Vec<Tile> {
+    grow(n, |tile| {
+        let mut tile = tile.clone();
+        tile.translate(&-tile.offset());
+        tile.points.sort();
+        tile.points
+    })
+}
+
+// the polyominoes of `n` cells with distinct `canonical` cells, which must be
+// at the origin.
+fn grow(n: usize, mut canonical: impl FnMut(&Tile) -> Points) -> Vec<Tile> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut shapes: BTreeSet<Points> = BTreeSet::new();
+    shapes.insert(Tile::from_points("", &[(0, 0)]).points);
+
+    // grow every shape by each of its neighbouring cells
+    for _ in 1..n {
+        let mut grown = BTreeSet::new();
+        for shape in &shapes {
+            for p in shape {
+                for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                    let q = Point::new(p.x + dx, p.y + dy);
+                    if shape.contains(&q) {
+                        continue;
+                    }
+                    let mut tile = Tile::new("");
+                    tile.points = shape.clone();
+                    tile.points.push(q);
+                    grown.insert(canonical(&tile));
+                }
+            }
+        }
+        shapes = grown;
+    }
+    let name = n.to_string();
+    shapes
+        .into_iter()
+        .map(|points| {
+            // at the origin already, only the order changes
+            let mut tile = Tile::new(&name);
+            tile.points = points;
+            tile.points.sort_by_key(|p| (p.y, p.x));
+            tile
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::fixed_polyominoes;
+    use super::free_polyominoes;
+    use super::one_sided_polyominoes;
+    use crate::shape::Tile;
+
+    #[test]
+    fn counts() {
+        let counts: Vec<usize> = (0..=8).map(|n| free_polyominoes(n).len()).collect();
+        assert_eq!(counts, vec![0, 1, 1, 2, 5, 12, 35, 108, 369]);
+
+        let pentominoes = free_polyominoes(5);
+        assert!(pentominoes.iter().all(|t| t.len() == 5 && t.is_connected()));
+        assert_eq!(pentominoes[0].name(), "5");
+        // no two are congruent
+        for (i, a) in pentominoes.iter().enumerate() {
+            assert!(pentominoes[i + 1..].iter().all(|b| !a.congruent_to(b)));
+        }
+    }
+
+    #[test]
+    fn chirality() {
+        let counts = |f: fn(usize) -> Vec<Tile>| (1..=6).map(|n| f(n).len()).collect::<Vec<_>>();
+        assert_eq!(counts(one_sided_polyominoes), vec![1, 1, 2, 7, 18, 60]);
+        assert_eq!(counts(fixed_polyominoes), vec![1, 2, 6, 19, 63, 216]);
+
+        // the S tetromino and its mirror image are two one-sided pieces
+        let s = Tile::from_str("S", " xx\nxx");
+        let tetrominoes = one_sided_polyominoes(4);
+        let congruent = tetrominoes.iter().filter(|t| t.congruent_to(&s)).count();
+        assert_eq!(congruent, 2);
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+use crate::parser::ParserError;
+use crate::snapshot::SnapshotError;
+use crate::warning::Warning;
+
+// Why a puzzle could not be read. Problems that still leave a puzzle to solve
+// are warnings instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    // the text is not in the puzzle format.
+    Parse(ParserError),
+    // no key names a board.
+    MissingBoard,
+    // the boards have no cells.
+    EmptyBoard,
+    // no key draws a piece.
+    NoPieces,
+    // the value of a key is not of the expected kind.
+    InvalidValue { key: String },
+    // a row or solution rule does not compile.
+    Script(String),
+    // the saved matrix does not fit the puzzle.
+    Snapshot(SnapshotError),
+    // a warning the strict reading does not accept.
+    Rejected(Warning),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::Parse(error) => error.fmt(f),
+            GameError::MissingBoard => write!(f, "No board given"),
+            GameError::EmptyBoard => write!(f, "The board has no cells"),
+            GameError::NoPieces => write!(f, "No pieces given"),
+            GameError::InvalidValue { key } => write!(f, "Key {:?} has an invalid value", key),
+            GameError::Script(error) => write!(f, "Rule does not compile: {}", error),
+            GameError::Snapshot(error) => error.fmt(f),
+            GameError::Rejected(warning) => warning.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<ParserError> for GameError {
+    fn from(error: ParserError) -> Self {
+        GameError::Parse(error)
+    }
+}
+
+impl From<SnapshotError> for GameError {
+    fn from(error: SnapshotError) -> Self {
+        GameError::Snapshot(error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GameError;
+    use crate::parser::parse;
+
+    #[test]
+    fn conversions() {
+        let error = GameError::from(parse("Board: 'xx'\n").unwrap_err());
+        assert!(matches!(&error, GameError::Parse(e) if e.at() == 7));
+        assert_eq!(
+            error.to_string(),
+            "Unknown value at line 1, column 8:\nBoard: 'xx'\n       ^"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+// Interface of exact cover engines, so `Game` can run on other solvers
+// (SAT based, bitsets, ...) than the built-in dancing links.
+
+pub trait ExactCover {
+    // add a row covering the given (0-based) columns, returns its id.
+    fn add_row(&mut self, row: &[usize]) -> usize;
+
+    // remove a column and all rows intersecting it from the matrix.
+    fn cover(&mut self, col: usize);
+
+    // undo `cover`, in reverse order of covering.
+    fn uncover(&mut self, col: usize);
+
+    // all exact covers of the remaining columns, as row ids.
+    fn solve_iter(self: Box<Self>) -> Box<dyn Iterator<Item = Vec<usize>>>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::ExactCover;
+    use crate::dlx::Matrix;
+    use crate::polyomino::Game;
+
+    // Tries every subset of rows.
+    struct Subsets {
+        n_cols: usize,
+        rows: Vec<Vec<usize>>,
+        covered: Vec<bool>,
+    }
+
+    impl ExactCover for Subsets {
+        fn add_row(&mut self, row: &[usize]) -> usize {
+            self.rows.push(row.to_vec());
+            self.rows.len() - 1
+        }
+
+        fn cover(&mut self, col: usize) {
+            self.covered[col] = true;
+        }
+
+        fn uncover(&mut self, col: usize) {
+            self.covered[col] = false;
+        }
+
+        fn solve_iter(self: Box<Self>) -> Box<dyn Iterator<Item = Vec<usize>>> {
+            let mut solutions = Vec::new();
+            for subset in 0..1usize << self.rows.len() {
+                let mut count = vec![0; self.n_cols];
+                let rows: Vec<usize> = (0..self.rows.len())
+                    .filter(|r| subset & (1 << r) != 0)
+                    .collect();
+                for r in &rows {
+                    for c in &self.rows[*r] {
+                        count[*c] += 1;
+                    }
+                }
+                let exact = (0..self.n_cols).all(|c| match self.covered[c] {
+                    true => count[c] == 0,
+                    false => count[c] == 1,
+                });
+                if exact {
+                    solutions.push(rows);
+                }
+            }
+            Box::new(solutions.into_iter())
+        }
+    }
+
+    #[test]
+    fn backends() {
+        let yaml = "Board: |\n    xxx\n    xxx\nL: |\n    xx\n    x\nJ: |\n    xx\n     x\n";
+        let mut game = Game::from_yaml(yaml).unwrap();
+        let mut expected = Vec::new();
+        while let Some(solution) = game.solve() {
+            expected.push(solution);
+        }
+        assert_eq!(expected.len(), 4);
+
+        let n_cols = game.n_cols();
+        game.set_backend(Box::new(Subsets {
+            n_cols,
+            rows: Vec::new(),
+            covered: vec![false; n_cols],
+        }));
+        let mut solutions = Vec::new();
+        while let Some(solution) = game.solve() {
+            solutions.push(solution);
+        }
+        expected.sort();
+        solutions.sort();
+        assert_eq!(solutions, expected);
+    }
+
+    #[test]
+    fn cover() {
+        let mut m = Matrix::new(3);
+        m.add_row(&[0, 1]);
+        let b = m.add_row(&[2]);
+        let c = m.add_row(&[0]);
+        m.add_row(&[1, 2]);
+
+        // with column 1 taken, only rows avoiding it remain
+        ExactCover::cover(&mut m, 1);
+        let solutions: Vec<Vec<usize>> = ExactCover::solve_iter(Box::new(m)).collect();
+        assert_eq!(solutions, vec![vec![b, c]]);
+    }
+}
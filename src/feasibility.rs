@@ -0,0 +1,163 @@
+// Cheap necessary conditions for a tiling.
+//
+// Checked before generating any placements, so hopeless puzzles (a piece too
+// many, a piece that cannot fit) are rejected without building the matrix.
+// Cells and pieces without any placement are found right after generating
+// the rows, before the matrix is built.
+
+use std::fmt;
+
+use crate::rows::Rows;
+use crate::shape::Tile;
+
+// Why a puzzle has no solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Unsolvable {
+    // the pieces cover more or fewer cells than the board has.
+    AreaMismatch { board: usize, pieces: usize },
+    // a piece does not fit in the board's bounding box in any orientation.
+    TooLarge { name: String },
+    // a checkerboard coloring argument rules out any tiling.
+    Parity,
+    // no placement covers the board cell with this index.
+    UncoverableCell { cell: usize },
+    // the piece has no placement at all.
+    UnplaceablePiece { name: String },
+}
+
+impl fmt::Display for Unsolvable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unsolvable::AreaMismatch { board, pieces } => {
+                write!(f, "Pieces cover {} cells, the board has {}", pieces, board)
+            }
+            Unsolvable::TooLarge { name } => write!(f, "Piece {:?} does not fit the board", name),
+            Unsolvable::Parity => write!(f, "Checkerboard coloring rules out a tiling"),
+            Unsolvable::UncoverableCell { cell } => write!(f, "No piece can cover cell {}", cell),
+            Unsolvable::UnplaceablePiece { name } => {
+                write!(f, "Piece {:?} cannot be placed anywhere", name)
+            }
+        }
+    }
+}
+
+// the first failed condition on the board, `optional` of whose cells may
+// stay uncovered, and the pieces alone.
+pub fn check(board: &Tile, optional: usize, tiles: &[Tile]) -> Option<Unsolvable> {
+    let area: usize = tiles.iter().map(|t| t.len()).sum();
+    if area > board.len() || area + optional < board.len() {
+        return Some(Unsolvable::AreaMismatch {
+            board: board.len(),
+            pieces: area,
+        });
+    }
+    if board.points.is_empty() {
+        return None;
+    }
+    let bounds = board.size();
+    for tile in tiles.iter().filter(|t| !t.points.is_empty()) {
+        let size = tile.size();
+        let fits = |w: usize, h: usize| w <= bounds.width && h <= bounds.height;
+        if !fits(size.width, size.height) && !fits(size.height, size.width) {
+            return Some(Unsolvable::TooLarge {
+                name: tile.name.clone(),
+            });
+        }
+    }
+    None
+}
+
+// the first board cell, then the first piece, no row covers. Pieces are the
+// columns after the `n_cells` cells.
+pub fn uncovered(n_cells: usize, tiles: &[Tile], rows: &Rows) -> Option<Unsolvable> {
+    let mut covered = vec![false; n_cells + tiles.len()];
+    for row in rows.iter() {
+        for c in row {
+            covered[*c as usize] = true;
+        }
+    }
+    let c = covered.iter().position(|c| !c)?;
+    Some(match c < n_cells {
+        true => Unsolvable::UncoverableCell { cell: c },
+        false => Unsolvable::UnplaceablePiece {
+            name: tiles[c - n_cells].name.clone(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::Unsolvable;
+    use super::check;
+    use super::uncovered;
+    use crate::rows::Rows;
+    use crate::shape::Tile;
+
+    #[test]
+    fn conditions() {
+        let board = Tile::from_str("Board", "xxxx\nxxxx");
+        let i = Tile::from_str("I", "xxxx");
+        assert_eq!(check(&board, 0, &[i.clone(), i.clone()]), None);
+        assert_eq!(
+            check(&board, 0, &[Tile::from_str("T", "xxx\n x")]),
+            Some(Unsolvable::AreaMismatch {
+                board: 8,
+                pieces: 4
+            })
+        );
+
+        // the long bar fits neither way
+        let long = Tile::from_str("Long", "xxxxxxxx");
+        let strip = Tile::from_str("Board", "xxxx\nxxxx\nxxxx\nxxxx");
+        let o = Tile::from_str("O", "xx\nxx");
+        assert_eq!(
+            check(&strip, 0, &[long, o.clone(), o]),
+            Some(Unsolvable::TooLarge {
+                name: "Long".to_string()
+            })
+        );
+        // optional cells may stay uncovered
+        let half = [Tile::from_str("I", "xxxx")];
+        assert_eq!(check(&board, 4, &half), None);
+        assert_eq!(
+            check(&board, 3, &half),
+            Some(Unsolvable::AreaMismatch {
+                board: 8,
+                pieces: 4
+            })
+        );
+        // a vertical bar fits a horizontal board on its side
+        assert_eq!(
+            check(&board, 0, &[Tile::from_str("V", "x\nx\nx\nx"), i]),
+            None
+        );
+    }
+
+    #[test]
+    fn uncovered_columns() {
+        let tiles = [Tile::from_str("A", "x"), Tile::from_str("B", "x")];
+        let mut rows = Rows::new();
+        rows.push(&[0, 2]);
+        rows.push(&[1, 2]);
+        assert_eq!(
+            uncovered(2, &tiles, &rows),
+            Some(Unsolvable::UnplaceablePiece {
+                name: "B".to_string()
+            })
+        );
+        rows.push(&[0, 3]);
+        assert_eq!(uncovered(2, &tiles, &rows), None);
+
+        let mut rows = Rows::new();
+        rows.push(&[0, 2]);
+        rows.push(&[0, 3]);
+        assert_eq!(
+            uncovered(2, &tiles, &rows),
+            Some(Unsolvable::UncoverableCell { cell: 1 })
+        );
+        assert_eq!(
+            Unsolvable::UncoverableCell { cell: 2 }.to_string(),
+            "No piece can cover cell 2"
+        );
+    }
+}
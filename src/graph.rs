@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+// Solution adjacency graph.
+//
+// Nodes are solutions (by index), and two solutions are connected when one can
+// be turned into the other by re-placing at most `max_moved` pieces.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SolutionGraph {
+    pub nodes: usize,
+    pub edges: Vec<(usize, usize, usize)>,
+}
+
+// number of placements (rows) in `a` that are not in `b`.
+fn moved(a: &HashSet<usize>, b: &HashSet<usize>) -> usize {
+    a.difference(b).count()
+}
+
+impl SolutionGraph {
+    pub fn new(solutions: &[Vec<usize>], max_moved: usize) -> Self {
+        let sets: Vec<HashSet<usize>> = solutions
+            .iter()
+            .map(|s| s.iter().cloned().collect())
+            .collect();
+
+        let mut edges = Vec::new();
+        for i in 0..sets.len() {
+            for j in (i + 1)..sets.len() {
+                let m = moved(&sets[i], &sets[j]).max(moved(&sets[j], &sets[i]));
+                if m > 0 && m <= max_moved {
+                    edges.push((i, j, m));
+                }
+            }
+        }
+
+        Self {
+            nodes: solutions.len(),
+            edges,
+        }
+    }
+
+    // neighbors of a single solution.
+    pub fn neighbors(&self, node: usize) -> Vec<usize> {
+        let mut neighbors = Vec::new();
+        for (a, b, _) in &self.edges {
+            if *a == node {
+                neighbors.push(*b);
+            } else if *b == node {
+                neighbors.push(*a);
+            }
+        }
+        neighbors
+    }
+
+    // number of connected components.
+    pub fn components(&self) -> usize {
+        let mut parent: Vec<usize> = (0..self.nodes).collect();
+
+        fn find(parent: &mut [usize], i: usize) -> usize {
+            let mut root = i;
+            while parent[root] != root {
+                root = parent[root];
+            }
+            parent[i] = root;
+            root
+        }
+
+        let mut count = self.nodes;
+        for (a, b, _) in &self.edges {
+            let ra = find(&mut parent, *a);
+            let rb = find(&mut parent, *b);
+            if ra != rb {
+                parent[ra] = rb;
+                count -= 1;
+            }
+        }
+        count
+    }
+
+    // Graphviz DOT representation, edges weighted by the number of moved pieces.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("graph solutions {\n");
+        for n in 0..self.nodes {
+            writeln!(out, "  {};", n).unwrap();
+        }
+        for (a, b, m) in &self.edges {
+            writeln!(out, "  {} -- {} [weight={}];", a, b, m).unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    // GraphML representation, edges carry the number of moved pieces.
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"moved\" for=\"edge\" attr.name=\"moved\" attr.type=\"int\"/>\n",
+            "  <graph id=\"solutions\" edgedefault=\"undirected\">\n"
+        ));
+        for n in 0..self.nodes {
+            writeln!(out, "    <node id=\"n{}\"/>", n).unwrap();
+        }
+        for (a, b, m) in &self.edges {
+            writeln!(
+                out,
+                "    <edge source=\"n{}\" target=\"n{}\"><data key=\"moved\">{}</data></edge>",
+                a, b, m
+            )
+            .unwrap();
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SolutionGraph;
+
+    #[test]
+    fn basics() {
+        let solutions = vec![vec![1, 2, 3], vec![1, 4, 5], vec![6, 7, 8]];
+
+        let graph = SolutionGraph::new(&solutions, 2);
+        assert_eq!(graph.nodes, 3);
+        assert_eq!(graph.edges, vec![(0, 1, 2)]);
+        assert_eq!(graph.neighbors(0), vec![1]);
+        assert_eq!(graph.neighbors(2), vec![]);
+        assert_eq!(graph.components(), 2);
+
+        let graph = SolutionGraph::new(&solutions, 3);
+        assert_eq!(graph.edges.len(), 3);
+        assert_eq!(graph.components(), 1);
+    }
+
+    #[test]
+    fn export() {
+        let solutions = vec![vec![1, 2], vec![1, 3]];
+        let graph = SolutionGraph::new(&solutions, 1);
+
+        assert_eq!(
+            graph.to_dot(),
+            "graph solutions {\n  0;\n  1;\n  0 -- 1 [weight=1];\n}\n"
+        );
+
+        let graphml = graph.to_graphml();
+        assert!(graphml.contains("<node id=\"n1\"/>"));
+        assert!(
+            graphml
+                .contains("<edge source=\"n0\" target=\"n1\"><data key=\"moved\">1</data></edge>")
+        );
+    }
+}
@@ -0,0 +1,97 @@
+// Rectangular rendering of a (partial) solution, including the cells around
+// and inside the board that no piece covers.
+
+// Content of a grid cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    // not part of the board
+    Outside,
+    // part of the board drawing, but never covered ('#' in the board)
+    Blocked,
+    // a board cell the (partial) solution leaves uncovered
+    Uncovered,
+    // a board cell covered by the piece with this index
+    Piece(usize),
+}
+
+impl Cell {
+    // numeric token: pieces are their index, the rest negative.
+    pub fn token(&self) -> i32 {
+        match self {
+            Cell::Outside => -1,
+            Cell::Blocked => -2,
+            Cell::Uncovered => -3,
+            Cell::Piece(piece) => i32::try_from(*piece).unwrap(),
+        }
+    }
+
+    // text symbol, pieces are the first character of their name.
+    pub fn symbol(&self, names: &[&str]) -> char {
+        match self {
+            Cell::Outside => ' ',
+            Cell::Blocked => '#',
+            Cell::Uncovered => '.',
+            Cell::Piece(piece) => names[*piece].chars().next().unwrap_or('?'),
+        }
+    }
+}
+
+// Cells row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<Cell>,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::Outside; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Cell {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        self.cells[y * self.width + x] = cell;
+    }
+
+    pub fn to_tokens(&self) -> Vec<i32> {
+        self.cells.iter().map(|c| c.token()).collect()
+    }
+
+    // one line per row, with trailing outside cells trimmed.
+    pub fn to_text(&self, names: &[&str]) -> String {
+        let mut text = String::new();
+        for row in self.cells.chunks(self.width.max(1)) {
+            let line: String = row.iter().map(|c| c.symbol(names)).collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Cell;
+    use super::Grid;
+
+    #[test]
+    fn tokens() {
+        let mut grid = Grid::new(3, 2);
+        grid.set(0, 0, Cell::Piece(0));
+        grid.set(1, 0, Cell::Piece(1));
+        grid.set(2, 0, Cell::Blocked);
+        grid.set(0, 1, Cell::Uncovered);
+
+        assert_eq!(grid.get(2, 0), Cell::Blocked);
+        assert_eq!(grid.to_tokens(), vec![0, 1, -2, -3, -1, -1]);
+        assert_eq!(grid.to_text(&["A", "B"]), "AB#\n.\n");
+    }
+}
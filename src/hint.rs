@@ -0,0 +1,72 @@
+// Ranking of hint placements by the solutions they keep alive.
+
+// Which hints to prefer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintStyle {
+    // placements that keep the most solutions open
+    Safe,
+    // placements that narrow the puzzle down the most
+    Decisive,
+}
+
+impl HintStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "safe" => Some(HintStyle::Safe),
+            "decisive" => Some(HintStyle::Decisive),
+            _ => None,
+        }
+    }
+}
+
+// A placement (solver row id) and the number of solutions using it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub row: usize,
+    pub solutions: usize,
+}
+
+// order hints by style, ties by row.
+pub fn rank(mut hints: Vec<Hint>, style: HintStyle) -> Vec<Hint> {
+    hints.retain(|h| h.solutions > 0);
+    match style {
+        HintStyle::Safe => hints.sort_by_key(|h| (usize::MAX - h.solutions, h.row)),
+        HintStyle::Decisive => hints.sort_by_key(|h| (h.solutions, h.row)),
+    }
+    hints
+}
+
+#[cfg(test)]
+mod test {
+    use super::Hint;
+    use super::HintStyle;
+    use super::rank;
+
+    #[test]
+    fn styles() {
+        let hints = vec![
+            Hint {
+                row: 1,
+                solutions: 2,
+            },
+            Hint {
+                row: 2,
+                solutions: 0,
+            },
+            Hint {
+                row: 3,
+                solutions: 5,
+            },
+            Hint {
+                row: 4,
+                solutions: 2,
+            },
+        ];
+        let rows = |hints: Vec<Hint>| -> Vec<usize> { hints.iter().map(|h| h.row).collect() };
+
+        assert_eq!(rows(rank(hints.clone(), HintStyle::Safe)), vec![3, 1, 4]);
+        assert_eq!(rows(rank(hints, HintStyle::Decisive)), vec![1, 4, 3]);
+        assert_eq!(HintStyle::from_name("safe"), Some(HintStyle::Safe));
+        assert_eq!(HintStyle::from_name("bold"), None);
+    }
+}
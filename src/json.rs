@@ -0,0 +1,230 @@
+// Minimal JSON reader for puzzle data, kept small like the YAML-ish parser:
+// objects keep their key order, numbers are read as f64 and strings handle the
+// usual escapes, \u ones included.
+
+use crate::parser::ParserError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    // the value of a key of an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse(contents: &str) -> Result<Json, ParserError> {
+    let mut reader = Reader { contents, at: 0 };
+    let value = reader.value()?;
+    reader.skip_whitespace();
+    match reader.at == contents.len() {
+        true => Ok(value),
+        false => Err(reader.error("Trailing data")),
+    }
+}
+
+struct Reader<'a> {
+    contents: &'a str,
+    // byte offset of the next character
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self, message: &str) -> ParserError {
+        ParserError::new(message, self.contents, self.at)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.contents[self.at..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.at += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParserError> {
+        self.skip_whitespace();
+        match self.peek() == Some(c) {
+            true => {
+                self.next();
+                Ok(())
+            }
+            false => Err(self.error(&format!("Expected {:?}", c))),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, ParserError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some('t') => self.literal("true", Json::Bool(true)),
+            Some('f') => self.literal("false", Json::Bool(false)),
+            Some('n') => self.literal("null", Json::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, ParserError> {
+        match self.contents[self.at..].starts_with(word) {
+            true => {
+                self.at += word.len();
+                Ok(value)
+            }
+            false => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, ParserError> {
+        let start = self.at;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            self.next();
+        }
+        match self.contents[start..self.at].parse() {
+            Ok(number) => Ok(Json::Number(number)),
+            Err(_) => {
+                self.at = start;
+                Err(self.error("Invalid number"))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParserError> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            let escape = self.at;
+            match self.next() {
+                None => return Err(self.error("Unterminated string")),
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let hex = self.contents.get(self.at..self.at + 4).unwrap_or("");
+                            let code = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+                            self.at += hex.len();
+                            match code {
+                                Some(c) => c,
+                                None => {
+                                    self.at = escape;
+                                    return Err(self.error("Invalid escape"));
+                                }
+                            }
+                        }
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        _ => {
+                            self.at = escape;
+                            return Err(self.error("Invalid escape"));
+                        }
+                    };
+                    string.push(c);
+                }
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, ParserError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.at += 1,
+                Some(']') => {
+                    self.at += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, ParserError> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(Json::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            entries.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.at += 1,
+                Some('}') => {
+                    self.at += 1;
+                    return Ok(Json::Object(entries));
+                }
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Json;
+    use super::parse;
+
+    #[test]
+    fn values() {
+        let json = parse(r#"{ "a": [1, -2.5e1, true, null], "b": "x\né\"", "c": {} }"#).unwrap();
+        assert_eq!(
+            json.get("a"),
+            Some(&Json::Array(vec![
+                Json::Number(1.0),
+                Json::Number(-25.0),
+                Json::Bool(true),
+                Json::Null
+            ]))
+        );
+        assert_eq!(json.get("b"), Some(&Json::String("x\né\"".to_string())));
+        assert_eq!(json.get("c"), Some(&Json::Object(vec![])));
+        assert_eq!(json.get("d"), None);
+
+        let error = parse("{\n  \"a\": [1 2]\n}").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 11));
+        assert!(parse("[1] 2").is_err());
+        assert!(parse("\"open").is_err());
+    }
+}
@@ -1,77 +1,361 @@
+pub mod analysis;
+#[cfg(feature = "algox")]
+pub mod backend;
+pub mod batch;
+pub mod bitboard;
+pub mod challenges;
+pub mod conformance;
+pub mod count;
+pub mod dancing_cells;
+pub mod descriptors;
+pub mod diff;
+pub mod dlx;
+pub mod enumerate;
+pub mod error;
+pub mod estimate;
+pub mod exact_cover;
+pub mod feasibility;
+pub mod graph;
+pub mod grid;
+pub mod hint;
+pub mod json;
+pub mod memory;
+pub mod metrics;
+pub mod nesting;
+pub mod options;
+pub mod orientation;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod parity;
 pub mod parser;
+pub mod placement;
 pub mod polyomino;
+pub mod prune;
+pub mod reduce;
+pub mod regions;
+pub mod rows;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod search;
+pub mod sets;
+pub mod shape;
+pub mod sink;
+pub mod snapshot;
+pub mod warning;
 
 use wasm_bindgen::prelude::*;
 
+use crate::graph::SolutionGraph;
+use crate::grid::Cell;
+use crate::hint::HintStyle;
 use crate::polyomino::Game;
+use crate::sink::Bitsets;
+use crate::sink::Counter;
+use crate::sink::SolutionSink;
 
 #[wasm_bindgen]
 pub struct JsGame {
     game: Game,
 
+    // packed, decoded when a solution is asked for
     #[wasm_bindgen(skip)]
-    solutions: Vec<Vec<usize>>,
+    solutions: Bitsets,
+    // counts instead of storing solutions, when set
+    #[wasm_bindgen(skip)]
+    counter: Option<Counter>,
 }
 
-#[wasm_bindgen]
 impl JsGame {
-    #[wasm_bindgen]
-    pub fn fromYaml(yaml: &str) -> Self {
-        // populate from yaml
+    // where solutions go: the stored list, or the counter.
+    fn sink(&mut self) -> &mut dyn SolutionSink {
+        match &mut self.counter {
+            Some(counter) => counter,
+            None => &mut self.solutions,
+        }
+    }
+
+    fn new(game: Game) -> Self {
         JsGame {
-            game: Game::from_yaml(yaml),
-            solutions: Vec::new(),
+            solutions: Bitsets::new(game.row_ids().to_vec()),
+            game,
+            counter: None,
         }
     }
 
+    // the stored solution at `index`, as solver row ids.
+    fn stored(&self, index: usize) -> Vec<usize> {
+        self.solutions.get(index).expect("no such solution")
+    }
+}
+
+#[wasm_bindgen]
+impl JsGame {
+    #[wasm_bindgen]
+    pub fn fromYaml(yaml: &str) -> Result<JsGame, JsError> {
+        // populate from yaml, throwing on text that is not a puzzle
+        Game::from_yaml(yaml)
+            .map(JsGame::new)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    #[wasm_bindgen]
+    pub fn fromJson(json: &str) -> Result<JsGame, JsError> {
+        // populate from json, as in Game::from_json
+        Game::from_json(json)
+            .map(JsGame::new)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    #[wasm_bindgen]
+    pub fn fromYamlWithMatrix(yaml: &str, matrix: &[u8]) -> Result<JsGame, JsError> {
+        // populate from yaml and a saved matrix, generating the rows when the
+        // matrix does not fit the puzzle.
+        Game::from_yaml_with_matrix(yaml, matrix)
+            .or_else(|_| Game::from_yaml(yaml))
+            .map(JsGame::new)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    #[wasm_bindgen]
+    pub fn matrixBytes(&self) -> Vec<u8> {
+        // the placement rows, for fromYamlWithMatrix.
+        self.game.save_matrix()
+    }
+
+    #[wasm_bindgen]
+    pub fn conformance() -> Vec<String> {
+        // failed checks of the JS-facing methods, empty when all pass.
+        conformance::run()
+    }
+
     #[wasm_bindgen]
     pub fn solve(&mut self) -> usize {
         // generates the solutions.
         let solution = self.game.solve();
 
         if let Some(solution) = solution {
-            self.solutions.push(solution);
+            self.sink().push(solution);
         }
 
-        self.solutions.len()
+        self.sink().count()
     }
 
     #[wasm_bindgen]
     pub fn solveAll(&mut self) -> usize {
-        // solve at least once.
-        self.solve();
-
-        let mut previous_count: usize = 0;
-        loop {
-            let count = self.solve();
-            if count == previous_count {
-                break;
-            }
-            previous_count = count;
-        }
-        self.solutions.len()
+        // enumerate the remaining solutions in one pass.
+        let sink = match &mut self.counter {
+            Some(counter) => counter as &mut dyn SolutionSink,
+            None => &mut self.solutions,
+        };
+        self.game.solve_into_sink(sink);
+        self.sink().count()
+    }
+
+    #[wasm_bindgen]
+    pub fn setCountOnly(&mut self, count_only: bool) {
+        // only count further solutions instead of storing them, for huge
+        // enumerations. Stored solutions stay available.
+        self.counter = match count_only {
+            true => Some(Counter {
+                count: self.solutions.len(),
+            }),
+            false => None,
+        };
+    }
+
+    #[wasm_bindgen]
+    pub fn memoryUsage(&self) -> Vec<usize> {
+        // estimated bytes as [placements, matrix, solver, solutions, total].
+        let mut report = self.game.memory_report();
+        report.solutions = self.solutions.bytes();
+        vec![
+            report.placements,
+            report.matrix,
+            report.solver,
+            report.solutions,
+            report.total(),
+        ]
+    }
+
+    #[wasm_bindgen]
+    pub fn isParityFeasible(&self) -> bool {
+        // false when a checkerboard coloring proves the puzzle unsolvable.
+        self.game.is_parity_feasible()
     }
 
     #[wasm_bindgen]
-    pub fn solution(&self, index: usize) -> Vec<usize> {
-        // represent solution as string?
-        let mut board: Vec<usize> = vec![0; self.game.len()];
+    pub fn unsolvableReason(&self) -> Option<String> {
+        // why the puzzle has no solution, when cheap checks show it.
+        self.game.unsolvable().map(|u| u.to_string())
+    }
+
+    #[wasm_bindgen]
+    pub fn warnings(&self) -> Vec<String> {
+        // problems found while reading the puzzle, as messages.
+        self.game.warnings().iter().map(|w| w.to_string()).collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn boardSizes(&self) -> Vec<usize> {
+        // number of solution cells belonging to each board, in order.
+        self.game
+            .boards()
+            .iter()
+            .map(|(_, range)| range.len())
+            .collect()
+    }
 
-        for r in &self.solutions[index] {
-            let indices = self.game.row(*r);
-            let tile_idx = indices.last().unwrap() - 1 - self.game.len();
+    #[wasm_bindgen]
+    pub fn solution(&self, index: usize) -> Vec<i32> {
+        // piece index of every board cell, -3 for cells left uncovered, as
+        // in solutionGrid.
+        let mut board: Vec<i32> = vec![Cell::Uncovered.token(); self.game.len()];
 
-            for i in 0..(indices.len() - 1) {
-                board[indices[i] - 1] = tile_idx;
+        for r in &self.stored(index) {
+            let Some(placement) = self.game.placement(*r) else {
+                continue;
+            };
+            let piece = Cell::Piece(placement.piece).token();
+            for cell in self.game.row_cells(*r) {
+                board[cell] = piece;
             }
         }
         board
     }
 
-    // #[wasm_bindgen]
-    // pub fn hint(&self) -> Vec<usize> {
-    //     // find the tile placement with the most solutions
-    // }
+    #[wasm_bindgen]
+    pub fn solutionGrid(&self, index: usize) -> Vec<i32> {
+        // solution over the board's bounding box, row by row: piece indices,
+        // -1 outside the board, -2 blocked and -3 uncovered cells.
+        self.game.solution_grid(&self.stored(index)).to_tokens()
+    }
+
+    #[wasm_bindgen]
+    pub fn labelPositions(&self, index: usize) -> Vec<f64> {
+        // where to write the piece names of a solution in the solution grid,
+        // as flattened [piece, x, y] triples.
+        self.game
+            .label_positions(&self.stored(index))
+            .iter()
+            .flat_map(|(piece, (x, y))| [*piece as f64, *x, *y])
+            .collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn pieceOutlines(&self, index: usize) -> Vec<i32> {
+        // outlines of the pieces of a stored solution in the solution grid, as
+        // flattened [piece, x0, y0, x1, y1] segments.
+        self.game
+            .piece_outlines(&self.stored(index))
+            .iter()
+            .flat_map(|(piece, segments)| {
+                segments.iter().flat_map(move |(start, end)| {
+                    [*piece as isize, start.x, start.y, end.x, end.y].map(|v| v as i32)
+                })
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn gridSize(&self) -> Vec<usize> {
+        // [width, height] of the solution grids.
+        let grid = self.game.solution_grid(&[]);
+        vec![grid.width, grid.height]
+    }
+
+    #[wasm_bindgen]
+    pub fn cellIndex(&self, x: usize, y: usize) -> Option<usize> {
+        // index into solution() of the solution grid position (x, y).
+        self.game.cell_index(x, y)
+    }
+
+    #[wasm_bindgen]
+    pub fn cellCoords(&self, index: usize) -> Vec<usize> {
+        // [x, y] in the solution grid of an index into solution().
+        let (x, y) = self.game.cell_coords(index);
+        vec![x, y]
+    }
+
+    #[wasm_bindgen]
+    pub fn cellWeights(&self) -> Vec<u32> {
+        // weight of every board cell, in the order of solution().
+        self.game.weights()
+    }
+
+    #[wasm_bindgen]
+    pub fn solutionWeight(&self, index: usize) -> u32 {
+        // total weight of the cells a stored solution covers.
+        self.game.solution_weight(&self.stored(index))
+    }
+
+    #[wasm_bindgen]
+    pub fn tileableRectangles(&self, max_area: usize) -> Vec<usize> {
+        // tileable rectangles as flattened [width, height] pairs.
+        self.game
+            .tileable_rectangles(max_area)
+            .iter()
+            .flat_map(|(w, h)| [*w, *h])
+            .collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn setDeadRegionPruning(&mut self, pruning: bool) {
+        // backtrack early on regions the remaining pieces cannot fill.
+        self.game.set_dead_region_pruning(pruning);
+    }
+
+    #[wasm_bindgen]
+    pub fn setNoCrossroads(&mut self, no_crossroads: bool) {
+        // reject solutions where four pieces meet at a point.
+        self.game.set_no_crossroads(no_crossroads);
+    }
+
+    #[wasm_bindgen]
+    pub fn estimateSolutionCount(&self, samples: usize) -> Vec<f64> {
+        // approximate solution count as [mean, variance].
+        let estimate = self.game.estimate_solution_count(samples);
+        vec![estimate.mean, estimate.variance]
+    }
+
+    #[wasm_bindgen]
+    pub fn solutionGraph(&self, max_moved: usize, format: &str) -> String {
+        // adjacency graph of the solutions found so far, as "dot" or "graphml".
+        let solutions: Vec<Vec<usize>> = self.solutions.iter().collect();
+        let graph = SolutionGraph::new(&solutions, max_moved);
+        match format {
+            "graphml" => graph.to_graphml(),
+            _ => graph.to_dot(),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn nestingSvg(
+        &self,
+        width: usize,
+        height: usize,
+        spacing: usize,
+        copies: usize,
+        cell: f64,
+    ) -> Option<String> {
+        // cutting layout of the pieces on a sheet, as SVG.
+        self.game
+            .nest(copies, width, height, spacing)
+            .map(|nesting| nesting.to_svg(cell))
+    }
+
+    #[wasm_bindgen]
+    pub fn hint(&self, style: &str) -> Vec<usize> {
+        // best placement for the "safe" or "decisive" style, as [piece, cells...].
+        let style = HintStyle::from_name(style).unwrap_or(HintStyle::Safe);
+        match self.game.hints(&[], style).first() {
+            Some(hint) => {
+                let mut hint_cells = vec![self.game.placement(hint.row).unwrap().piece];
+                hint_cells.extend(self.game.row_cells(hint.row));
+                hint_cells
+            }
+            None => Vec::new(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -116,7 +400,7 @@ Board: |
       x
     xxx
 ";
-        JsGame::fromYaml(yaml)
+        JsGame::fromYaml(yaml).unwrap()
     }
 
     #[test]
@@ -138,4 +422,17 @@ Board: |
 
         assert_eq!(game.solveAll(), 68)
     }
+
+    #[test]
+    fn solution_graph() {
+        let mut game = setup();
+        game.solveAll();
+
+        let dot = game.solutionGraph(2, "dot");
+        assert!(dot.starts_with("graph solutions {"));
+        assert!(dot.contains("  67;"));
+
+        let graphml = game.solutionGraph(2, "graphml");
+        assert!(graphml.contains("<node id=\"n67\"/>"));
+    }
 }
@@ -0,0 +1,48 @@
+use polyomino::polyomino::Game;
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+fn usage() -> ExitCode {
+    eprintln!("usage: polyomino diff <a.yaml> <b.yaml>");
+    ExitCode::FAILURE
+}
+
+fn read(path: &str) -> Option<Game> {
+    match fs::read_to_string(path) {
+        Ok(contents) => match Game::from_yaml(&contents) {
+            Ok(game) => Some(game),
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                None
+            }
+        },
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            None
+        }
+    }
+}
+
+// show what changed between two puzzle versions and how the solution count moved.
+fn diff(a: &str, b: &str) -> ExitCode {
+    let (Some(a), Some(b)) = (read(a), read(b)) else {
+        return ExitCode::FAILURE;
+    };
+
+    print!("{}", a.diff(&b));
+    println!(
+        "solutions: {} -> {}",
+        a.count_solutions(),
+        b.count_solutions()
+    );
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    match args.iter().map(|a| a.as_str()).collect::<Vec<_>>()[1..] {
+        ["diff", a, b] => diff(a, b),
+        _ => usage(),
+    }
+}
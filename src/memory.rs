@@ -0,0 +1,62 @@
+// Estimated heap usage of a game, so embedders can tell when to drop caches.
+//
+// The numbers count the bytes of the element buffers (their capacity), not
+// allocator overhead, so they are a lower bound on what is really used.
+
+use std::mem::size_of;
+use std::mem::size_of_val;
+
+// Bytes used, by part.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MemoryReport {
+    // matrix rows and their placement metadata
+    pub placements: usize,
+    // the cached dancing links matrix
+    pub matrix: usize,
+    // the copy of the matrix (and search state) the running enumeration uses
+    pub solver: usize,
+    // solutions kept by the caller
+    pub solutions: usize,
+}
+
+impl MemoryReport {
+    // add the bytes of stored solutions.
+    pub fn with_solutions(mut self, solutions: &[Vec<usize>]) -> Self {
+        self.solutions = nested_bytes(solutions);
+        self
+    }
+
+    pub fn total(&self) -> usize {
+        self.placements + self.matrix + self.solver + self.solutions
+    }
+}
+
+// bytes of a vector of vectors, including the outer buffer.
+pub(crate) fn nested_bytes<T>(rows: &[Vec<T>]) -> usize {
+    size_of_val(rows)
+        + rows
+            .iter()
+            .map(|r| r.capacity() * size_of::<T>())
+            .sum::<usize>()
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemoryReport;
+    use super::nested_bytes;
+
+    #[test]
+    fn solutions() {
+        let solutions = vec![vec![1, 2], vec![3, 4]];
+        let bytes = nested_bytes(&solutions);
+        assert!(bytes >= 4 * size_of::<usize>());
+
+        let report = MemoryReport {
+            placements: 10,
+            ..MemoryReport::default()
+        }
+        .with_solutions(&solutions);
+        assert_eq!(report.solutions, bytes);
+        assert_eq!(report.total(), 10 + bytes);
+    }
+}
@@ -0,0 +1,15 @@
+// Counters of the work a game did building its rows and searching, to compare
+// options (like pruning) without an external profiler.
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Metrics {
+    // placement rows generated
+    pub rows_built: usize,
+    // orientations computed for new piece shapes (cached shapes are free)
+    pub orientations: usize,
+    // columns covered and uncovered by the `solve` enumeration
+    pub covers: usize,
+    pub uncovers: usize,
+    // largest estimated memory use seen, in bytes
+    pub peak_bytes: usize,
+}
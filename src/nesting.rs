@@ -0,0 +1,275 @@
+// Nesting of pieces on a stock sheet, for cutting them out.
+//
+// Reuses the row generation of the packing solver: pieces are primary columns
+// and sheet cells secondary ones, so every piece is placed once and the sheet
+// need not be filled. Spacing is handled on a grid with half cells, where each
+// piece grows by the spacing (in half cells) in every direction. Two grown
+// pieces are disjoint exactly when the pieces are at least the spacing apart.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::analysis::Step;
+use crate::analysis::boundary_word;
+use crate::dlx::Matrix;
+use crate::dlx::Solver;
+use crate::orientation::OrientationCache;
+use crate::placement::Placement;
+use crate::polyomino::Rules;
+use crate::polyomino::build_rows;
+use crate::shape::Point;
+use crate::shape::Points;
+use crate::shape::Tile;
+
+// Pieces placed on a sheet, in cell coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nesting {
+    pub width: usize,
+    pub height: usize,
+    pub pieces: Vec<Tile>,
+}
+
+// scale the tile by `scale` and grow it by `margin` in all eight directions.
+fn grow(tile: &Tile, scale: isize, margin: isize) -> Tile {
+    let mut points: HashSet<Point> = HashSet::new();
+    for p in &tile.scaled(scale as usize).points {
+        for dx in -margin..=margin {
+            for dy in -margin..=margin {
+                points.insert(Point::new(p.x + dx, p.y + dy));
+            }
+        }
+    }
+    let mut points: Points = points.into_iter().collect();
+    points.sort();
+    Tile {
+        name: tile.name.clone(),
+        points,
+    }
+}
+
+// place `copies` of every tile on a `width` by `height` sheet, keeping the
+// pieces `spacing` cells apart. None when they do not fit.
+pub fn nest(
+    tiles: &[Tile],
+    copies: usize,
+    width: usize,
+    height: usize,
+    spacing: usize,
+) -> Option<Nesting> {
+    let pieces: Vec<Tile> = tiles
+        .iter()
+        .flat_map(|t| std::iter::repeat_n(t.clone(), copies))
+        .collect();
+
+    let scale: isize = if spacing > 0 { 2 } else { 1 };
+    let margin = isize::try_from(spacing).unwrap();
+    let grown: Vec<Tile> = pieces.iter().map(|t| grow(t, scale, margin)).collect();
+
+    // the grown pieces may stick out of the sheet by the margin
+    let sheet = Tile::rect(
+        width * scale as usize + 2 * spacing,
+        height * scale as usize + 2 * spacing,
+    );
+    let (rows, infos) = build_rows(
+        &sheet,
+        &grown,
+        &Rules::default(),
+        &mut OrientationCache::new(),
+    );
+
+    let mut m = Matrix::with_secondary(pieces.len(), sheet.len());
+    let mut index: HashMap<usize, usize> = HashMap::new();
+    for (r, row) in rows.iter().enumerate() {
+        // only whole cell positions
+        let anchor = &infos[r].anchor;
+        if anchor.x % scale != 0 || anchor.y % scale != 0 {
+            continue;
+        }
+        let cols: Vec<usize> = row
+            .iter()
+            .map(|c| match (*c as usize) < sheet.len() {
+                true => pieces.len() + *c as usize,
+                false => *c as usize - sheet.len(),
+            })
+            .collect();
+        index.insert(m.add_row(&cols), r);
+    }
+
+    let solution = Solver::new(m).next()?;
+    let mut placed: Vec<Tile> = solution
+        .iter()
+        .map(|id| {
+            let info = &infos[index[id]];
+            Placement {
+                anchor: Point::new(info.anchor.x / scale, info.anchor.y / scale),
+                ..info.clone()
+            }
+            .place(&pieces[info.piece])
+        })
+        .collect();
+    placed.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Some(Nesting {
+        width,
+        height,
+        pieces: placed,
+    })
+}
+
+// closed outlines of the tile as vertex loops, unit squares when the boundary
+// is not a single curve.
+fn outlines(tile: &Tile) -> Vec<Vec<Point>> {
+    let word = match boundary_word(tile) {
+        Some(word) => word,
+        None => {
+            return tile
+                .points
+                .iter()
+                .map(|p| {
+                    vec![
+                        p.clone(),
+                        Point::new(p.x + 1, p.y),
+                        Point::new(p.x + 1, p.y + 1),
+                        Point::new(p.x, p.y + 1),
+                    ]
+                })
+                .collect();
+        }
+    };
+
+    // the boundary starts at the top left corner of the first cell
+    let start = tile.points.iter().min_by_key(|p| (p.y, p.x)).unwrap();
+    let mut vertex = start.clone();
+    let mut corners = Vec::new();
+    let mut heading: Option<Step> = None;
+    for step in word {
+        if heading != Some(step) {
+            corners.push(vertex.clone());
+            heading = Some(step);
+        }
+        vertex += step.delta();
+    }
+    vec![corners]
+}
+
+impl Nesting {
+    // outlines of the sheet and the pieces, with cells of `cell` units.
+    pub fn to_svg(&self, cell: f64) -> String {
+        let width = self.width as f64 * cell;
+        let height = self.height as f64 * cell;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+            width, height
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "  <rect width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"blue\"/>",
+            width, height
+        )
+        .unwrap();
+        for piece in &self.pieces {
+            let mut d = String::new();
+            for corners in outlines(piece) {
+                for (i, p) in corners.iter().enumerate() {
+                    let command = if i == 0 { "M" } else { "L" };
+                    write!(d, "{}{} {} ", command, p.x as f64 * cell, p.y as f64 * cell).unwrap();
+                }
+                d.push('Z');
+            }
+            writeln!(
+                svg,
+                "  <path d=\"{}\" fill=\"none\" stroke=\"black\"><title>{}</title></path>",
+                d, piece.name
+            )
+            .unwrap();
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // piece outlines as DXF lines, one layer per piece, with cells of `cell` units.
+    pub fn to_dxf(&self, cell: f64) -> String {
+        // DXF has the y axis pointing up
+        let height = self.height as isize;
+        let mut dxf = String::from("0\nSECTION\n2\nENTITIES\n");
+        for piece in &self.pieces {
+            for corners in outlines(piece) {
+                for (i, a) in corners.iter().enumerate() {
+                    let b = &corners[(i + 1) % corners.len()];
+                    write!(
+                        dxf,
+                        "0\nLINE\n8\n{}\n10\n{}\n20\n{}\n11\n{}\n21\n{}\n",
+                        piece.name,
+                        a.x as f64 * cell,
+                        (height - a.y) as f64 * cell,
+                        b.x as f64 * cell,
+                        (height - b.y) as f64 * cell,
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        dxf.push_str("0\nENDSEC\n0\nEOF\n");
+        dxf
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::nest;
+    use crate::shape::Point;
+    use crate::shape::Tile;
+
+    #[test]
+    fn spacing() {
+        let tiles = vec![Tile::from_str("A", "x"), Tile::from_str("B", "xx")];
+
+        // touching pieces fit without spacing
+        let nesting = nest(&tiles, 1, 3, 1, 0).unwrap();
+        assert_eq!(nesting.pieces.len(), 2);
+
+        // a gap of one cell needs a wider sheet
+        assert_eq!(nest(&tiles, 1, 3, 1, 1), None);
+        let nesting = nest(&tiles, 1, 4, 1, 1).unwrap();
+        let a = &nesting.pieces[0];
+        let b = &nesting.pieces[1];
+        assert_eq!(a.name, "A");
+        assert!(
+            a.points
+                .iter()
+                .all(|p| b.points.iter().all(|q| (p.x - q.x).abs() > 1))
+        );
+
+        // copies
+        assert_eq!(nest(&tiles, 2, 4, 2, 0).unwrap().pieces.len(), 4);
+        assert_eq!(nest(&tiles, 2, 4, 2, 1), None);
+    }
+
+    #[test]
+    fn export() {
+        let tiles = vec![Tile::from_str("P", "xx\nx")];
+        let nesting = nest(&tiles, 1, 2, 2, 0).unwrap();
+        assert_eq!(nesting.pieces[0].len(), 3);
+        assert!(
+            nesting.pieces[0]
+                .points
+                .iter()
+                .all(|p| *p != Point::new(-1, 0))
+        );
+
+        // an L shape has six corners
+        let svg = nesting.to_svg(10.0);
+        assert!(svg.starts_with("<svg "));
+        assert_eq!(svg.matches("<path ").count(), 1);
+        assert_eq!(svg.matches('L').count(), 5);
+
+        let dxf = nesting.to_dxf(10.0);
+        assert_eq!(dxf.matches("LINE").count(), 6);
+        assert!(dxf.ends_with("EOF\n"));
+    }
+}
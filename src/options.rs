@@ -0,0 +1,149 @@
+// How `Game` searches for solutions.
+
+use crate::dlx::Heuristic;
+use crate::polyomino::Game;
+
+// The exact cover algorithm enumerating the solutions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Algorithm {
+    // dancing links, with the column choice of the heuristic
+    #[default]
+    DancingLinks,
+    // dancing cells, always choosing the column with the fewest rows left
+    DancingCells,
+    // the in-crate search, always choosing the column with the fewest rows
+    // left. The only one that prunes dead regions and reports to observers,
+    // so those run it whatever the algorithm asked for.
+    Search,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveOptions {
+    pub algorithm: Algorithm,
+    pub heuristic: Heuristic,
+    // abandon branches leaving regions the remaining pieces cannot fill,
+    // which runs the search whatever the algorithm
+    pub dead_region_pruning: bool,
+    // drop duplicate and dominated rows when generating them
+    pub row_reduction: bool,
+    // solve batches on the thread pool, with the `parallel` feature
+    pub parallel: bool,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::default(),
+            heuristic: Heuristic::default(),
+            dead_region_pruning: false,
+            row_reduction: false,
+            parallel: cfg!(feature = "parallel"),
+        }
+    }
+}
+
+impl SolveOptions {
+    // options suited to the puzzle, picked from its size, number of pieces
+    // and how much of the board's bounding box it fills.
+    //
+    // Ragged boards and boards with holes split into regions during the
+    // search, where dead region pruning pays off, and the search is the
+    // algorithm that prunes them. Row reduction costs about
+    // rows times columns, and is only worth it while the matrix is moderate
+    // and there are enough pieces for dominated placements to matter. Large
+    // matrices favour the flat arrays of dancing cells, and the thread pool
+    // only pays off beyond a few dozen cells.
+    pub fn auto(game: &Game) -> Self {
+        let board = game.board();
+        let cells = board.len();
+        let pieces = game.piece_names().len();
+        let rows = game.matrix().1.len();
+        let size = board.size();
+        let filled = match cells {
+            0 => 1.0,
+            _ => cells as f64 / (size.width * size.height) as f64,
+        };
+
+        let dead_region_pruning = filled < 0.8;
+        let algorithm = match (dead_region_pruning, rows > 20_000) {
+            (true, _) => Algorithm::Search,
+            (false, true) => Algorithm::DancingCells,
+            (false, false) => Algorithm::DancingLinks,
+        };
+        Self {
+            algorithm,
+            heuristic: Heuristic::MinimumRemaining,
+            dead_region_pruning,
+            row_reduction: pieces >= 6 && rows * game.n_cols() <= 2_000_000,
+            parallel: cfg!(feature = "parallel") && cells >= 40,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Algorithm;
+    use super::SolveOptions;
+    use crate::polyomino::Game;
+
+    #[test]
+    fn auto() {
+        let game = Game::from_yaml(
+            "Board: |\n    xxx\n    xxx\nL: |\n    xx\n    x\nJ: |\n    xx\n     x\n",
+        )
+        .unwrap();
+        let options = SolveOptions::auto(&game);
+        assert_eq!(options.algorithm, Algorithm::DancingLinks);
+        assert!(!options.dead_region_pruning);
+        assert!(!options.row_reduction);
+        assert!(!options.parallel);
+
+        // a ring is mostly hole
+        let mut yaml = "Board: |\n    xxxx\n    x  x\n    x  x\n    xxxx\n".to_string();
+        for i in 0..6 {
+            yaml += &format!("D{}: |\n    xx\n", i);
+        }
+        let mut game = Game::from_yaml(&yaml).unwrap();
+        let count = game.count_solutions();
+        let options = SolveOptions::auto(&game);
+        assert!(options.dead_region_pruning);
+        assert!(options.row_reduction);
+        assert_eq!(options.algorithm, Algorithm::Search);
+
+        game.set_options(&options);
+        assert_eq!(game.algorithm(), options.algorithm);
+        assert_eq!(game.count_solutions(), count);
+        assert!(game.solve().is_some());
+    }
+
+    #[test]
+    fn algorithm() {
+        let mut game = Game::from_yaml(
+            "Board: |\n    xxx\n    xxx\nL: |\n    xx\n    x\nJ: |\n    xx\n     x\n",
+        )
+        .unwrap();
+        assert_eq!(game.algorithm(), Algorithm::DancingLinks);
+        for algorithm in [
+            Algorithm::DancingCells,
+            Algorithm::Search,
+            Algorithm::DancingLinks,
+        ] {
+            game.set_options(&SolveOptions {
+                algorithm,
+                ..SolveOptions::default()
+            });
+            assert_eq!(game.algorithm(), algorithm);
+            assert_eq!(game.count_solutions(), 4);
+        }
+
+        // pruning needs the search
+        game.set_options(&SolveOptions {
+            algorithm: Algorithm::DancingCells,
+            dead_region_pruning: true,
+            ..SolveOptions::default()
+        });
+        assert_eq!(game.algorithm(), Algorithm::Search);
+        game.set_dead_region_pruning(false);
+        assert_eq!(game.algorithm(), Algorithm::DancingCells);
+    }
+}
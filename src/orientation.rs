@@ -0,0 +1,145 @@
+// Distinct orientations of piece shapes, computed once per shape.
+//
+// Shapes are keyed by their cells moved to the origin, so identical pieces
+// (and rebuilds of the same puzzle) share one entry.
+
+use std::collections::HashMap;
+
+use crate::placement::Transform;
+use crate::shape::Point;
+use crate::shape::Points;
+use crate::shape::Tile;
+
+// An orientation's cells (at the origin, sorted) and the mask of the
+// transforms producing it.
+pub type Orientation = (Points, u8);
+
+#[derive(Debug, Default, Clone)]
+pub struct OrientationCache {
+    shapes: HashMap<Points, Vec<Orientation>>,
+}
+
+// cells of the tile moved to the origin, sorted.
+fn normalized(tile: &Tile) -> Points {
+    let mut t = tile.clone();
+    if !t.points.is_empty() {
+        t.translate(&-t.offset());
+    }
+    t.points.sort();
+    t.points
+}
+
+// coordinates transformed at once: an octomino fits in one chunk.
+const LANES: usize = 8;
+
+// cells of the shape (at the origin) under the transform, moved back to the
+// origin and sorted. The cells go through the matrix in fixed-size chunks of
+// `LANES` coordinates, and the points stay inline in `Points`, so nothing is
+// allocated for shapes of up to 12 cells.
+fn oriented(shape: &Points, transform: Transform) -> Points {
+    let [a, b, c, d] = transform.matrix();
+    let mut points = Points::new();
+    let (mut min_x, mut min_y) = (0, 0);
+    for chunk in shape.chunks(LANES) {
+        let mut xs = [0isize; LANES];
+        let mut ys = [0isize; LANES];
+        for (i, p) in chunk.iter().enumerate() {
+            xs[i] = p.x;
+            ys[i] = p.y;
+        }
+        let tx: [isize; LANES] = std::array::from_fn(|i| a * xs[i] + b * ys[i]);
+        let ty: [isize; LANES] = std::array::from_fn(|i| c * xs[i] + d * ys[i]);
+        for (x, y) in tx.into_iter().zip(ty).take(chunk.len()) {
+            if points.is_empty() {
+                (min_x, min_y) = (x, y);
+            }
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            points.push(Point::new(x, y));
+        }
+    }
+    for p in points.iter_mut() {
+        p.x -= min_x;
+        p.y -= min_y;
+    }
+    points.sort();
+    points
+}
+
+impl OrientationCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // number of distinct shapes seen.
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    // number of orientations computed, over all shapes.
+    pub fn generated(&self) -> usize {
+        self.shapes.values().map(|o| o.len()).sum()
+    }
+
+    // distinct orientations of the tile's shape, in order of their first transform.
+    pub fn orientations(&mut self, tile: &Tile) -> &[Orientation] {
+        let key = normalized(tile);
+        self.shapes.entry(key).or_insert_with_key(|key| {
+            let mut orientations: Vec<Orientation> = Vec::new();
+            for o in 0..8 {
+                let points = oriented(key, Transform(o));
+                match orientations.iter_mut().find(|(p, _)| *p == points) {
+                    Some((_, mask)) => *mask |= 1 << o,
+                    None => orientations.push((points, 1 << o)),
+                }
+            }
+            orientations
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::OrientationCache;
+    use super::normalized;
+    use super::oriented;
+    use crate::placement::Transform;
+    use crate::shape::Point;
+    use crate::shape::Tile;
+
+    #[test]
+    fn shared() {
+        let mut cache = OrientationCache::new();
+        assert_eq!(cache.orientations(&Tile::from_str("O", "xx\nxx")).len(), 1);
+        assert_eq!(cache.orientations(&Tile::from_str("I", "xxx")).len(), 2);
+
+        // an L has eight orientations, one per transform
+        let l = cache.orientations(&Tile::from_str("L", "x\nx\nxx"));
+        assert_eq!(l.len(), 8);
+        assert!(l.iter().all(|(_, mask)| mask.count_ones() == 1));
+
+        // the same shape elsewhere, under another name, hits the cache
+        let mut moved = Tile::from_str("L2", "x\nx\nxx");
+        moved.translate(&Point::new(3, 4));
+        cache.orientations(&moved);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn chunks() {
+        // ten cells, over two chunks of coordinates
+        let tile = Tile::from_str("W", "xxx\n  xxxx\n     xx\n      x");
+        for o in 0..8 {
+            let mut applied = tile.clone();
+            Transform(o).apply(&mut applied);
+            assert_eq!(
+                oriented(&normalized(&tile), Transform(o)),
+                normalized(&applied)
+            );
+        }
+    }
+}
@@ -0,0 +1,268 @@
+// Parallel enumeration of all exact covers.
+//
+// The search is split by the rows of the first column the solver would branch
+// on (the one with the fewest rows). Every branch is an independent exact
+// cover problem with the row's columns taken out, solved on the thread pool.
+//
+// `solve_all_stealing` splits further: as long as a branch has many rows left
+// it branches again in parallel, so rayon's work stealing keeps all threads
+// busy when the first level branches are uneven. Solutions are collected in a
+// shared set, which drops duplicates.
+//
+// `solve_all_ordered` splits the same way, but tags every solution with the
+// positions of its branches and its place among the solutions of the final
+// branch. Sorting by the tags gives back the order in which a single thread
+// would have found the solutions, whatever order the threads finished in.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use rayon::prelude::*;
+
+use crate::dlx::Matrix;
+use crate::exact_cover::ExactCover;
+
+// all solutions as sorted indices into `rows`, in sorted order.
+pub fn solve_all(n_cols: usize, rows: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    if n_cols == 0 {
+        return vec![Vec::new()];
+    }
+    let mut by_col: Vec<Vec<usize>> = vec![Vec::new(); n_cols];
+    for (r, row) in rows.iter().enumerate() {
+        for c in row {
+            by_col[*c].push(r);
+        }
+    }
+    let branches = by_col.iter().min_by_key(|rows| rows.len()).unwrap();
+
+    let mut solutions: Vec<Vec<usize>> = branches
+        .par_iter()
+        .flat_map_iter(|first| {
+            let mut m = Matrix::new(n_cols);
+            let mut index = Vec::with_capacity(rows.len());
+            for row in rows {
+                index.push(m.add_row(row));
+            }
+            for c in &rows[*first] {
+                ExactCover::cover(&mut m, *c);
+            }
+
+            // map node ids back to row indices
+            ExactCover::solve_iter(Box::new(m)).map(move |solution| {
+                let mut solution: Vec<usize> = solution
+                    .iter()
+                    .map(|id| index.binary_search(id).unwrap())
+                    .chain([*first])
+                    .collect();
+                solution.sort();
+                solution
+            })
+        })
+        .collect();
+    solutions.sort();
+    solutions
+}
+
+struct Stealing<'a> {
+    n_cols: usize,
+    // branches with more rows than this are split again
+    split_rows: usize,
+    rows: &'a [Vec<usize>],
+    by_col: Vec<Vec<usize>>,
+    // the solutions, each with its branch positions followed by its place
+    // in the final branch
+    solutions: Mutex<Vec<(Vec<usize>, Vec<usize>)>>,
+}
+
+impl Stealing<'_> {
+    // whether the row avoids the covered columns.
+    fn fits(&self, covered: &[bool], row: usize) -> bool {
+        self.rows[row].iter().all(|c| !covered[*c])
+    }
+
+    fn explore(&self, covered: Vec<bool>, partial: Vec<usize>, tag: Vec<usize>) {
+        let alive: Vec<usize> = (0..self.rows.len())
+            .filter(|r| self.fits(&covered, *r))
+            .collect();
+
+        // the uncovered column with the fewest rows left
+        let branch = (0..self.n_cols)
+            .filter(|c| !covered[*c])
+            .map(|c| {
+                let rows: Vec<usize> = self.by_col[c]
+                    .iter()
+                    .copied()
+                    .filter(|r| self.fits(&covered, *r))
+                    .collect();
+                rows
+            })
+            .min_by_key(|rows| rows.len());
+        let branch = match branch {
+            Some(branch) => branch,
+            None => {
+                let mut solution = partial;
+                solution.sort();
+                self.solutions.lock().unwrap().push((tag, solution));
+                return;
+            }
+        };
+
+        if alive.len() > self.split_rows {
+            branch.par_iter().enumerate().for_each(|(i, row)| {
+                let mut covered = covered.clone();
+                for c in &self.rows[*row] {
+                    covered[*c] = true;
+                }
+                let mut partial = partial.clone();
+                partial.push(*row);
+                let mut tag = tag.clone();
+                tag.push(i);
+                self.explore(covered, partial, tag);
+            });
+            return;
+        }
+
+        // small enough, solve the rest on this thread
+        let mut m = Matrix::new(self.n_cols);
+        let index: Vec<usize> = alive.iter().map(|r| m.add_row(&self.rows[*r])).collect();
+        for (c, covered) in covered.iter().enumerate() {
+            if *covered {
+                ExactCover::cover(&mut m, c);
+            }
+        }
+        let mut found = Vec::new();
+        for (i, solution) in ExactCover::solve_iter(Box::new(m)).enumerate() {
+            let mut solution: Vec<usize> = solution
+                .iter()
+                .map(|id| alive[index.binary_search(id).unwrap()])
+                .chain(partial.iter().copied())
+                .collect();
+            solution.sort();
+            let mut tag = tag.clone();
+            tag.push(i);
+            found.push((tag, solution));
+        }
+        self.solutions.lock().unwrap().extend(found);
+    }
+
+    // explore the whole search tree, returning the tagged solutions.
+    fn run(n_cols: usize, rows: &[Vec<usize>], split_rows: usize) -> Vec<(Vec<usize>, Vec<usize>)> {
+        let mut by_col: Vec<Vec<usize>> = vec![Vec::new(); n_cols];
+        for (r, row) in rows.iter().enumerate() {
+            for c in row {
+                by_col[*c].push(r);
+            }
+        }
+        let stealing = Stealing {
+            n_cols,
+            split_rows,
+            rows,
+            by_col,
+            solutions: Mutex::new(Vec::new()),
+        };
+        stealing.explore(vec![false; n_cols], Vec::new(), Vec::new());
+        stealing.solutions.into_inner().unwrap()
+    }
+}
+
+// all distinct solutions as sorted indices into `rows`, in sorted order,
+// splitting branches with more than `split_rows` rows over the thread pool.
+pub fn solve_all_stealing(
+    n_cols: usize,
+    rows: &[Vec<usize>],
+    split_rows: usize,
+) -> Vec<Vec<usize>> {
+    let solutions: HashSet<Vec<usize>> = Stealing::run(n_cols, rows, split_rows)
+        .into_iter()
+        .map(|(_, solution)| solution)
+        .collect();
+    let mut solutions: Vec<Vec<usize>> = solutions.into_iter().collect();
+    solutions.sort();
+    solutions
+}
+
+// all solutions as sorted indices into `rows`, in the order the dancing links
+// solver finds them choosing the column with the fewest rows, splitting like
+// `solve_all_stealing`.
+pub fn solve_all_ordered(n_cols: usize, rows: &[Vec<usize>], split_rows: usize) -> Vec<Vec<usize>> {
+    let mut solutions = Stealing::run(n_cols, rows, split_rows);
+    solutions.sort();
+    solutions
+        .into_iter()
+        .map(|(_, solution)| solution)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::solve_all;
+    use super::solve_all_ordered;
+    use super::solve_all_stealing;
+    use crate::dlx::Matrix;
+    use crate::exact_cover::ExactCover;
+
+    #[test]
+    fn branches() {
+        let rows = vec![vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3], vec![0]];
+        assert_eq!(solve_all(4, &rows), vec![vec![0, 1], vec![2, 3]]);
+
+        // a column without rows has no solutions
+        assert!(solve_all(5, &rows).is_empty());
+    }
+
+    #[test]
+    fn stealing() {
+        // a strip of cells covered by monominoes and dominoes, big enough to split
+        let n = 12;
+        let mut rows = Vec::new();
+        for i in 0..n {
+            rows.push(vec![i]);
+            if i + 1 < n {
+                rows.push(vec![i, i + 1]);
+            }
+        }
+
+        let solutions = solve_all_stealing(n, &rows, 4);
+        assert_eq!(solutions.len(), 233);
+        assert_eq!(solutions, solve_all(n, &rows));
+        assert_eq!(solve_all_stealing(4, &rows[..7], 4).len(), 5);
+    }
+
+    #[test]
+    fn ordered() {
+        // the search branches on column 1, trying row 1 before row 2
+        let rows = vec![vec![0], vec![0, 1], vec![1], vec![0]];
+        assert_eq!(solve_all(2, &rows), vec![vec![0, 2], vec![1], vec![2, 3]]);
+        for split_rows in [0, 1000] {
+            assert_eq!(
+                solve_all_ordered(2, &rows, split_rows),
+                vec![vec![1], vec![0, 2], vec![2, 3]]
+            );
+        }
+
+        // the order of a single solver, mapped from node ids to row indices
+        let n = 12;
+        let mut rows = Vec::new();
+        for i in (0..n).rev() {
+            rows.push(vec![i]);
+            if i + 1 < n {
+                rows.push(vec![i, i + 1]);
+            }
+        }
+        let mut m = Matrix::new(n);
+        let index: Vec<usize> = rows.iter().map(|row| m.add_row(row)).collect();
+        let expected: Vec<Vec<usize>> = ExactCover::solve_iter(Box::new(m))
+            .map(|solution| {
+                let mut solution: Vec<usize> = solution
+                    .iter()
+                    .map(|id| index.binary_search(id).unwrap())
+                    .collect();
+                solution.sort();
+                solution
+            })
+            .collect();
+        for split_rows in [4, 10, 1000] {
+            assert_eq!(solve_all_ordered(n, &rows, split_rows), expected);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// Checkerboard coloring argument.
+//
+// Color the cells like a checkerboard. Wherever a piece is placed it covers
+// either `d` more black than white cells or `d` more white than black ones, so
+// a tiling needs signs with `±d_1 ± d_2 ± ... = black - white` of the board.
+use crate::shape::Tile;
+use std::collections::HashSet;
+
+// black minus white cells, with black where x + y is even.
+pub fn balance(tile: &Tile) -> isize {
+    tile.points
+        .iter()
+        .map(|p| {
+            if (p.x + p.y).rem_euclid(2) == 0 {
+                1
+            } else {
+                -1
+            }
+        })
+        .sum()
+}
+
+// whether the piece imbalances can add up to the board imbalance.
+pub fn feasible(board: &Tile, tiles: &[Tile]) -> bool {
+    let mut sums: HashSet<isize> = HashSet::from([0]);
+    for tile in tiles {
+        let d = balance(tile).abs();
+        if d == 0 {
+            continue;
+        }
+        sums = sums.iter().flat_map(|s| [s + d, s - d]).collect();
+    }
+    sums.contains(&balance(board))
+}
+
+#[cfg(test)]
+mod test {
+    use super::balance;
+    use super::feasible;
+    use crate::shape::Tile;
+
+    #[test]
+    fn basics() {
+        assert_eq!(balance(&Tile::from_str("T", "xxx\n x")), 2);
+        assert_eq!(balance(&Tile::from_str("I", "xxxx")), 0);
+        assert_eq!(balance(&Tile::from_str("B", "xxx\nxxx")), 0);
+
+        // a T-tetromino is unbalanced, so it needs a second one to cover a balanced board
+        let board = Tile::from_str("Board", "xxxx\nxxxx");
+        let t = Tile::from_str("T", "xxx\n x");
+        assert!(!feasible(
+            &board,
+            &[t.clone(), Tile::from_str("O", "xx\nxx")]
+        ));
+        assert!(feasible(&board, &[t.clone(), t]));
+
+        // the 8x8 board minus two opposite corners cannot be covered by dominoes
+        let board = Tile::from_str(
+            "Board",
+            " xxxxxxx\nxxxxxxxx\nxxxxxxxx\nxxxxxxxx\nxxxxxxxx\nxxxxxxxx\nxxxxxxxx\nxxxxxxx ",
+        );
+        let dominoes: Vec<Tile> = (0..31).map(|_| Tile::from_str("D", "xx")).collect();
+        assert!(!feasible(&board, &dominoes));
+    }
+}
@@ -1,31 +1,169 @@
-// Parser error.
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParserError<'a> {
+// Parser error, with the line it occurred on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserError {
     at: usize,
-    context: &'a str,
-    message: &'a str,
+    // 1-based, the column counting characters
+    line: usize,
+    column: usize,
+    context: String,
+    message: String,
 }
 
-impl<'a> ParserError<'a> {
-    pub fn new(message: &'a str, context: &'a str, at: usize) -> Self {
+impl ParserError {
+    // the error at byte offset `at` of the contents.
+    pub fn new(message: &str, contents: &str, at: usize) -> Self {
+        let before = &contents[..at];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line_end = contents[at..].find('\n').map_or(contents.len(), |i| at + i);
         Self {
-            message: message,
-            at: at,
-            context: context,
+            at,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            context: contents[line_start..line_end].to_string(),
+            message: message.to_string(),
         }
     }
+
+    // byte offset in the contents where reading failed.
+    pub fn at(&self) -> usize {
+        self.at
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    // the line of the contents the error is on.
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
 }
 
-impl std::error::Error for ParserError<'_> {}
+impl std::error::Error for ParserError {}
 
-impl std::fmt::Display for ParserError<'_> {
+// the message and position, then the line with a caret under the column.
+impl std::fmt::Display for ParserError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        f.write_fmt(format_args!("ParserError at {:?}", self.at))
+        writeln!(
+            f,
+            "{} at line {}, column {}:",
+            self.message, self.line, self.column
+        )?;
+        writeln!(f, "{}", self.context)?;
+        write!(f, "{}^", " ".repeat(self.column - 1))
+    }
+}
+
+// Characters drawings use for filled and blank cells, besides the 'x' and
+// spaces every drawing may use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Charset {
+    pub filled: Vec<char>,
+    pub blank: Vec<char>,
+}
+
+impl Charset {
+    pub fn new(filled: &str, blank: &str) -> Self {
+        Self {
+            filled: filled.chars().collect(),
+            blank: blank.chars().collect(),
+        }
+    }
+
+    // read "filled" and "blank" lines, each followed by its characters, as in
+    // "filled #*". None on any other line.
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        let mut charset = Self::default();
+        for line in spec.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (kind, chars) = line.split_once(' ').unwrap_or((line, ""));
+            let chars = chars.chars().filter(|c| !c.is_whitespace());
+            match kind {
+                "filled" => charset.filled.extend(chars),
+                "blank" => charset.blank.extend(chars),
+                _ => return None,
+            }
+        }
+        Some(charset)
+    }
+
+    // the drawing with filled characters as 'x' and blank ones as spaces.
+    pub fn normalize(&self, drawing: &str) -> String {
+        drawing
+            .chars()
+            .map(|c| match c {
+                _ if self.filled.contains(&c) => 'x',
+                _ if self.blank.contains(&c) => ' ',
+                _ => c,
+            })
+            .collect()
     }
 }
 
+// Columns between tab stops, for tabs in indentation and drawings.
+pub const TAB_WIDTH: usize = 4;
+
+// the indent after one more character of indentation: a space is one column
+// and a tab goes on to the next tab stop. None for any other character.
+fn indent_step(indent: usize, chr: char, tab_width: usize) -> Option<usize> {
+    match chr {
+        ' ' => Some(indent + 1),
+        '\t' => Some((indent / tab_width + 1) * tab_width),
+        _ => None,
+    }
+}
+
+// the text with every tab replaced by spaces up to the next tab stop.
+pub fn expand_tabs(contents: &str, tab_width: usize) -> String {
+    let mut expanded = String::with_capacity(contents.len());
+    let mut col = 0;
+    for c in contents.chars() {
+        match c {
+            '\t' => {
+                let stop = (col / tab_width + 1) * tab_width;
+                expanded.extend(std::iter::repeat_n(' ', stop - col));
+                col = stop;
+            }
+            '\n' => {
+                expanded.push(c);
+                col = 0;
+            }
+            _ => {
+                expanded.push(c);
+                col += 1;
+            }
+        }
+    }
+    expanded
+}
+
 // Parser.
 pub fn parse(contents: &str) -> Result<Vec<(&str, &str)>, ParserError> {
+    parse_with_tab_width(contents, TAB_WIDTH)
+}
+
+// Parser for indentation with tabs of the given width. A tab after spaces
+// reads differently in every editor and is an error.
+//
+// Comments start with '#' on a line of their own or after a key, as in YAML.
+// Within a block every line is part of the drawing, '#' included, and a
+// comment less indented than the block ends it: drawing lines after it are an
+// error, as in YAML.
+//
+// A value on the line of its key is read as is, so one-row pieces need no
+// block. Values in other YAML forms (quoted, flow, anchors and aliases) are
+// errors.
+pub fn parse_with_tab_width(
+    contents: &str,
+    tab_width: usize,
+) -> Result<Vec<(&str, &str)>, ParserError> {
     let mut parts: Vec<(&str, &str)> = Vec::new();
 
     #[derive(Debug, PartialEq)]
@@ -34,9 +172,12 @@ pub fn parse(contents: &str) -> Result<Vec<(&str, &str)>, ParserError> {
         Identifier,
         Multiline { indent: usize },
         Skip,
+        Comment,
     }
     let mut state = State::Identifier;
     let mut indent = 0;
+    // whether the indentation of the line so far has spaces
+    let mut spaced = false;
     let mut start = 0;
     let mut key = "";
 
@@ -44,21 +185,30 @@ pub fn parse(contents: &str) -> Result<Vec<(&str, &str)>, ParserError> {
     for (idx, chr) in chars {
         match state {
             State::Value => {
-                let rest_of_line = contents[start..idx].trim();
+                let rest_of_line = strip_comment(&contents[start..idx]).trim();
 
                 if let '\n' = chr {
                     if rest_of_line.is_empty() {
+                        // a key without a value
+                        parts.push((key, ""));
                         state = State::Skip;
                     } else {
                         if rest_of_line == "|" {
+                            // the first line belongs to the block whatever its
+                            // indent, which still must not mix tabs and spaces
+                            line_indent(contents, idx + 1, tab_width)?;
                             state = State::Multiline { indent };
                             start = idx + 1;
+                        } else if !rest_of_line
+                            .starts_with(['|', '>', '"', '\'', '{', '[', '&', '*', '!'])
+                        {
+                            parts.push((key, rest_of_line));
+                            state = State::Skip;
                         } else {
-                            return Err(ParserError::new(
-                                "Unknown value",
-                                &contents[start..idx],
-                                idx,
-                            ));
+                            // point at the value, past the spaces after the key
+                            let value = contents[start..idx].len()
+                                - contents[start..idx].trim_start().len();
+                            return Err(ParserError::new("Unknown value", contents, start + value));
                         }
                     }
                     indent = 0;
@@ -68,25 +218,11 @@ pub fn parse(contents: &str) -> Result<Vec<(&str, &str)>, ParserError> {
                 indent: current_indent,
             } => {
                 if let '\n' = chr {
-                    // Peek ahead.
-                    let upcoming_line = &contents[(idx + '\n'.len_utf8())..];
-                    let mut upcoming_indent = 0;
-                    let mut is_empty = false;
-
-                    // Count indent up to first actual char.
-                    for chr in upcoming_line.chars() {
-                        if let '\n' = chr {
-                            // Allow empty lines
-                            is_empty = true;
-                            break;
-                        } else if let ' ' = chr {
-                            upcoming_indent += 1;
-                        } else {
-                            break;
-                        }
-                    }
-                    if !is_empty && upcoming_indent <= current_indent {
-                        parts.push((key, &contents[start..idx]));
+                    // Peek ahead, empty lines are allowed.
+                    let upcoming = line_indent(contents, idx + '\n'.len_utf8(), tab_width)?;
+                    if upcoming.is_some_and(|upcoming| upcoming <= current_indent) {
+                        let value = contents[start..idx].trim_end_matches('\r');
+                        parts.push((key, value));
                         state = State::Skip;
                         indent = 0;
                     }
@@ -94,7 +230,16 @@ pub fn parse(contents: &str) -> Result<Vec<(&str, &str)>, ParserError> {
             }
             State::Identifier => {
                 // Skip initial YAML dashes
-                if let '-' = chr {
+                if chr == '#' && contents[start..idx].trim().is_empty() {
+                    state = State::Comment;
+                } else if chr == '\n' && !contents[start..idx].trim().is_empty() {
+                    let line = contents[start..idx].len() - contents[start..idx].trim_start().len();
+                    return Err(ParserError::new(
+                        "Line without a key",
+                        contents,
+                        start + line,
+                    ));
+                } else if let '-' = chr {
                     start = idx + '-'.len_utf8();
                 } else if let ':' = chr {
                     key = &contents[start..idx].trim();
@@ -103,20 +248,37 @@ pub fn parse(contents: &str) -> Result<Vec<(&str, &str)>, ParserError> {
                 }
             }
             State::Skip => {
-                if let ' ' = chr {
-                    indent += 1;
+                if let '\n' = chr {
+                    indent = 0;
+                    spaced = false;
+                } else if let Some(next) = indent_step(indent, chr, tab_width) {
+                    if chr == '\t' && spaced {
+                        return Err(mixed(contents, idx));
+                    }
+                    spaced |= chr == ' ';
+                    indent = next;
+                } else if let '#' = chr {
+                    state = State::Comment;
                 } else if !chr.is_whitespace() {
                     state = State::Identifier;
                     start = idx;
+                    spaced = false;
+                }
+            }
+            State::Comment => {
+                if let '\n' = chr {
+                    state = State::Skip;
+                    indent = 0;
+                    spaced = false;
                 }
             }
         }
     }
     // Ending e.g. without newline
-    if state != State::Skip {
+    if state != State::Skip && state != State::Comment {
         return Err(ParserError::new(
             "Unfinished data. Perhaps missing new line.",
-            &contents[start..],
+            contents,
             start,
         ));
     }
@@ -124,9 +286,97 @@ pub fn parse(contents: &str) -> Result<Vec<(&str, &str)>, ParserError> {
     Ok(parts)
 }
 
+// the line up to a comment: a '#' at its start or after whitespace.
+fn strip_comment(line: &str) -> &str {
+    let mut previous = ' ';
+    for (i, c) in line.char_indices() {
+        if c == '#' && previous.is_whitespace() {
+            return &line[..i];
+        }
+        previous = c;
+    }
+    line
+}
+
+// the indent of the line starting at byte offset `line_start`, None for an
+// empty line.
+fn line_indent(
+    contents: &str,
+    line_start: usize,
+    tab_width: usize,
+) -> Result<Option<usize>, ParserError> {
+    let mut indent = 0;
+    let mut spaced = false;
+    // Count indent up to first actual char.
+    for (i, chr) in contents[line_start..].char_indices() {
+        if let '\n' = chr {
+            return Ok(None);
+        } else if let '\r' = chr {
+            // Windows line endings
+            continue;
+        } else if let Some(next) = indent_step(indent, chr, tab_width) {
+            if chr == '\t' && spaced {
+                return Err(mixed(contents, line_start + i));
+            }
+            spaced |= chr == ' ';
+            indent = next;
+        } else {
+            break;
+        }
+    }
+    Ok(Some(indent))
+}
+
+fn mixed(contents: &str, at: usize) -> ParserError {
+    ParserError::new(
+        "Tab after spaces in indentation, indent with either",
+        contents,
+        at,
+    )
+}
+
+// Parser for full YAML, for puzzle files written with other YAML tools:
+// quoted keys, flow mappings, anchors and plain values. The first document
+// must be a mapping; values are strings, numbers or booleans, or empty.
+#[cfg(feature = "yaml")]
+pub fn parse_yaml(contents: &str) -> Result<Vec<(String, String)>, ParserError> {
+    use yaml_rust2::Yaml;
+    use yaml_rust2::YamlLoader;
+
+    let documents = YamlLoader::load_from_str(contents).map_err(|e| {
+        // markers count characters
+        let index = e.marker().index();
+        let at = contents
+            .char_indices()
+            .nth(index)
+            .map_or(contents.len(), |(i, _)| i);
+        ParserError::new(e.info(), contents, at)
+    })?;
+    let text = |value: &Yaml| match value {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.trim_end_matches('\n').to_string()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        Yaml::Null => Some(String::new()),
+        _ => None,
+    };
+    let Some(Yaml::Hash(hash)) = documents.into_iter().next() else {
+        return Err(ParserError::new("Expected a mapping", contents, 0));
+    };
+    hash.iter()
+        .map(|(key, value)| match (text(key), text(value)) {
+            (Some(key), Some(value)) => Ok((key, value)),
+            _ => Err(ParserError::new("Unknown value", contents, 0)),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
+    use super::Charset;
+    use super::ParserError;
+    use super::expand_tabs;
     use super::parse;
+    use super::parse_with_tab_width;
 
     #[test]
     pub fn basics() {
@@ -140,12 +390,123 @@ mod test {
             "   xxx\n",
             "    x\n",
             "Y: |\n",
-            "  xxx\n"
+            "  xxx\n",
+            "Z:\n",
         );
         let map = parse(contents).unwrap();
 
         assert_eq!(map[0], ("Board", "    xxxx\n    xxxx"));
         assert_eq!(map[1], ("X", "    x\n   xxx\n    x"));
         assert_eq!(map[2], ("Y", "  xxx"));
+        assert_eq!(map[3], ("Z", ""));
+    }
+
+    #[test]
+    fn crlf() {
+        let contents = "Board: |\r\n    xx\r\n\r\n    xx\r\nA: |\r\n    x\r\nB:\r\n";
+        let map = parse(contents).unwrap();
+        assert_eq!(map[0], ("Board", "    xx\r\n\r\n    xx"));
+        assert_eq!(map[1], ("A", "    x"));
+        assert_eq!(map[2], ("B", ""));
+    }
+
+    #[test]
+    fn tabs() {
+        let map = parse("Board: |\n\txx\n\txx\nA: |\n\t x\n").unwrap();
+        assert_eq!(map[0], ("Board", "\txx\n\txx"));
+        assert_eq!(map[1], ("A", "\t x"));
+        assert_eq!(expand_tabs("\tx\n x\ty", 4), "    x\n x  y");
+
+        // the tab reaches past the key's indent only when wider than it
+        let contents = "B:\n  A: |\n\tx\n\ty\n";
+        assert_eq!(parse(contents).unwrap()[1], ("A", "\tx\n\ty"));
+        assert!(parse_with_tab_width(contents, 2).is_err());
+
+        let error = parse("A: |\n  \tx\n").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 3));
+        assert!(parse("A: |\n  x\n  \tx\n").is_err());
+    }
+
+    #[test]
+    fn comments() {
+        let contents = concat!(
+            "# Pentomino puzzle, 2 solutions\n",
+            "---\n",
+            "Board: |  # from a puzzle book\n",
+            "    ##\n",
+            "# the end of the board\n",
+            "X: |\n",
+            "    # x\n",
+            "Z: # no drawing\n",
+            "# trailing",
+        );
+        let map = parse(contents).unwrap();
+        assert_eq!(map, [("Board", "    ##"), ("X", "    # x"), ("Z", "")]);
+        assert!(parse("A: |#\n    x\n").is_err());
+
+        // a comment at the start of a line ends the block
+        let error = parse("Board: |\n    xx\n# note\n    xx\nA: |\n    xx\n").unwrap_err();
+        assert_eq!((error.line(), error.column()), (4, 5));
+        assert_eq!(
+            parse("Board: |\n    xx\n    # note\n    xx\n").unwrap(),
+            [("Board", "    xx\n    # note\n    xx")]
+        );
+    }
+
+    #[test]
+    fn inline() {
+        let map = parse("I: xxxxx\nL: xxx/x  # one-sided\nBoard: |\n    xx\n").unwrap();
+        assert_eq!(map, [("I", "xxxxx"), ("L", "xxx/x"), ("Board", "    xx")]);
+        assert!(parse("A: |-\n    x\n").is_err());
+        assert!(parse("A: *rows\n").is_err());
+    }
+
+    #[test]
+    fn charset() {
+        let charset = Charset::from_spec("filled #*\nblank .\n").unwrap();
+        assert_eq!(charset, Charset::new("#*", "."));
+        assert_eq!(charset.normalize("#*.\n.x#"), "xx \n xx");
+        assert_eq!(Charset::from_spec("full #"), None);
+    }
+
+    #[test]
+    fn errors() {
+        let error = parse("Board: |\n    xx\nA: 'xx'\n").unwrap_err();
+        assert_eq!((error.line(), error.column(), error.at()), (3, 4, 19));
+        assert_eq!(error.context(), "A: 'xx'");
+        assert_eq!(
+            error.to_string(),
+            "Unknown value at line 3, column 4:\nA: 'xx'\n   ^"
+        );
+
+        // columns count characters, not bytes
+        let error = ParserError::new("Oops", "Å: ø\n", 4);
+        assert_eq!((error.line(), error.column()), (1, 4));
+        assert!(parse("Board: |\n    xx").is_err());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn full_yaml() {
+        use super::parse_yaml;
+
+        let contents = concat!(
+            "---\n",
+            "\"Board\": &rows |\n",
+            "    xxxx\n",
+            "    xxxx\n",
+            "X: *rows\n",
+            "Topology: torus\n",
+            "Z:\n",
+        );
+        let map = parse_yaml(contents).unwrap();
+        assert_eq!(map[0], ("Board".to_string(), "xxxx\nxxxx".to_string()));
+        assert_eq!(map[1].1, map[0].1);
+        assert_eq!(map[2], ("Topology".to_string(), "torus".to_string()));
+        assert_eq!(map[3], ("Z".to_string(), String::new()));
+
+        let error = parse_yaml("Board: [x\n").unwrap_err();
+        assert_eq!(error.line(), 2);
+        assert!(parse_yaml("A: {b: c}\n").is_err());
     }
 }
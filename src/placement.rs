@@ -0,0 +1,170 @@
+// Where a piece goes on the board: which piece, in which orientation, and at
+// which position.
+
+use std::fmt;
+
+use crate::shape::Point;
+use crate::shape::Tile;
+
+// Index of a piece in the game.
+pub type PieceId = usize;
+
+// Orientation of a piece: mirrored when `>= 4`, then rotated `% 4` times
+// counter-clockwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Transform(pub u8);
+
+impl Transform {
+    pub fn is_mirrored(&self) -> bool {
+        self.0 >= 4
+    }
+
+    pub fn rotations(&self) -> u8 {
+        self.0 % 4
+    }
+
+    // the transform as a matrix [a, b, c, d], mapping (x, y) to
+    // (a x + b y, c x + d y).
+    pub fn matrix(&self) -> [isize; 4] {
+        let mut m = if self.is_mirrored() {
+            [-1, 0, 0, 1]
+        } else {
+            [1, 0, 0, 1]
+        };
+        for _ in 0..self.rotations() {
+            m = [m[2], m[3], -m[0], -m[1]];
+        }
+        m
+    }
+
+    // orient the tile in place, in a single pass over its points.
+    pub fn apply(&self, tile: &mut Tile) {
+        let [a, b, c, d] = self.matrix();
+        for p in tile.points.iter_mut() {
+            (p.x, p.y) = (a * p.x + b * p.y, c * p.x + d * p.y);
+        }
+    }
+}
+
+// The transforms mapping a shape onto itself, up to translation: a subgroup
+// of the eight symmetries of the square, with a bit per transform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymmetryGroup(pub u8);
+
+impl SymmetryGroup {
+    pub fn contains(&self, transform: Transform) -> bool {
+        self.0 & (1 << transform.0) != 0
+    }
+
+    // the transforms in the group, identity first.
+    pub fn transforms(&self) -> Vec<Transform> {
+        (0..8)
+            .map(Transform)
+            .filter(|t| self.contains(*t))
+            .collect()
+    }
+
+    // number of transforms in the group, 1 for an asymmetric shape and 8
+    // for a square.
+    pub fn order(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    // number of rotations (the identity included) in the group: 1, 2 or 4.
+    pub fn rotations(&self) -> usize {
+        (self.0 & 0x0f).count_ones() as usize
+    }
+
+    // whether some reflection maps the shape onto itself, so it looks the
+    // same flipped.
+    pub fn is_mirror_symmetric(&self) -> bool {
+        self.0 & 0xf0 != 0
+    }
+}
+
+// A piece in some orientation, with the top-left corner of its bounding box
+// at the anchor.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Placement {
+    pub piece: PieceId,
+    pub transform: Transform,
+    pub anchor: Point,
+}
+
+impl Placement {
+    // the cells the piece covers, with sorted points.
+    pub fn place(&self, tile: &Tile) -> Tile {
+        let mut tile = tile.clone();
+        self.transform.apply(&mut tile);
+        tile.translate(&(self.anchor.clone() - tile.offset()));
+        tile.points.sort();
+        tile
+    }
+}
+
+// "piece:transform@x,y"
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}@{},{}",
+            self.piece, self.transform.0, self.anchor.x, self.anchor.y
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Placement;
+    use super::SymmetryGroup;
+    use super::Transform;
+    use crate::shape::Point;
+    use crate::shape::Tile;
+
+    #[test]
+    fn place() {
+        let tile = Tile::from_str("L", "x\nx\nxx");
+        let placement = Placement {
+            piece: 0,
+            transform: Transform(5),
+            anchor: Point::new(2, 1),
+        };
+        assert!(placement.transform.is_mirrored());
+        assert_eq!(placement.transform.rotations(), 1);
+
+        let placed = placement.place(&tile);
+        assert_eq!(placed.len(), 4);
+        assert_eq!(placed.offset(), Point::new(2, 1));
+        assert_eq!(placement.to_string(), "0:5@2,1");
+    }
+
+    #[test]
+    fn matrix() {
+        let tile = Tile::from_str("F", " xx\nxx\n x");
+        for o in 0..8 {
+            let mut stepwise = tile.clone();
+            if o >= 4 {
+                stepwise.mirror();
+            }
+            for _ in 0..(o % 4) {
+                stepwise.rotate();
+            }
+            let mut oriented = tile.clone();
+            Transform(o).apply(&mut oriented);
+            assert_eq!(oriented.points, stepwise.points);
+        }
+    }
+
+    #[test]
+    fn symmetry_group() {
+        // the identity and the half turn
+        let group = SymmetryGroup(0b0000_0101);
+        assert!(group.contains(Transform(2)));
+        assert!(!group.contains(Transform(1)));
+        assert_eq!(group.transforms(), vec![Transform(0), Transform(2)]);
+        assert_eq!(group.order(), 2);
+        assert_eq!(group.rotations(), 2);
+        assert!(!group.is_mirror_symmetric());
+        assert!(SymmetryGroup(0b0001_0001).is_mirror_symmetric());
+    }
+}
@@ -1,462 +1,4707 @@
-use crate::parser::parse;
-use algox::algox::IterativeSolver;
-use algox::algox::Matrix;
+use crate::analysis;
+use crate::bitboard::Bitboard;
+use crate::count;
+use crate::dancing_cells::DancingCells;
+use crate::diff::Diff;
+use crate::dlx::Heuristic;
+use crate::dlx::Matrix;
+use crate::dlx::Solver;
+use crate::error::GameError;
+use crate::estimate::Estimate;
+use crate::estimate::Rng;
+use crate::estimate::estimate;
+use crate::exact_cover::ExactCover;
+use crate::feasibility;
+use crate::feasibility::Unsolvable;
+use crate::grid::Cell;
+use crate::grid::Grid;
+use crate::hint::Hint;
+use crate::hint::HintStyle;
+use crate::hint::rank;
+use crate::json;
+use crate::json::Json;
+use crate::memory::MemoryReport;
+use crate::memory::nested_bytes;
+use crate::metrics::Metrics;
+use crate::nesting;
+use crate::nesting::Nesting;
+use crate::options::Algorithm;
+use crate::options::SolveOptions;
+use crate::orientation::OrientationCache;
+#[cfg(feature = "parallel")]
+use crate::parallel;
+use crate::parity;
+use crate::parser;
+use crate::parser::Charset;
+#[cfg(feature = "toml")]
+use crate::parser::ParserError;
+use crate::placement::Placement;
+use crate::placement::Transform;
+use crate::prune::DeadRegions;
+use crate::reduce;
+use crate::regions::Regions;
+use crate::rows::Rows;
+#[cfg(feature = "scripting")]
+use crate::script::Script;
+use crate::search::NoObserver;
+use crate::search::Search;
+use crate::search::SearchObserver;
+use crate::search::TraceEvent;
+use crate::shape::Point;
+use crate::shape::Points;
+use crate::shape::SIDES;
+use crate::shape::Tile;
+use crate::shape::Topology;
+use crate::shape::pentomino_drawing;
+use crate::sink::SolutionSink;
+use crate::snapshot;
+use crate::snapshot::Fingerprint;
+use crate::snapshot::Snapshot;
+use crate::snapshot::SnapshotError;
+use crate::warning::Warning;
+use std::collections::HashMap;
 use std::collections::HashSet;
-use std::ops;
+use std::ops::ControlFlow;
+use std::ops::Range;
+#[cfg(feature = "scripting")]
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 
-// Size
-#[derive(Debug, PartialEq, Eq)]
-struct Size {
-    width: usize,
-    height: usize,
+// allowed orientations for a cell label in the orientation grid.
+//
+// Orientation `o` mirrors the tile when `o >= 4` and then rotates it `o % 4`
+// times, so even orientations keep the drawn grain direction.
+fn orientation_mask(label: char) -> Option<u8> {
+    match label {
+        '0'..='7' => Some(1 << label.to_digit(10).unwrap()),
+        'h' => Some(0b0101_0101),
+        'v' => Some(0b1010_1010),
+        'r' => Some(0b0000_1111),
+        'm' => Some(0b1111_0000),
+        _ => None,
+    }
+}
+
+// placement rules applied during row generation
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Rules {
+    // whether placements wrap around the board's bounding box
+    topology: Topology,
+    // allowed orientations (bit mask) for pieces covering a board cell
+    orientations: HashMap<Point, u8>,
+    // colors of board cells, and of the squares of each piece by name
+    colors: HashMap<Point, char>,
+    piece_colors: HashMap<String, HashMap<Point, char>>,
+    // the anchor square of pieces by name and the board cells it may cover
+    anchors: HashMap<String, (Point, HashSet<Point>)>,
+    // weights of board cells other than 1, for scoring covered cells
+    weights: HashMap<Point, u32>,
+    // custom row and solution rules from the puzzle file
+    #[cfg(feature = "scripting")]
+    script: Option<Rc<Script>>,
+}
+
+impl Rules {
+    // read the labeled orientation grid, aligned with the board contents.
+    // Fails on a label that names no orientations.
+    pub fn add_orientations(
+        &mut self,
+        board_contents: &str,
+        contents: &str,
+    ) -> Result<(), GameError> {
+        for (point, label) in aligned_labels(board_contents, contents) {
+            if label == '.' || label == 'x' {
+                continue;
+            }
+            let mask = orientation_mask(label).ok_or(GameError::InvalidValue {
+                key: "Orientations".to_string(),
+            })?;
+            self.orientations.insert(point, mask);
+        }
+        Ok(())
+    }
+
+    // read the board color grid, aligned with the board contents. Uncolored cells are '.'.
+    pub fn add_colors(&mut self, board_contents: &str, contents: &str) {
+        for (point, color) in aligned_labels(board_contents, contents) {
+            if color != '.' {
+                self.colors.insert(point, color);
+            }
+        }
+    }
+
+    // read a grid of cell weights, aligned with the board contents: digits
+    // are weights, other cells keep a weight of 1.
+    pub fn add_weights(&mut self, board_contents: &str, contents: &str) {
+        for (point, digit) in aligned_labels(board_contents, contents) {
+            if let Some(weight) = digit.to_digit(10) {
+                self.weights.insert(point, weight);
+            }
+        }
+    }
+
+    // read the color grid of a piece, aligned with the piece contents.
+    pub fn add_piece_colors(&mut self, name: &str, piece_contents: &str, contents: &str) {
+        let colors = self.piece_colors.entry(name.to_string()).or_default();
+        for (point, color) in aligned_labels(piece_contents, contents) {
+            if color != '.' {
+                colors.insert(point, color);
+            }
+        }
+    }
+
+    // labels drawn in the piece itself, as colors of its squares. A colors
+    // grid for the piece may still override them.
+    fn add_piece_labels(&mut self, name: &str, labels: Vec<(Point, char)>) {
+        if labels.is_empty() {
+            return;
+        }
+        let colors = self.piece_colors.entry(name.to_string()).or_default();
+        colors.extend(labels);
+    }
+
+    // colors of the piece squares in the order of the points, empty for uncolored pieces.
+    fn square_colors(&self, name: &str, points: &[Point]) -> Vec<char> {
+        match self.piece_colors.get(name) {
+            Some(colors) => points
+                .iter()
+                .map(|p| *colors.get(p).unwrap_or(&'.'))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // read the board cells the anchor square of a piece may cover, marked
+    // on a grid aligned with the board contents. Unmarked cells are '.'.
+    pub fn add_anchor_positions(
+        &mut self,
+        name: &str,
+        anchor: Point,
+        board_contents: &str,
+        contents: &str,
+    ) {
+        let cells = aligned_labels(board_contents, contents)
+            .into_iter()
+            .filter(|(_, label)| *label != '.')
+            .map(|(point, _)| point)
+            .collect();
+        self.anchors.insert(name.to_string(), (anchor, cells));
+    }
+
+    // position of the anchor square in the points of a piece, None for
+    // pieces that go anywhere.
+    fn anchor_square(&self, name: &str, points: &[Point]) -> Option<usize> {
+        let (anchor, _) = self.anchors.get(name)?;
+        points.iter().position(|p| p == anchor)
+    }
+
+    // whether the anchor square of a piece may cover the board cell.
+    fn anchor_allows(&self, name: &str, point: &Point) -> bool {
+        self.anchors
+            .get(name)
+            .is_none_or(|(_, cells)| cells.contains(point))
+    }
+
+    // move the board cells the rules refer to along with the board.
+    fn translate(&mut self, offset: &Point) {
+        self.orientations = self
+            .orientations
+            .drain()
+            .map(|(p, mask)| (p + offset.clone(), mask))
+            .collect();
+        self.colors = self
+            .colors
+            .drain()
+            .map(|(p, color)| (p + offset.clone(), color))
+            .collect();
+        for (_, cells) in self.anchors.values_mut() {
+            *cells = cells.drain().map(|p| p + offset.clone()).collect();
+        }
+        self.weights = self
+            .weights
+            .drain()
+            .map(|(p, weight)| (p + offset.clone(), weight))
+            .collect();
+    }
+
+    // whether the colored squares of a placement match the colored board cells.
+    fn colors_match(&self, points: &[Point], colors: &[char]) -> bool {
+        points.iter().zip(colors).all(|(point, color)| {
+            *color == '.' || self.colors.get(point).is_none_or(|c| c == color)
+        })
+    }
+
+    // feed the rules deciding the rows into a snapshot fingerprint, in an
+    // order independent of the hash maps. Weights and the solution rule only
+    // score or filter solutions.
+    pub(crate) fn fingerprint(&self, hash: &mut Fingerprint) {
+        hash.number(self.topology as i64);
+        let mut orientations: Vec<_> = self.orientations.iter().collect();
+        orientations.sort();
+        for (point, mask) in orientations {
+            hash.point(point);
+            hash.number(i64::from(*mask));
+        }
+        let mut colors: Vec<_> = self.colors.iter().collect();
+        colors.sort();
+        for (point, color) in colors {
+            hash.point(point);
+            hash.number(i64::from(u32::from(*color)));
+        }
+        let mut piece_colors: Vec<_> = self.piece_colors.iter().collect();
+        piece_colors.sort_by_key(|(name, _)| *name);
+        for (name, colors) in piece_colors {
+            hash.text(name);
+            let mut colors: Vec<_> = colors.iter().collect();
+            colors.sort();
+            for (point, color) in colors {
+                hash.point(point);
+                hash.number(i64::from(u32::from(*color)));
+            }
+        }
+        let mut anchors: Vec<_> = self.anchors.iter().collect();
+        anchors.sort_by_key(|(name, _)| *name);
+        for (name, (anchor, cells)) in anchors {
+            hash.text(name);
+            hash.point(anchor);
+            let mut cells: Vec<_> = cells.iter().collect();
+            cells.sort();
+            hash.number(cells.len() as i64);
+            for cell in cells {
+                hash.point(cell);
+            }
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(script) = &self.script {
+            hash.text(script.row_source());
+        }
+    }
+
+    // orientations of a placement allowed by the cells it covers.
+    fn allowed(&self, points: &[Point], mut mask: u8) -> u8 {
+        for point in points {
+            if let Some(m) = self.orientations.get(point) {
+                mask &= m;
+            }
+        }
+        mask
+    }
+}
+
+// labels of a grid drawn over `contents`, relative to the top left of its cells
+fn aligned_labels(contents: &str, grid: &str) -> Vec<(Point, char)> {
+    let cells: Points = labels(contents)
+        .into_iter()
+        .filter(|(_, c)| *c == 'x')
+        .map(|(p, _)| p)
+        .collect();
+    if cells.is_empty() {
+        return Vec::new();
+    }
+    let offset = Tile {
+        name: String::new(),
+        points: cells,
+    }
+    .offset();
+
+    labels(grid)
+        .into_iter()
+        .map(|(point, label)| (point - offset.clone(), label))
+        .collect()
+}
+
+// whether the value of a puzzle key draws cells: a board, a piece or where
+// a piece may go, rather than a grid of labels or a setting.
+fn is_drawing(key: &str) -> bool {
+    let settings = [
+        "Orientations",
+        "Colors",
+        "Weights",
+        "Topology",
+        "Characters",
+        "Row rule",
+        "Solution rule",
+    ];
+    !settings.contains(&key) && !key.ends_with(" colors")
+}
+
+// a key and its rows in the puzzle format, for TOML puzzles, which have its keys.
+#[cfg(feature = "toml")]
+fn block(key: &str, rows: &str) -> Result<String, GameError> {
+    if key.contains([':', '\n']) || key.trim().is_empty() {
+        return Err(GameError::InvalidValue {
+            key: key.to_string(),
+        });
+    }
+    let mut block = format!("{}: |\n", key);
+    for row in rows.lines() {
+        block += &format!("    {}\n", row);
+    }
+    Ok(block)
+}
+
+// all non-whitespace characters and their positions
+fn labels(contents: &str) -> Vec<(Point, char)> {
+    let mut row: usize = 0;
+    let mut col: usize = 0;
+    let mut labels = Vec::new();
+
+    for c in contents.chars() {
+        match c {
+            '\n' => {
+                row += 1;
+                col = 0;
+            }
+            '\r' => {}
+            _ => {
+                if !c.is_whitespace() {
+                    labels.push((Point::from(&col, &row), c));
+                }
+                col += 1;
+            }
+        }
+    }
+    labels
+}
+
+// build matrix rows from the existing board and tiles,
+// skipping placements that break the rules
+pub(crate) fn build_rows(
+    board: &Tile,
+    tiles: &[Tile],
+    rules: &Rules,
+    cache: &mut OrientationCache,
+) -> (Rows, Vec<Placement>) {
+    let mut rows = Rows::new();
+    let mut infos = Vec::new();
+
+    // unique (colored) orientations, with the position of the anchor square,
+    // and the mask of orientations producing them
+    let mut uniqs: HashMap<(Tile, usize, Vec<char>, Option<usize>), u8> = HashMap::new();
+    for (index, tile) in tiles.iter().enumerate() {
+        let colors = rules.square_colors(&tile.name, &tile.points);
+        let anchor = rules.anchor_square(&tile.name, &tile.points);
+        if colors.is_empty() && anchor.is_none() {
+            for (points, mask) in cache.orientations(tile) {
+                let t = Tile {
+                    name: tile.name.clone(),
+                    points: points.clone(),
+                };
+                *uniqs.entry((t, index, Vec::new(), None)).or_insert(0) |= mask;
+            }
+            continue;
+        }
+
+        // colored and anchor squares go with the points, so these are not shared
+        for o in 0..8 {
+            let mut t = tile.clone();
+            if o >= 4 {
+                t.mirror();
+            }
+            for _ in 0..(o % 4) {
+                t.rotate();
+            }
+            t.translate(&-t.offset());
+
+            // sort the square colors and the anchor along with the points
+            let mut squares: Vec<(Point, char, bool)> = t
+                .points
+                .drain(..)
+                .enumerate()
+                .map(|(i, p)| (p, *colors.get(i).unwrap_or(&'.'), Some(i) == anchor))
+                .collect();
+            squares.sort();
+            t.points = squares.iter().map(|(p, _, _)| p.clone()).collect();
+            let a = squares.iter().position(|(_, _, anchor)| *anchor);
+            let c = match colors.is_empty() {
+                true => Vec::new(),
+                false => squares.into_iter().map(|(_, c, _)| c).collect(),
+            };
+
+            *uniqs.entry((t, index, c, a)).or_insert(0) |= 1 << o;
+        }
+    }
+
+    // board index of each point for easy checking, bit masks for small boards
+    let positions: HashMap<&Point, usize> = board
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p, i))
+        .collect();
+    let bits = Bitboard::new(board);
+    let size = board.size();
+    if board.points.is_empty() {
+        return (rows, infos);
+    }
+    let origin = board.offset();
+
+    // order tiles predictably
+    let mut uniqs = Vec::from_iter(uniqs);
+    uniqs.sort();
+
+    // reused for every row
+    let mut row: Vec<usize> = Vec::new();
+    // placements wrapping around may cover the same cells, and those
+    // crossing an edge that does not wrap are left out as on a plane
+    let wrapping = rules.topology != Topology::Plane;
+    let mut seen: HashSet<Vec<usize>> = HashSet::new();
+    let (width, height) = (
+        isize::try_from(size.width).unwrap(),
+        isize::try_from(size.height).unwrap(),
+    );
+    // once around a Möbius strip turns pieces over, so anchors go around twice
+    let turns = match rules.topology {
+        Topology::Mobius => 2,
+        _ => 1,
+    };
+
+    // the orientations sit at the origin, each anchor moves them there and back
+    for ((mut t, index, colors, square), mask) in uniqs {
+        let shape = bits.as_ref().and_then(|b| b.shape(&t));
+        for i in origin.x..origin.x + turns * width {
+            for j in origin.y..origin.y + height {
+                let anchor = Point::new(i, j);
+                let unwrapped = match wrapping {
+                    // the cells taken back into the bounding box, none twice
+                    true => {
+                        let mut wrapped: Points = t
+                            .points
+                            .iter()
+                            .map(|p| {
+                                rules
+                                    .topology
+                                    .wrap(&(p.clone() + anchor.clone()), &origin, &size)
+                            })
+                            .collect();
+                        let distinct: HashSet<&Point> = wrapped.iter().collect();
+                        if distinct.len() < wrapped.len()
+                            || !wrapped.iter().all(|p| positions.contains_key(p))
+                        {
+                            continue;
+                        }
+                        std::mem::swap(&mut t.points, &mut wrapped);
+                        Some(wrapped)
+                    }
+                    false => {
+                        let contains = match (&bits, &shape) {
+                            (Some(bits), Some(shape)) => {
+                                bits.contains(shape, i.unsigned_abs(), j.unsigned_abs())
+                            }
+                            (Some(_), None) => false,
+                            _ => t
+                                .points
+                                .iter()
+                                .all(|p| positions.contains_key(&(p.clone() + anchor.clone()))),
+                        };
+                        if !contains {
+                            continue;
+                        }
+                        t.translate(&anchor);
+                        None
+                    }
+                };
+                let keyed = square.is_none_or(|a| rules.anchor_allows(&t.name, &t.points[a]));
+                let allowed = match keyed && rules.colors_match(&t.points, &colors) {
+                    true => rules.allowed(&t.points, mask),
+                    false => 0,
+                };
+                #[cfg(feature = "scripting")]
+                let allowed = match &rules.script {
+                    Some(script) if allowed != 0 => {
+                        let transform = u8::try_from(allowed.trailing_zeros()).unwrap();
+                        match script.allows_row(&tiles[index].name, transform, &anchor, &t.points) {
+                            true => allowed,
+                            false => 0,
+                        }
+                    }
+                    _ => allowed,
+                };
+                if allowed != 0 {
+                    row.clear();
+                    row.extend(t.points.iter().map(|p| positions[p]));
+                    row.push(board.len() + index);
+
+                    let fresh = !wrapping || {
+                        let mut cells = row.clone();
+                        cells.sort();
+                        seen.insert(cells)
+                    };
+                    if fresh {
+                        rows.push(&row);
+                        infos.push(Placement {
+                            piece: index,
+                            transform: Transform(u8::try_from(allowed.trailing_zeros()).unwrap()),
+                            anchor: anchor.clone(),
+                        });
+                    }
+                }
+                match unwrapped {
+                    Some(points) => t.points = points,
+                    None => t.translate(&-anchor),
+                }
+            }
+        }
+    }
+    (rows, infos)
+}
+
+// feed the rows to an exact cover backend, with solutions as sorted row ids.
+fn solve_rows(
+    mut backend: Box<dyn ExactCover>,
+    rows: &Rows,
+    ids: &[usize],
+) -> Box<dyn Iterator<Item = Vec<usize>>> {
+    let mut map: HashMap<usize, usize> = HashMap::new();
+    for (index, id) in ids.iter().enumerate() {
+        map.insert(backend.add_row(&rows.to_vec(index)), *id);
+    }
+    Box::new(backend.solve_iter().map(move |solution| {
+        let mut solution: Vec<usize> = solution.iter().map(|r| map[r]).collect();
+        solution.sort();
+        solution
+    }))
+}
+
+// blocked cells ('#') of a board drawing, aligned with its cells.
+fn blocked_cells(contents: &str) -> Tile {
+    let mut blocked = Tile::new("Blocked");
+    for (point, label) in aligned_labels(contents, contents) {
+        if label == '#' {
+            blocked.points.push(point);
+        }
+    }
+    blocked
+}
+
+// optional cells ('o') of a board drawing, aligned with its cells. They are
+// board cells the pieces may, but need not, cover.
+fn optional_cells(contents: &str) -> Tile {
+    let mut optional = Tile::new("Optional");
+    for (point, label) in aligned_labels(contents, contents) {
+        if label == 'o' {
+            optional.points.push(point);
+        }
+    }
+    optional
+}
+
+// whether a character of a piece drawing is a labeled cell ('a', 'b', ...),
+// as opposed to a plain cell ('x') or an empty one.
+fn is_label(c: char) -> bool {
+    c.is_alphanumeric() && c != 'x'
+}
+
+// a piece drawing with its labeled cells ('a', 'b', ...) and anchor cell
+// ('*'), and the labels aligned with the cells. Labels become the colors of
+// the squares.
+fn piece_cells(name: &str, contents: &str) -> (Tile, String, Vec<(Point, char)>) {
+    let drawing: String = contents
+        .chars()
+        .map(|c| if is_label(c) || c == '*' { 'x' } else { c })
+        .collect();
+    let labels = aligned_labels(&drawing, contents)
+        .into_iter()
+        .filter(|(_, c)| is_label(*c))
+        .collect();
+    (Tile::from_str(name, &drawing), drawing, labels)
+}
+
+// the cells of a board drawing: those to cover ('x') followed by the
+// optional ones ('o'), and the blocked cells ('#'). Anything else, like '.',
+// is outside the board.
+fn board_cells(name: &str, contents: &str) -> BoardCells {
+    let mut board = Tile::from_str(name, contents);
+    let optional = optional_cells(contents);
+    board.points.extend(optional.points.iter().cloned());
+    (board, blocked_cells(contents), optional)
+}
+
+// a board with its blocked and optional cells.
+type BoardCells = (Tile, Tile, Tile);
+
+// lay out boards (with their blocked and optional cells) side by side,
+// separated by an empty column so pieces cannot span two boards. Returns the
+// combined board, blocked and optional cells and the name and size of each part.
+fn combine_boards(boards: Vec<BoardCells>) -> (BoardCells, Vec<(String, usize)>) {
+    let mut board = Tile::new("Board");
+    let mut blocked = Tile::new("Blocked");
+    let mut optional = Tile::new("Optional");
+    let mut sections = Vec::new();
+    let mut x: isize = 0;
+
+    for (mut b, mut k, mut o) in boards {
+        let width = isize::try_from(b.size().width).unwrap();
+        b.translate(&Point::new(x, 0));
+        k.translate(&Point::new(x, 0));
+        o.translate(&Point::new(x, 0));
+        x += width + 1;
+
+        sections.push((b.name.clone(), b.len()));
+        board.points.extend(b.points);
+        blocked.points.extend(k.points);
+        optional.points.extend(o.points);
+    }
+    if sections.is_empty() {
+        sections.push((board.name.clone(), 0));
+    }
+    ((board, blocked, optional), sections)
 }
 
-impl Size {
-    pub fn new(width: usize, height: usize) -> Self {
-        Size {
-            width: width,
-            height: height,
+// The boards, pieces and rules of a puzzle as read, in whatever format, and
+// the warnings about them.
+#[derive(Default)]
+struct Parts {
+    boards: Vec<BoardCells>,
+    tiles: Vec<Tile>,
+    rules: Rules,
+    warnings: Vec<Warning>,
+}
+
+impl Parts {
+    fn add_board(&mut self, name: &str, drawing: &str) {
+        self.boards.push(board_cells(name, drawing));
+    }
+
+    // a piece drawing, its labels becoming the colors of its squares. A
+    // pentomino letter with nothing drawn takes the standard shape. Returns
+    // the drawing with the labels as plain cells.
+    fn add_piece(&mut self, name: &str, drawing: &str) -> String {
+        let drawing = match drawing.trim().is_empty() {
+            true => pentomino_drawing(name).unwrap_or(drawing),
+            false => drawing,
+        };
+        let (tile, drawing, labels) = piece_cells(name, drawing);
+        self.rules.add_piece_labels(name, labels);
+        if tile.len() == 0 {
+            self.warnings.push(Warning::UnusedKey {
+                name: name.to_string(),
+            });
+            return drawing;
+        }
+        if self.tiles.iter().any(|t| t.name == tile.name) {
+            self.warnings.push(Warning::DuplicatePiece {
+                name: name.to_string(),
+            });
+        }
+        self.tiles.push(tile);
+        drawing
+    }
+
+    // the game, with the rows of the snapshot or generated ones.
+    fn into_game(
+        self,
+        snapshot: Option<Snapshot>,
+        cache: &mut OrientationCache,
+    ) -> Result<Game, GameError> {
+        let Parts {
+            boards,
+            tiles,
+            rules,
+            mut warnings,
+        } = self;
+        if boards.is_empty() {
+            return Err(GameError::MissingBoard);
+        }
+        if tiles.is_empty() {
+            return Err(GameError::NoPieces);
+        }
+        let ((board, blocked, optional), sections) = combine_boards(boards);
+        if board.points.is_empty() {
+            return Err(GameError::EmptyBoard);
+        }
+        let mut game = match snapshot {
+            Some(snapshot) => Game::restore(board, optional, tiles, &rules, snapshot)?,
+            None => Game::build_with(board, optional, tiles, &rules, std::mem::take(cache)),
+        };
+        game.boards = sections;
+        game.blocked = blocked;
+        game.check_boards();
+        warnings.append(&mut game.warnings);
+        game.warnings = warnings;
+        Ok(game)
+    }
+}
+
+// A solution as sorted solver row ids.
+pub type Solution = Vec<usize>;
+
+// Outcome of an anytime solve, with solver row ids.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Anytime {
+    // a complete solution.
+    Solution(Vec<usize>),
+    // stopped early, the partial solution covering the most cells seen.
+    Partial(Vec<usize>),
+    // the search finished without a solution.
+    Unsolvable,
+}
+
+// Translates search row indices into the solver's row ids.
+struct RowIds<'a> {
+    ids: &'a [usize],
+    observer: &'a mut dyn SearchObserver,
+}
+
+impl SearchObserver for RowIds<'_> {
+    fn enter(&mut self, row: usize) {
+        self.observer.enter(self.ids[row]);
+    }
+
+    fn backtrack(&mut self, row: usize) {
+        self.observer.backtrack(self.ids[row]);
+    }
+
+    fn solution(&mut self, rows: &[usize]) {
+        let rows: Vec<usize> = rows.iter().map(|r| self.ids[*r]).collect();
+        self.observer.solution(&rows);
+    }
+}
+
+// The running `solve` enumeration.
+enum Enumeration {
+    // the in-crate dancing links solver, whose counters go into the metrics
+    Links(Box<Solver>),
+    Other(Box<dyn Iterator<Item = Vec<usize>>>),
+}
+
+pub struct Game {
+    board: Tile,
+    // names and number of cells of the boards making up the board, in order
+    boards: Vec<(String, usize)>,
+    // cells drawn as part of the board that are never covered
+    blocked: Tile,
+    // board cells that may stay uncovered; every one has a row of its own
+    // after the placement rows, covering just the cell
+    optional: Tile,
+    tiles: Vec<Tile>,
+    rules: Rules,
+    rows: Rows,
+    // placement of each row
+    infos: Vec<Placement>,
+    // solver id of each row (the id of its first matrix node)
+    ids: Vec<usize>,
+    // the matrix of all rows, built when the board or pieces change and
+    // cloned for every enumeration
+    matrix: Matrix,
+    // column choice of the dancing links solver
+    heuristic: Heuristic,
+    algorithm: Algorithm,
+    // orientations of the piece shapes, kept across rebuilds
+    orientations: OrientationCache,
+    solver: Enumeration,
+    // in-crate search used by `solve` instead of the solver, when needed
+    search: Option<Search>,
+    observer: Option<Box<dyn SearchObserver>>,
+    // search decisions kept by the running engine
+    trace_capacity: usize,
+    // abandon branches leaving regions no remaining pieces can fill
+    dead_region_pruning: bool,
+    // drop duplicate and dominated rows after generating them
+    reduce_rows: bool,
+    // with row reduction, the matrix with one column for all copies of a
+    // piece that the dancing links solver runs on, and the solutions behind
+    // its current one
+    copies: Option<reduce::Copies>,
+    expansion: Option<reduce::Expansion>,
+    // `solve_parallel` keeps the order of a single dancing links solver
+    #[cfg(feature = "parallel")]
+    ordered_parallel: bool,
+    // reject solutions where four pieces meet at a point
+    no_crossroads: bool,
+    warnings: Vec<Warning>,
+    // whether the checkerboard coloring allows a tiling
+    parity_feasible: bool,
+    // why the puzzle cannot be solved, when a cheap check shows it; the rows
+    // are then left empty
+    unsolvable: Option<Unsolvable>,
+    // work done so far, without that of the running solver and search
+    metrics: Metrics,
+}
+
+impl Game {
+    // read a puzzle, failing on text that is not a puzzle: a parse error, no
+    // board or no pieces, or a setting with a value it cannot take.
+    pub fn from_yaml(yaml: &str) -> Result<Self, GameError> {
+        Self::read_yaml(yaml, parser::TAB_WIDTH, None, &mut OrientationCache::new())
+    }
+
+    // the same as `from_yaml`.
+    pub fn try_from_yaml(yaml: &str) -> Result<Self, GameError> {
+        Self::from_yaml(yaml)
+    }
+
+    // read a puzzle indented or drawn with tabs of the given width.
+    pub fn try_from_yaml_with_tab_width(yaml: &str, tab_width: usize) -> Result<Self, GameError> {
+        Self::read_yaml(yaml, tab_width, None, &mut OrientationCache::new())
+    }
+
+    // read a puzzle from JSON: the board and every piece drawn as a list of
+    // rows, or a single string with a row per line, and optionally the
+    // topology, as in
+    // { "board": ["xxxx", "xx"], "tiles": { "L": ["xx", "x"] } }.
+    pub fn from_json(contents: &str) -> Result<Self, GameError> {
+        let json = json::parse(contents)?;
+        let rows = |key: &str, value: &Json| -> Result<String, GameError> {
+            let invalid = || GameError::InvalidValue {
+                key: key.to_string(),
+            };
+            let rows: Vec<&str> = match value {
+                Json::String(drawing) => drawing.lines().collect(),
+                Json::Array(rows) => rows
+                    .iter()
+                    .map(|row| match row {
+                        Json::String(row) => Ok(row.as_str()),
+                        _ => Err(invalid()),
+                    })
+                    .collect::<Result<_, _>>()?,
+                _ => return Err(invalid()),
+            };
+            Ok(rows.join("\n"))
+        };
+
+        let mut parts = Parts::default();
+        let board = json.get("board").ok_or(GameError::MissingBoard)?;
+        parts.add_board("Board", &rows("board", board)?);
+        match json.get("tiles") {
+            Some(Json::Object(tiles)) => {
+                for (name, tile) in tiles {
+                    parts.add_piece(name, &rows(name, tile)?);
+                }
+            }
+            Some(_) => {
+                return Err(GameError::InvalidValue {
+                    key: "tiles".to_string(),
+                });
+            }
+            None => return Err(GameError::NoPieces),
+        }
+        match json.get("topology") {
+            Some(Json::String(topology)) => {
+                parts.rules.topology =
+                    Topology::from_name(topology.trim()).ok_or(GameError::InvalidValue {
+                        key: "topology".to_string(),
+                    })?;
+            }
+            Some(_) => {
+                return Err(GameError::InvalidValue {
+                    key: "topology".to_string(),
+                });
+            }
+            None => {}
+        }
+        parts.into_game(None, &mut OrientationCache::new())
+    }
+
+    // read a puzzle from TOML, with the keys of the puzzle format and the
+    // drawings as multi-line strings or lists of rows:
+    // Board = """
+    // xxx
+    // xxx
+    // """
+    // L = ["xx", "x"]
+    #[cfg(feature = "toml")]
+    pub fn from_toml(contents: &str) -> Result<Self, GameError> {
+        use toml::Value;
+
+        let table: toml::Table = contents.parse().map_err(|e: toml::de::Error| {
+            let at = e.span().map_or(0, |span| span.start);
+            ParserError::new(e.message(), contents, at)
+        })?;
+        let mut yaml = String::new();
+        for (key, value) in table.iter() {
+            let invalid = || GameError::InvalidValue { key: key.clone() };
+            let rows = match value {
+                Value::String(drawing) => drawing.trim_end_matches('\n').to_string(),
+                Value::Array(rows) => rows
+                    .iter()
+                    .map(|row| row.as_str().ok_or_else(invalid))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join("\n"),
+                _ => return Err(invalid()),
+            };
+            yaml += &block(key, &rows)?;
+        }
+        Self::try_from_yaml(&yaml)
+    }
+
+    // read a puzzle, failing like `from_yaml` and also on the first piece
+    // that is not 4-connected. A stray cell in a piece drawing is more likely
+    // a typo than intended.
+    pub fn from_yaml_strict(yaml: &str) -> Result<Self, GameError> {
+        let game = Self::from_yaml(yaml)?;
+        match game
+            .warnings
+            .iter()
+            .find(|w| matches!(w, Warning::DisconnectedTile { .. }))
+        {
+            Some(warning) => Err(GameError::Rejected(warning.clone())),
+            None => Ok(game),
+        }
+    }
+
+    // read a puzzle, taking the orientations of known shapes from a cache
+    // shared between games and adding the new ones. The game keeps no cache,
+    // and text that is not a puzzle leaves the cache as it was.
+    pub fn from_yaml_sharing(
+        yaml: &str,
+        orientations: &mut OrientationCache,
+    ) -> Result<Self, GameError> {
+        let mut game = Self::read_yaml(yaml, parser::TAB_WIDTH, None, orientations)?;
+        *orientations = std::mem::take(&mut game.orientations);
+        Ok(game)
+    }
+
+    // read a puzzle with the rows saved by `save_matrix`, instead of generating
+    // them. Fails when the snapshot is damaged or for another board or pieces.
+    pub fn from_yaml_with_matrix(yaml: &str, matrix: &[u8]) -> Result<Self, GameError> {
+        Self::read_yaml(
+            yaml,
+            parser::TAB_WIDTH,
+            Some(snapshot::decode(matrix)?),
+            &mut OrientationCache::new(),
+        )
+    }
+
+    fn read_yaml(
+        yaml: &str,
+        tab_width: usize,
+        snapshot: Option<Snapshot>,
+        cache: &mut OrientationCache,
+    ) -> Result<Self, GameError> {
+        // what the puzzle format does not cover may still be YAML, and the
+        // errors are those of the puzzle format
+        #[cfg(feature = "yaml")]
+        let parsed: Vec<(String, String)>;
+        let contents = match parser::parse_with_tab_width(yaml, tab_width) {
+            Ok(contents) => contents,
+            #[cfg(feature = "yaml")]
+            Err(error) => {
+                parsed = parser::parse_yaml(yaml).map_err(|_| error)?;
+                parsed
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.as_str()))
+                    .collect()
+            }
+            #[cfg(not(feature = "yaml"))]
+            Err(error) => return Err(error.into()),
+        };
+
+        // drawings have their tabs expanded, rows of those on the line of their
+        // key split at '/' or ',', and those in other characters are read as
+        // if drawn with 'x'
+        let charset = match contents.iter().find(|(name, _)| *name == "Characters") {
+            Some((_, spec)) => Some(Charset::from_spec(spec).ok_or(GameError::InvalidValue {
+                key: "Characters".to_string(),
+            })?),
+            None => None,
+        };
+        let normalized: Vec<(&str, String)> = contents
+            .iter()
+            .map(|(name, part)| {
+                let mut part = part.to_string();
+                if is_drawing(name) {
+                    if !part.contains('\n') {
+                        part = part.replace(['/', ','], "\n");
+                    }
+                    if part.contains('\t') {
+                        part = parser::expand_tabs(&part, tab_width);
+                    }
+                    if let Some(charset) = &charset {
+                        part = charset.normalize(&part);
+                    }
+                }
+                (*name, part)
+            })
+            .collect();
+        let contents: Vec<(&str, &str)> = normalized
+            .iter()
+            .map(|(name, part)| (*name, part.as_str()))
+            .collect();
+        let mut parts = Parts::default();
+        let mut board_contents = "";
+        let mut orientations = "";
+        let mut colors = "";
+        let mut weights = "";
+        let mut piece_colors: Vec<(&str, &str)> = Vec::new();
+        let mut piece_positions: Vec<(&str, &str)> = Vec::new();
+        let mut topology = "";
+        let mut drawings: Vec<(&str, String)> = Vec::new();
+        let mut row_rule = "";
+        let mut solution_rule = "";
+
+        for (name, part) in contents.iter() {
+            match *name {
+                "Orientations" => {
+                    orientations = part;
+                }
+                "Colors" => {
+                    colors = part;
+                }
+                "Weights" => {
+                    weights = part;
+                }
+                "Topology" => {
+                    topology = part.trim();
+                }
+                _ if name.ends_with(" colors") => {
+                    piece_colors.push((name.trim_end_matches(" colors"), part));
+                }
+                _ if name.ends_with(" positions") => {
+                    piece_positions.push((name.trim_end_matches(" positions"), part));
+                }
+                "Characters" => {}
+                "Row rule" => {
+                    row_rule = part;
+                }
+                "Solution rule" => {
+                    solution_rule = part;
+                }
+                _ if name.starts_with("Board") => {
+                    if parts.boards.is_empty() {
+                        board_contents = part;
+                    }
+                    parts.add_board(name, part);
+                }
+                _ => {
+                    let drawing = parts.add_piece(name, part);
+                    drawings.push((*name, drawing));
+                }
+            }
+        }
+
+        // orientations are aligned with the first board
+        let tiles = &parts.tiles;
+        let rules = &mut parts.rules;
+        let warnings = &mut parts.warnings;
+        if !topology.is_empty() {
+            rules.topology = Topology::from_name(topology).ok_or(GameError::InvalidValue {
+                key: "Topology".to_string(),
+            })?;
+        }
+        rules.add_orientations(board_contents, orientations)?;
+        rules.add_colors(board_contents, colors);
+        rules.add_weights(board_contents, weights);
+        for (name, part) in piece_colors {
+            match drawings.iter().find(|(n, _)| *n == name) {
+                Some((_, piece)) if tiles.iter().any(|t| t.name == name) => {
+                    rules.add_piece_colors(name, piece, part)
+                }
+                _ => warnings.push(Warning::UnusedKey {
+                    name: format!("{} colors", name),
+                }),
+            }
+        }
+
+        // the anchor is the '*' square, or the first one
+        for (name, part) in piece_positions {
+            let anchor = drawings
+                .iter()
+                .find(|(n, _)| *n == name)
+                .and_then(|(_, piece)| {
+                    let tile = tiles.iter().find(|t| t.name == name)?;
+                    let star = contents.iter().find(|(n, _)| *n == name)?.1;
+                    let marked = aligned_labels(piece, star)
+                        .into_iter()
+                        .find(|(_, c)| *c == '*')
+                        .map(|(p, _)| p);
+                    Some(marked.unwrap_or_else(|| tile.points[0].clone()))
+                });
+            match anchor {
+                Some(anchor) => rules.add_anchor_positions(name, anchor, board_contents, part),
+                None => warnings.push(Warning::UnusedKey {
+                    name: format!("{} positions", name),
+                }),
+            }
+        }
+
+        #[cfg(feature = "scripting")]
+        if !row_rule.is_empty() || !solution_rule.is_empty() {
+            let script = Script::new(row_rule, solution_rule).map_err(GameError::Script)?;
+            rules.script = Some(Rc::new(script));
+        }
+        #[cfg(not(feature = "scripting"))]
+        for (name, rule) in [("Row rule", row_rule), ("Solution rule", solution_rule)] {
+            if !rule.is_empty() {
+                warnings.push(Warning::UnusedKey {
+                    name: name.to_string(),
+                });
+            }
+        }
+
+        parts.into_game(snapshot, cache)
+    }
+
+    // puzzle covering a plain width by height rectangle with the pieces,
+    // under the default rules.
+    pub fn new_rect(width: usize, height: usize, tiles: Vec<Tile>) -> Self {
+        Self::build(Tile::rect(width, height), tiles, &Rules::default())
+    }
+
+    fn build(board: Tile, tiles: Vec<Tile>, rules: &Rules) -> Self {
+        let optional = Tile::new("Optional");
+        Self::build_with(board, optional, tiles, rules, OrientationCache::new())
+    }
+
+    fn build_with(
+        board: Tile,
+        optional: Tile,
+        tiles: Vec<Tile>,
+        rules: &Rules,
+        orientations: OrientationCache,
+    ) -> Self {
+        let mut game = Self::empty(board, tiles, rules);
+        game.optional = optional;
+        game.orientations = orientations;
+        game.rebuild();
+        game
+    }
+
+    // a game with the rows of a snapshot.
+    fn restore(
+        board: Tile,
+        optional: Tile,
+        tiles: Vec<Tile>,
+        rules: &Rules,
+        snapshot: Snapshot,
+    ) -> Result<Self, SnapshotError> {
+        let mut game = Self::empty(board, tiles, rules);
+        game.optional = optional;
+        let fingerprint =
+            snapshot::fingerprint(&game.board, &game.optional, &game.tiles, &game.rules);
+        let pieces_match = snapshot
+            .placements
+            .iter()
+            .all(|p| p.piece < game.tiles.len());
+        if snapshot.fingerprint != fingerprint || snapshot.n_cols != game.n_cols() || !pieces_match
+        {
+            return Err(SnapshotError::Mismatch);
+        }
+        game.refresh();
+        if game.unsolvable.is_none() {
+            game.rows = snapshot.rows;
+            game.infos = snapshot.placements;
+        }
+        game.assemble();
+        Ok(game)
+    }
+
+    // the rows of the game, for `from_yaml_with_matrix`.
+    pub fn save_matrix(&self) -> Vec<u8> {
+        snapshot::encode(&Snapshot {
+            fingerprint: snapshot::fingerprint(
+                &self.board,
+                &self.optional,
+                &self.tiles,
+                &self.rules,
+            ),
+            n_cols: self.n_cols(),
+            rows: self.rows.clone(),
+            placements: self.infos.clone(),
+        })
+    }
+
+    // a game without rows.
+    fn empty(board: Tile, tiles: Vec<Tile>, rules: &Rules) -> Self {
+        Self {
+            boards: vec![(board.name.clone(), board.len())],
+            blocked: Tile::new("Blocked"),
+            optional: Tile::new("Optional"),
+            board,
+            tiles,
+            rules: rules.clone(),
+            rows: Rows::new(),
+            infos: Vec::new(),
+            ids: Vec::new(),
+            matrix: Matrix::new(0),
+            heuristic: Heuristic::default(),
+            algorithm: Algorithm::default(),
+            orientations: OrientationCache::new(),
+            solver: Enumeration::Other(Box::new(std::iter::empty())),
+            search: None,
+            observer: None,
+            trace_capacity: 0,
+            dead_region_pruning: false,
+            reduce_rows: false,
+            copies: None,
+            expansion: None,
+            #[cfg(feature = "parallel")]
+            ordered_parallel: false,
+            no_crossroads: false,
+            warnings: Vec::new(),
+            parity_feasible: true,
+            unsolvable: None,
+            metrics: Metrics::default(),
+        }
+    }
+
+    // regenerate the placements and the matrix after the board or pieces changed.
+    fn rebuild(&mut self) {
+        self.rows = Rows::new();
+        self.infos = Vec::new();
+        self.refresh();
+        if self.unsolvable.is_none() {
+            let generated = self.orientations.generated();
+            (self.rows, self.infos) = build_rows(
+                &self.board,
+                &self.tiles,
+                &self.rules,
+                &mut self.orientations,
+            );
+            self.metrics.rows_built += self.rows.len();
+            self.metrics.orientations += self.orientations.generated() - generated;
+            for point in &self.optional.points {
+                let cell = self.board.index(point).unwrap();
+                self.rows.push(&[cell]);
+            }
+            if self.reduce_rows {
+                let kept = reduce::reduce(self.n_cols(), &self.rows);
+                let mut rows = Rows::new();
+                for r in &kept {
+                    rows.push(&self.rows.to_vec(*r));
+                }
+                self.infos = kept
+                    .iter()
+                    .filter_map(|r| self.infos.get(*r).cloned())
+                    .collect();
+                self.rows = rows;
+            }
+        }
+        self.assemble();
+    }
+
+    // recheck the warnings about boards falling apart into several regions.
+    // Separate boards are expected, each one is checked on its own.
+    fn check_boards(&mut self) {
+        let warnings: Vec<Warning> = self
+            .boards()
+            .into_iter()
+            .filter_map(|(name, range)| {
+                let mut board = Tile::new(name);
+                board.points = self.board.points[range].iter().cloned().collect();
+                let regions = board.components().len();
+                (regions > 1).then(|| Warning::DisconnectedBoard {
+                    name: name.to_string(),
+                    regions,
+                })
+            })
+            .collect();
+        self.warnings
+            .retain(|w| !matches!(w, Warning::DisconnectedBoard { .. }));
+        self.warnings.extend(warnings);
+    }
+
+    // recheck the warnings, the parity and the other cheap conditions of the
+    // puzzle, dropping all rows when it cannot be tiled.
+    fn refresh(&mut self) {
+        self.warnings.retain(|w| {
+            !matches!(
+                w,
+                Warning::DisconnectedTile { .. } | Warning::AreaMismatch { .. }
+            )
+        });
+        self.check_boards();
+        for tile in &self.tiles {
+            if !tile.is_connected() {
+                self.warnings.push(Warning::DisconnectedTile {
+                    name: tile.name.clone(),
+                });
+            }
+        }
+        let area: usize = self.tiles.iter().map(|t| t.len()).sum();
+        let must = self.board.len() - self.optional.len();
+        if !self.tiles.is_empty() && !(must..=self.board.len()).contains(&area) {
+            self.warnings.push(Warning::AreaMismatch {
+                board: self.board.len(),
+                pieces: area,
+            });
+        }
+
+        // skip all placements when a coloring argument (or a cheaper check)
+        // rules out any tiling. The coloring argument needs every cell covered.
+        // Neither holds when pieces may wrap around the board, where odd
+        // sides also break the coloring.
+        let wraps = self.rules.topology != Topology::Plane;
+        self.parity_feasible =
+            !self.optional.points.is_empty() || wraps || parity::feasible(&self.board, &self.tiles);
+        self.unsolvable = feasibility::check(&self.board, self.optional.len(), &self.tiles)
+            .filter(|reason| !wraps || !matches!(reason, Unsolvable::TooLarge { .. }));
+        if self.unsolvable.is_none() && !self.parity_feasible {
+            self.unsolvable = Some(Unsolvable::Parity);
+        }
+        if self.unsolvable.is_some() {
+            self.rows.clear();
+            self.infos.clear();
+        }
+    }
+
+    // build the matrix from the rows and restart the enumeration.
+    fn assemble(&mut self) {
+        if self.unsolvable.is_none() {
+            self.unsolvable = feasibility::uncovered(self.board.len(), &self.tiles, &self.rows);
+            if self.unsolvable.is_some() {
+                self.rows.clear();
+                self.infos.clear();
+            }
+        }
+        self.matrix = Matrix::new(self.n_cols());
+        self.ids = (0..self.rows.len())
+            .map(|index| self.matrix.add_row(&self.rows.to_vec(index)))
+            .collect();
+        self.copies = match self.reduce_rows {
+            true => reduce::Copies::new(self.board.len(), self.tiles.len(), &self.rows),
+            false => None,
+        };
+        self.restart();
+    }
+
+    // restart the enumeration from the cached matrix.
+    fn restart(&mut self) {
+        let solver = match self.algorithm {
+            // the search below runs instead
+            Algorithm::Search => Enumeration::Other(Box::new(std::iter::empty())),
+            Algorithm::DancingLinks => Enumeration::Links(Box::new(Solver::with_heuristic(
+                self.copies
+                    .as_ref()
+                    .map_or(&self.matrix, |c| c.matrix())
+                    .clone(),
+                self.heuristic.clone(),
+            ))),
+            Algorithm::DancingCells => Enumeration::Other(solve_rows(
+                Box::new(DancingCells::new(self.n_cols())),
+                &self.rows,
+                &self.ids,
+            )),
+        };
+        self.replace_solver(solver);
+        self.expansion = None;
+        self.replace_search(self.uses_search().then(|| self.new_search()));
+        self.set_trace_capacity(self.trace_capacity);
+        self.metrics.peak_bytes = self.metrics.peak_bytes.max(self.memory_report().total());
+    }
+
+    // swap in a new enumeration, keeping the counters of the old one.
+    fn replace_solver(&mut self, solver: Enumeration) {
+        if let Enumeration::Links(old) = &self.solver {
+            self.metrics.covers += old.stats().covers;
+            self.metrics.uncovers += old.stats().uncovers;
+        }
+        self.solver = solver;
+    }
+
+    fn replace_search(&mut self, search: Option<Search>) {
+        if let Some(old) = &self.search {
+            let (covers, uncovers) = old.covers();
+            self.metrics.covers += covers;
+            self.metrics.uncovers += uncovers;
+        }
+        self.search = search;
+    }
+
+    // counters of the work done building and solving, since the game was
+    // made or `reset_metrics` was called.
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = self.metrics.clone();
+        if let Enumeration::Links(solver) = &self.solver {
+            metrics.covers += solver.stats().covers;
+            metrics.uncovers += solver.stats().uncovers;
+        }
+        if let Some(search) = &self.search {
+            let (covers, uncovers) = search.covers();
+            metrics.covers += covers;
+            metrics.uncovers += uncovers;
+        }
+        metrics.peak_bytes = metrics.peak_bytes.max(self.memory_report().total());
+        metrics
+    }
+
+    // zero the counters, the memory peak starts over from the current use.
+    pub fn reset_metrics(&mut self) {
+        self.metrics = Metrics::default();
+        if let Enumeration::Links(solver) = &mut self.solver {
+            solver.reset_stats();
+        }
+        if let Some(search) = &mut self.search {
+            search.reset_covers();
+        }
+    }
+
+    // the algorithm `solve` runs: the in-crate search when pruning or an
+    // observer needs it, otherwise the one set with `set_options`.
+    pub fn algorithm(&self) -> Algorithm {
+        match self.uses_search() {
+            true => Algorithm::Search,
+            false => self.algorithm,
+        }
+    }
+
+    fn uses_search(&self) -> bool {
+        self.algorithm == Algorithm::Search || self.dead_region_pruning || self.observer.is_some()
+    }
+
+    // column choice of the solver. Restarts the enumeration.
+    pub fn set_heuristic(&mut self, heuristic: Heuristic) {
+        self.heuristic = heuristic;
+        self.restart();
+    }
+
+    // algorithm, column choice and pruning of the search. Dead region pruning
+    // runs the in-crate search whatever the algorithm, see `algorithm`.
+    // Rebuilds the rows when row reduction changes, and restarts the
+    // enumeration.
+    pub fn set_options(&mut self, options: &SolveOptions) {
+        self.algorithm = options.algorithm;
+        if options.row_reduction != self.reduce_rows {
+            self.set_row_reduction(options.row_reduction);
+        }
+        if options.dead_region_pruning != self.dead_region_pruning {
+            self.set_dead_region_pruning(options.dead_region_pruning);
+        }
+        self.set_heuristic(options.heuristic.clone());
+    }
+
+    // the columns (board cells, then pieces) with the number of placements
+    // covering them, tightest first. Columns covered by few placements are
+    // where a puzzle is hard: a column with none has no solution at all.
+    pub fn tightness(&self) -> Vec<(usize, usize)> {
+        let mut counts = vec![0; self.n_cols()];
+        for row in self.rows.iter() {
+            for c in row {
+                counts[*c as usize] += 1;
+            }
+        }
+        let mut tightness: Vec<(usize, usize)> = counts.into_iter().enumerate().collect();
+        tightness.sort_by_key(|(c, count)| (*count, *c));
+        tightness
+    }
+
+    // branch on the columns in order of `tightness`, fixed before solving.
+    // Restarts the enumeration.
+    pub fn order_by_tightness(&mut self) {
+        let order = self.tightness().into_iter().map(|(c, _)| c).collect();
+        self.set_heuristic(Heuristic::Order(order));
+    }
+
+    // replace the board (a single one, without blocked cells), keeping the
+    // pieces and rules. Restarts the enumeration.
+    pub fn set_board(&mut self, board: Tile) {
+        self.boards = vec![(board.name.clone(), board.len())];
+        self.blocked = Tile::new("Blocked");
+        self.optional = Tile::new("Optional");
+        self.board = board;
+        self.rebuild();
+    }
+
+    // replace the pieces. Restarts the enumeration.
+    pub fn set_tiles(&mut self, tiles: Vec<Tile>) {
+        self.tiles = tiles;
+        self.rebuild();
+    }
+
+    // how the edges of the board connect. On a torus or cylinder the board's
+    // bounding box wraps around, so placements may leave one edge and come
+    // back on the other. Restarts the enumeration.
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.rules.topology = topology;
+        self.rebuild();
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.rules.topology
+    }
+
+    // the maps of the board onto itself that keep neighbouring cells
+    // neighbours: a rotation or reflection followed by a translation, with
+    // the points then taken back into the box along the edges that wrap.
+    // The identity comes first. On a plane the translation only moves the
+    // board back in place, on a torus or cylinder it may also shift it around.
+    pub fn board_symmetries(&self) -> Vec<(Transform, Point)> {
+        let board = &self.board;
+        if board.points.is_empty() {
+            return vec![(Transform(0), Point::new(0, 0))];
+        }
+        let topology = self.rules.topology;
+        let cells: HashMap<&Point, usize> = board
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p, i))
+            .collect();
+        let (offset, size) = board.bounding_box();
+        // the neighbours of every cell by index, sorted
+        let neighbours: Vec<Vec<usize>> = board
+            .points
+            .iter()
+            .map(|p| {
+                let mut around: Vec<usize> = SIDES
+                    .iter()
+                    .map(|(dx, dy)| topology.wrap(&Point::new(p.x + dx, p.y + dy), &offset, &size))
+                    .filter(|q| q != p)
+                    .filter_map(|q| cells.get(&q).copied())
+                    .collect();
+                around.sort();
+                around.dedup();
+                around
+            })
+            .collect();
+        let (wrap_x, wrap_y) = topology.wraps();
+        let shifts_x = if wrap_x { size.width } else { 1 };
+        let shifts_y = if wrap_y { size.height } else { 1 };
+
+        let mut symmetries = Vec::new();
+        let mut hit = vec![false; board.len()];
+        for transform in (0..8).map(Transform) {
+            let mut turned = board.clone();
+            transform.apply(&mut turned);
+            let base = offset.clone() - turned.offset();
+            for dx in 0..shifts_x as isize {
+                for dy in 0..shifts_y as isize {
+                    let shift = base.clone() + Point::new(dx, dy);
+                    // the cell each cell goes to, if all land on distinct cells
+                    hit.fill(false);
+                    let image: Option<Vec<usize>> = turned
+                        .points
+                        .iter()
+                        .map(|p| {
+                            let q = topology.wrap(&(p.clone() + shift.clone()), &offset, &size);
+                            let i = *cells.get(&q)?;
+                            (!std::mem::replace(&mut hit[i], true)).then_some(i)
+                        })
+                        .collect();
+                    // and the neighbours of each cell go to those of its image
+                    let adjacent = image.is_some_and(|image| {
+                        (0..board.len()).all(|i| {
+                            let mut around: Vec<usize> =
+                                neighbours[i].iter().map(|j| image[*j]).collect();
+                            around.sort();
+                            around == neighbours[image[i]]
+                        })
+                    });
+                    if adjacent {
+                        symmetries.push((transform, shift));
+                    }
+                }
+            }
+        }
+        symmetries
+    }
+
+    // only allow placements of a piece whose `anchor` square (a cell of the
+    // piece as given) covers one of the board `cells`. Pieces share the
+    // restriction with all pieces of the same name. Restarts the enumeration.
+    pub fn restrict_anchor(&mut self, piece: usize, anchor: Point, cells: &[Point]) {
+        let name = self.tiles[piece].name.clone();
+        let cells = cells.iter().cloned().collect();
+        self.rules.anchors.insert(name, (anchor, cells));
+        self.rebuild();
+    }
+
+    // add a piece, generating only its placements. Restarts the enumeration.
+    pub fn add_tile(&mut self, tile: Tile) {
+        self.tiles.push(tile);
+        if self.unsolvable.is_some() || self.reduce_rows || !self.optional.points.is_empty() {
+            return self.rebuild();
+        }
+        let index = self.tiles.len() - 1;
+        let (rows, infos) = build_rows(
+            &self.board,
+            &self.tiles[index..],
+            &self.rules,
+            &mut self.orientations,
+        );
+        for (r, mut info) in infos.into_iter().enumerate() {
+            let mut row = rows.to_vec(r);
+            *row.last_mut().unwrap() += index;
+            info.piece = index;
+            self.rows.push(&row);
+            self.infos.push(info);
+        }
+        self.refresh();
+        self.assemble();
+    }
+
+    // remove a piece and its placements. Restarts the enumeration.
+    pub fn remove_tile(&mut self, index: usize) -> Tile {
+        let tile = self.tiles.remove(index);
+        if self.unsolvable.is_some() || self.reduce_rows || !self.optional.points.is_empty() {
+            self.rebuild();
+            return tile;
+        }
+        let rows = std::mem::take(&mut self.rows);
+        let infos = std::mem::take(&mut self.infos);
+        for (r, mut info) in infos.into_iter().enumerate() {
+            if info.piece == index {
+                continue;
+            }
+            let mut row = rows.to_vec(r);
+            if info.piece > index {
+                *row.last_mut().unwrap() -= 1;
+                info.piece -= 1;
+            }
+            self.rows.push(&row);
+            self.infos.push(info);
+        }
+        self.refresh();
+        self.assemble();
+        tile
+    }
+
+    // remove board cells and the placements covering them. Restarts the
+    // enumeration.
+    pub fn remove_cells(&mut self, points: &[Point]) {
+        let removed: HashSet<&Point> = points.iter().collect();
+        let mut index = Vec::with_capacity(self.board.len());
+        let mut kept = 0;
+        for point in &self.board.points {
+            match removed.contains(point) {
+                true => index.push(None),
+                false => {
+                    index.push(Some(kept));
+                    kept += 1;
+                }
+            }
+        }
+        let n_removed = self.board.len() - kept;
+
+        // shrink the boards holding the cells
+        let mut start = 0;
+        for (_, len) in self.boards.iter_mut() {
+            let end = start + *len;
+            *len -= index[start..end].iter().filter(|i| i.is_none()).count();
+            start = end;
+        }
+        self.board.points.retain(|p| !removed.contains(p));
+
+        // the cells wrap around the box, which may shrink
+        let wraps = self.rules.topology != Topology::Plane;
+        if self.unsolvable.is_none() && !self.reduce_rows && !wraps {
+            let rows = std::mem::take(&mut self.rows);
+            let infos = std::mem::take(&mut self.infos);
+            for (row, info) in rows.iter().zip(infos) {
+                let (piece, cells) = row.split_last().unwrap();
+                let cells: Option<Vec<usize>> = cells.iter().map(|c| index[*c as usize]).collect();
+                if let Some(mut cells) = cells {
+                    cells.push(*piece as usize - n_removed);
+                    self.rows.push(&cells);
+                    self.infos.push(info);
+                }
+            }
+            self.refresh();
+            self.assemble();
+        } else {
+            self.rebuild();
+        }
+    }
+
+    // move the board to the origin, dropping blocked cells outside the box
+    // around the board cells, and return the offset it was moved by: old
+    // positions are the new ones plus the offset. Cell indices and solver
+    // rows stay the same, so solutions found before still apply, only their
+    // anchors move.
+    pub fn trim_board(&mut self) -> Point {
+        let (board, offset) = self.board.cropped();
+        let size = board.size();
+        let shift = -offset.clone();
+        self.board = board;
+        self.optional.translate(&shift);
+        self.blocked.translate(&shift);
+        self.blocked.points.retain(|p| {
+            (0..size.width as isize).contains(&p.x) && (0..size.height as isize).contains(&p.y)
+        });
+        self.rules.translate(&shift);
+        for info in self.infos.iter_mut() {
+            info.anchor += shift.clone();
+        }
+        offset
+    }
+
+    // add board cells (to the last board), generating only the placements
+    // covering them. Restarts the enumeration.
+    pub fn add_cells(&mut self, points: &[Point]) {
+        let mut added: Vec<Point> = Vec::new();
+        for point in points {
+            if self.board.index(point).is_none() && !added.contains(point) {
+                added.push(point.clone());
+            }
+        }
+        let n_cells = self.board.len();
+        self.board.points.extend(added.iter().cloned());
+        if let Some((_, len)) = self.boards.last_mut() {
+            *len += added.len();
+        }
+        if self.unsolvable.is_some()
+            || self.reduce_rows
+            || !self.optional.points.is_empty()
+            || self.rules.topology != Topology::Plane
+        {
+            return self.rebuild();
+        }
+
+        // placements covering an added cell lie within reach of it
+        let reach = self
+            .tiles
+            .iter()
+            .map(|t| {
+                let size = t.size();
+                isize::try_from(size.width.max(size.height)).unwrap() - 1
+            })
+            .max()
+            .unwrap_or(0);
+        let mut window = Tile::new("Window");
+        window.points = self
+            .board
+            .points
+            .iter()
+            .filter(|p| {
+                added
+                    .iter()
+                    .any(|a| (p.x - a.x).abs() <= reach && (p.y - a.y).abs() <= reach)
+            })
+            .cloned()
+            .collect();
+
+        let n_added = u32::try_from(added.len()).unwrap();
+        for index in 0..self.rows.len() {
+            *self.rows.get_mut(index).last_mut().unwrap() += n_added;
+        }
+        let position: HashMap<&Point, usize> = self
+            .board
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p, i))
+            .collect();
+        let (rows, infos) = build_rows(&window, &self.tiles, &self.rules, &mut self.orientations);
+        for (row, info) in rows.iter().zip(infos) {
+            let (piece, cells) = row.split_last().unwrap();
+            let cells: Vec<usize> = cells
+                .iter()
+                .map(|c| position[&window.points[*c as usize]])
+                .collect();
+            if cells.iter().all(|c| *c < n_cells) {
+                continue;
+            }
+            let mut row = cells;
+            row.push(*piece as usize - window.len() + self.board.len());
+            self.rows.push(&row);
+            self.infos.push(info);
+        }
+        self.refresh();
+        self.assemble();
+    }
+
+    // turn the game into a slightly different puzzle, reusing the placements
+    // of the pieces and cells both share, and trying the placements of
+    // `solution` (a solution of the old puzzle) first, so a nearby solution is
+    // found quickly. Pieces are matched by name and cells. Restarts the
+    // enumeration.
+    pub fn warm_start(&mut self, board: Tile, tiles: Vec<Tile>, solution: &[usize]) {
+        let previous: Vec<(Tile, Placement)> = self
+            .placements(solution)
+            .into_iter()
+            .map(|p| (self.tiles[p.piece].clone(), p))
+            .collect();
+
+        // match the pieces copy by copy: every new piece takes the first
+        // unmatched equal one, surplus copies go and missing ones are added
+        let mut matched: Vec<Option<usize>> = vec![None; self.tiles.len()];
+        for (target, tile) in tiles.iter().enumerate() {
+            let current =
+                (0..self.tiles.len()).find(|i| matched[*i].is_none() && self.tiles[*i] == *tile);
+            if let Some(current) = current {
+                matched[current] = Some(target);
+            }
+        }
+        for index in (0..self.tiles.len()).rev() {
+            if matched[index].is_none() {
+                self.remove_tile(index);
+            }
+        }
+        // the place in the new list of every current piece
+        let mut order: Vec<usize> = matched.into_iter().flatten().collect();
+        for (target, tile) in tiles.iter().enumerate() {
+            if !order.contains(&target) {
+                self.add_tile(tile.clone());
+                order.push(target);
+            }
+        }
+        let removed: Vec<Point> = self
+            .board
+            .points
+            .iter()
+            .filter(|p| board.index(p).is_none())
+            .cloned()
+            .collect();
+        if !removed.is_empty() {
+            self.remove_cells(&removed);
+        }
+        let added: Vec<Point> = board
+            .points
+            .iter()
+            .filter(|p| self.board.index(p).is_none())
+            .cloned()
+            .collect();
+        if !added.is_empty() {
+            self.add_cells(&added);
+        }
+        self.board.name = board.name;
+
+        // put the pieces in the given order
+        let n_cells = self.board.len();
+        for (index, info) in self.infos.iter_mut().enumerate() {
+            info.piece = order[info.piece];
+            let row = self.rows.get_mut(index);
+            *row.last_mut().unwrap() = u32::try_from(n_cells + info.piece).unwrap();
+        }
+        self.tiles = tiles;
+
+        // the previous placements first, in their new pieces
+        let mut used = vec![false; self.tiles.len()];
+        let mut preferred: HashSet<Placement> = HashSet::new();
+        for (tile, placement) in previous {
+            let same = |i: &usize| !used[*i] && self.tiles[*i].points == tile.points;
+            let piece = (0..self.tiles.len())
+                .find(|i| same(i) && self.tiles[*i].name == tile.name)
+                .or_else(|| (0..self.tiles.len()).find(same));
+            if let Some(piece) = piece {
+                used[piece] = true;
+                preferred.insert(Placement { piece, ..placement });
+            }
+        }
+        let (first, rest): (Vec<usize>, Vec<usize>) =
+            (0..self.infos.len()).partition(|r| preferred.contains(&self.infos[*r]));
+        let mut rows = Rows::new();
+        let mut infos = Vec::with_capacity(self.infos.len());
+        for r in first.into_iter().chain(rest) {
+            rows.push(&self.rows.to_vec(r));
+            infos.push(self.infos[r].clone());
+        }
+        // the rows of the optional cells stay last
+        for r in self.infos.len()..self.rows.len() {
+            rows.push(&self.rows.to_vec(r));
+        }
+        self.rows = rows;
+        self.infos = infos;
+        self.assemble();
+    }
+
+    pub fn len(&self) -> usize {
+        self.board.points.len()
+    }
+
+    // false when a checkerboard coloring argument proves the puzzle unsolvable.
+    pub fn is_parity_feasible(&self) -> bool {
+        self.parity_feasible
+    }
+
+    // why the puzzle has no solution, when a check before the search shows
+    // it. `solve` then returns None right away.
+    pub fn unsolvable(&self) -> Option<&Unsolvable> {
+        self.unsolvable.as_ref()
+    }
+
+    // problems found while reading and building the game.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    // names and cell index ranges of the boards, in the order of the solution cells.
+    pub fn boards(&self) -> Vec<(&str, Range<usize>)> {
+        let mut start = 0;
+        self.boards
+            .iter()
+            .map(|(name, size)| {
+                start += size;
+                (name.as_str(), (start - size)..start)
+            })
+            .collect()
+    }
+
+    pub fn solve(&mut self) -> Option<Vec<usize>> {
+        loop {
+            let solution = self.next_solution()?;
+            if !self.accepts(&solution) {
+                continue;
+            }
+            return Some(solution);
+        }
+    }
+
+    // push the remaining solutions of the `solve` enumeration into the sink,
+    // returns how many there were.
+    pub fn solve_into_sink(&mut self, sink: &mut dyn SolutionSink) -> usize {
+        self.solve_into(|solution| {
+            sink.push(solution.clone());
+            ControlFlow::Continue(())
+        })
+    }
+
+    // hand the remaining solutions of the `solve` enumeration to `sink` until
+    // it breaks, returns how many it got.
+    pub fn solve_into(&mut self, mut sink: impl FnMut(&Solution) -> ControlFlow<()>) -> usize {
+        let mut count = 0;
+        while let Some(solution) = self.solve() {
+            count += 1;
+            if sink(&solution).is_break() {
+                break;
+            }
+        }
+        count
+    }
+
+    fn next_solution(&mut self) -> Option<Vec<usize>> {
+        if let Some(search) = &mut self.search {
+            let mut none = NoObserver;
+            let mut observer = RowIds {
+                ids: &self.ids,
+                observer: match &mut self.observer {
+                    Some(observer) => observer.as_mut(),
+                    None => &mut none,
+                },
+            };
+            let mut solution: Vec<usize> = search
+                .next(&mut observer)?
+                .iter()
+                .map(|r| self.ids[*r])
+                .collect();
+            solution.sort();
+            return Some(solution);
+        }
+        match (&mut self.solver, &self.copies) {
+            (Enumeration::Links(solver), Some(copies)) => loop {
+                if let Some(rows) = self.expansion.as_mut().and_then(Iterator::next) {
+                    let mut solution: Vec<usize> = rows.iter().map(|r| self.ids[*r]).collect();
+                    solution.sort();
+                    return Some(solution);
+                }
+                let rows: Vec<usize> = solver.next()?.iter().map(|id| copies.row(*id)).collect();
+                self.expansion = Some(copies.expand(&rows));
+            },
+            (Enumeration::Links(solver), None) => solver.next(),
+            (Enumeration::Other(solver), _) => solver.next(),
+        }
+    }
+
+    // number of matrix columns: the board cells followed by the pieces.
+    pub fn n_cols(&self) -> usize {
+        self.board.len() + self.tiles.len()
+    }
+
+    // solve with another exact cover engine, given with `n_cols` empty columns.
+    // Restarts the enumeration.
+    pub fn set_backend(&mut self, backend: Box<dyn ExactCover>) {
+        self.replace_solver(Enumeration::Other(solve_rows(
+            backend, &self.rows, &self.ids,
+        )));
+        self.replace_search(None);
+    }
+
+    // rectangles (width <= height) with the same area as the pieces, up to
+    // `max_area`, that the pieces can tile.
+    pub fn tileable_rectangles(&self, max_area: usize) -> Vec<(usize, usize)> {
+        let area: usize = self.tiles.iter().map(|t| t.len()).sum();
+        if area == 0 || area > max_area {
+            return Vec::new();
+        }
+
+        let mut rectangles = Vec::new();
+        for width in 1..=area {
+            let height = area / width;
+            if width > height {
+                break;
+            }
+            if width * height != area {
+                continue;
+            }
+
+            let mut game = Self::build(
+                Tile::rect(width, height),
+                self.tiles.clone(),
+                &Rules::default(),
+            );
+            game.set_no_crossroads(self.no_crossroads);
+            if game.solve().is_some() {
+                rectangles.push((width, height));
+            }
+        }
+        rectangles
+    }
+
+    // whether some solutions of the matrix may be rejected.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn filters_solutions(&self) -> bool {
+        #[cfg(feature = "scripting")]
+        if self.rules.script.is_some() {
+            return true;
+        }
+        self.no_crossroads
+    }
+
+    // whether the solution passes the solution filters.
+    fn accepts(&self, solution: &[usize]) -> bool {
+        if self.no_crossroads && self.has_crossroad(solution) {
+            return false;
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(script) = &self.rules.script {
+            let placed: Vec<(&str, Vec<Point>)> = solution
+                .iter()
+                .filter_map(|r| {
+                    let name = self.tiles[self.placement(*r)?.piece].name.as_str();
+                    let points = self
+                        .row_cells(*r)
+                        .iter()
+                        .map(|i| self.board.points[*i].clone())
+                        .collect();
+                    Some((name, points))
+                })
+                .collect();
+            if !script.allows_solution(&placed) {
+                return false;
+            }
+        }
+        true
+    }
+
+    // skip solutions where four distinct pieces meet at a single lattice point.
+    pub fn set_no_crossroads(&mut self, no_crossroads: bool) {
+        self.no_crossroads = no_crossroads;
+    }
+
+    // pairs of pieces (by index, smaller first) of a solution sharing an edge,
+    // sorted.
+    pub fn adjacency_graph(&self, solution: &[usize]) -> Vec<(usize, usize)> {
+        let mut owners: HashMap<Point, usize> = HashMap::new();
+        for r in solution {
+            if let Some(info) = self.placement(*r) {
+                for i in self.row_cells(*r) {
+                    owners.insert(self.board.points[i].clone(), info.piece);
+                }
+            }
+        }
+
+        let mut edges: HashSet<(usize, usize)> = HashSet::new();
+        for (point, a) in &owners {
+            for neighbour in self.board.neighbors4(point, self.rules.topology) {
+                match owners.get(&neighbour) {
+                    Some(b) if b != a => {
+                        edges.insert((*a.min(b), *a.max(b)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let mut edges: Vec<(usize, usize)> = edges.into_iter().collect();
+        edges.sort();
+        edges
+    }
+
+    // whether four distinct pieces of the solution meet at a single lattice point.
+    pub fn has_crossroad(&self, solution: &[usize]) -> bool {
+        let mut owners: HashMap<Point, usize> = HashMap::new();
+        for (piece, r) in solution.iter().enumerate() {
+            for i in self.row_cells(*r) {
+                owners.insert(self.board.points[i].clone(), piece);
+            }
+        }
+
+        // the square right of and below each cell, across the edges that wrap
+        for point in &self.board.points {
+            let corners = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .map(|(dx, dy)| self.wrapped(&(point.clone() + Point::new(dx, dy))));
+            let pieces: HashSet<usize> = corners
+                .iter()
+                .filter_map(|p| owners.get(p).cloned())
+                .collect();
+            if pieces.len() == 4 {
+                return true;
+            }
+        }
+        false
+    }
+
+    // fresh in-crate search over the rows, with the enabled pruning.
+    fn new_search(&self) -> Search {
+        let mut search = Search::new(self.board.len() + self.tiles.len(), self.rows.to_vecs());
+        // the regions left for optional cells need not be filled
+        if self.dead_region_pruning && self.optional.points.is_empty() {
+            search.add_pruner(Box::new(DeadRegions::new(
+                &self.board,
+                &self.tiles,
+                self.rules.topology,
+            )));
+        }
+        search
+    }
+
+    // drop rows that repeat others or cannot be part of any solution when
+    // generating them. Every edit then regenerates all rows. Dancing links
+    // also solves with the copies of a piece as one piece, a column needing
+    // a row per copy, and hands out every assignment of the copies to its
+    // tilings. Rebuilds the rows and restarts the enumeration.
+    pub fn set_row_reduction(&mut self, reduce: bool) {
+        self.reduce_rows = reduce;
+        self.rebuild();
+    }
+
+    // make `solve_parallel` return the solutions in the order a single thread
+    // finds them, for reproducible output, instead of sorted.
+    #[cfg(feature = "parallel")]
+    pub fn set_ordered_parallel(&mut self, ordered: bool) {
+        self.ordered_parallel = ordered;
+    }
+
+    // backtrack as soon as an uncovered region cannot be filled by the remaining
+    // pieces. Restarts the enumeration.
+    pub fn set_dead_region_pruning(&mut self, pruning: bool) {
+        self.dead_region_pruning = pruning;
+        self.restart();
+    }
+
+    // report search events to the observer, restarting the enumeration.
+    pub fn set_observer(&mut self, observer: Box<dyn SearchObserver>) {
+        self.observer = Some(observer);
+        self.restart();
+    }
+
+    // stop reporting search events, and return the observer.
+    pub fn take_observer(&mut self) -> Option<Box<dyn SearchObserver>> {
+        let observer = self.observer.take();
+        if !self.uses_search() {
+            self.replace_search(None);
+        }
+        observer
+    }
+
+    // search for a solution within `max_steps` placements, or until `cancel` is
+    // set, returning the most covering partial tiling when stopped early.
+    pub fn solve_anytime(&self, max_steps: usize, cancel: Option<&AtomicBool>) -> Anytime {
+        let mut search = self.new_search();
+        let mut stop =
+            |steps: usize| steps >= max_steps || cancel.is_some_and(|c| c.load(Ordering::Relaxed));
+
+        let ids = |rows: &[usize]| {
+            let mut ids: Vec<usize> = rows.iter().map(|r| self.ids[*r]).collect();
+            ids.sort();
+            ids
+        };
+
+        loop {
+            match search.next_until(&mut NoObserver, &mut stop) {
+                Some(solution) => {
+                    let solution = ids(&solution);
+                    if !self.accepts(&solution) {
+                        continue;
+                    }
+                    return Anytime::Solution(solution);
+                }
+                None if search.interrupted() => {
+                    return Anytime::Partial(ids(search.best_partial()));
+                }
+                None => return Anytime::Unsolvable,
+            }
+        }
+    }
+
+    // keep the last `capacity` decisions of the engine `solve` runs, to
+    // attach to reports of slow or failing puzzles. Dancing cells keeps no
+    // trace. Zero turns tracing off.
+    pub fn set_trace_capacity(&mut self, capacity: usize) {
+        self.trace_capacity = capacity;
+        match (&mut self.search, &mut self.solver) {
+            (Some(search), _) => search.set_trace_capacity(capacity),
+            (None, Enumeration::Links(solver)) => solver.set_trace_capacity(capacity),
+            (None, Enumeration::Other(_)) => {}
+        }
+    }
+
+    // the most recent decisions of the running engine, oldest first, with
+    // rows by solver id as in solutions.
+    pub fn trace_tail(&self) -> Vec<TraceEvent> {
+        match (&self.search, &self.solver) {
+            (Some(search), _) => search
+                .trace_tail()
+                .into_iter()
+                .map(|event| match event {
+                    TraceEvent::Enter {
+                        row,
+                        depth,
+                        options,
+                    } => TraceEvent::Enter {
+                        row: self.ids[row],
+                        depth,
+                        options,
+                    },
+                    TraceEvent::Backtrack { row, depth } => TraceEvent::Backtrack {
+                        row: self.ids[row],
+                        depth,
+                    },
+                    event => event,
+                })
+                .collect(),
+            (None, Enumeration::Links(solver)) => match &self.copies {
+                Some(copies) => solver
+                    .trace_tail()
+                    .into_iter()
+                    .map(|event| match event {
+                        TraceEvent::Enter {
+                            row,
+                            depth,
+                            options,
+                        } => TraceEvent::Enter {
+                            row: self.ids[copies.row(row)],
+                            depth,
+                            options,
+                        },
+                        TraceEvent::Backtrack { row, depth } => TraceEvent::Backtrack {
+                            row: self.ids[copies.row(row)],
+                            depth,
+                        },
+                        event => event,
+                    })
+                    .collect(),
+                None => solver.trace_tail(),
+            },
+            (None, Enumeration::Other(_)) => Vec::new(),
+        }
+    }
+
+    // estimated bytes held by the game. The enumeration works on its own copy
+    // of the matrix, or of the one with the copies of a piece collapsed, plus
+    // a copy of the rows when the in-crate search runs.
+    pub fn memory_report(&self) -> MemoryReport {
+        let placements = self.rows.bytes()
+            + self.infos.capacity() * std::mem::size_of::<Placement>()
+            + self.ids.capacity() * std::mem::size_of::<usize>();
+        let mut matrix = self.matrix.bytes();
+        let mut solver = self.matrix.bytes();
+        if let Some(copies) = &self.copies {
+            matrix += copies.bytes();
+            solver = copies.matrix().bytes();
+        }
+        if self.search.is_some() {
+            solver += nested_bytes(&self.rows.to_vecs());
+        }
+        MemoryReport {
+            placements,
+            matrix,
+            solver,
+            solutions: 0,
+        }
+    }
+
+    // board cell indices of each 4-connected region of the board, in the
+    // order of their first cell.
+    pub fn regions(&self) -> Vec<Vec<usize>> {
+        let positions: HashMap<&Point, usize> = self
+            .board
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p, i))
+            .collect();
+        let mut regions: Vec<Vec<usize>> = self
+            .board
+            .components()
+            .iter()
+            .map(|region| region.points.iter().map(|p| positions[p]).collect())
+            .collect();
+        for region in regions.iter_mut() {
+            region.sort();
+        }
+        regions.sort();
+        regions
+    }
+
+    // the rows split over the regions of the board, None when a placement
+    // spans several of them.
+    fn split_regions(&self) -> Option<Regions<'_>> {
+        let optional: HashSet<usize> = self
+            .optional
+            .points
+            .iter()
+            .filter_map(|p| self.board.index(p))
+            .collect();
+        let regions = self.regions();
+        let must = regions
+            .iter()
+            .map(|cells| cells.iter().filter(|c| !optional.contains(c)).count())
+            .collect();
+        let sizes = self.tiles.iter().map(|t| t.len()).collect();
+        Regions::new(self.board.len(), regions, must, sizes, &self.rows)
+    }
+
+    // count all solutions, tiling the separate regions of the board (like
+    // the trays of a two tray puzzle) one at a time with every way to share
+    // the pieces between them. Solution filters are not applied.
+    pub fn count_by_regions(&self) -> u128 {
+        match self.split_regions() {
+            Some(regions) if regions.len() > 1 => regions.count(),
+            _ => self.count_memoized(),
+        }
+    }
+
+    // a solution found region by region, like `count_by_regions`.
+    // Independent of the `solve` enumeration.
+    pub fn solve_by_regions(&self) -> Option<Solution> {
+        match self.split_regions() {
+            Some(regions) if regions.len() > 1 => {
+                let rows = regions.solve()?;
+                Some(rows.iter().map(|r| self.ids[*r]).collect())
+            }
+            _ => {
+                let solution = self.new_search().next(&mut NoObserver)?;
+                let mut solution: Solution = solution.iter().map(|r| self.ids[*r]).collect();
+                solution.sort();
+                Some(solution)
+            }
+        }
+    }
+
+    // count all solutions, independently of the `solve` enumeration.
+    pub fn count_solutions(&self) -> usize {
+        let mut search = self.new_search();
+        let mut count = 0;
+        while let Some(solution) = search.next(&mut NoObserver) {
+            let solution: Vec<usize> = solution.iter().map(|r| self.ids[*r]).collect();
+            if self.accepts(&solution) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    // count all solutions with a transposition table, which makes long strips
+    // and other boards with many equivalent partial tilings tractable. Solution
+    // filters (no crossroads, solution rules) are not applied.
+    pub fn count_memoized(&self) -> u128 {
+        count::count(self.n_cols(), &self.rows)
+    }
+
+    // all solutions, splitting the search over the thread pool. Independent of
+    // the `solve` enumeration. Sorted, or with `set_ordered_parallel` in the
+    // order of `solve` with the default options.
+    #[cfg(feature = "parallel")]
+    pub fn solve_parallel(&self) -> Vec<Vec<usize>> {
+        let rows = self.rows.to_vecs();
+        let solutions = match self.ordered_parallel {
+            true => parallel::solve_all_ordered(self.n_cols(), &rows, 128),
+            false => parallel::solve_all_stealing(self.n_cols(), &rows, 128),
+        };
+        solutions
+            .into_iter()
+            .map(|solution| solution.iter().map(|r| self.ids[*r]).collect())
+            .filter(|solution: &Vec<usize>| self.accepts(solution))
+            .collect()
+    }
+
+    // placements that can extend the placed rows, ranked by the number of
+    // solutions each keeps alive. Enumerates all completions.
+    pub fn hints(&self, placed: &[usize], style: HintStyle) -> Vec<Hint> {
+        let mut m = self.matrix.clone();
+
+        // take the columns of the placed rows out, failing on overlaps
+        let mut covered = vec![false; self.n_cols()];
+        for r in placed {
+            let index = match self.row_index(*r) {
+                Some(index) => index,
+                None => return Vec::new(),
+            };
+            for c in self.rows.to_vec(index) {
+                if covered[c] {
+                    return Vec::new();
+                }
+                covered[c] = true;
+                ExactCover::cover(&mut m, c);
+            }
+        }
+
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        for completion in ExactCover::solve_iter(Box::new(m)) {
+            let mut solution: Vec<usize> = placed.iter().chain(&completion).cloned().collect();
+            solution.sort();
+            if !self.accepts(&solution) {
+                continue;
+            }
+            for r in completion {
+                if self.placement(r).is_some() {
+                    *counts.entry(r).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let hints = counts
+            .into_iter()
+            .map(|(row, solutions)| Hint { row, solutions })
+            .collect();
+        rank(hints, style)
+    }
+
+    // board cell and piece changes from this puzzle to `other`.
+    pub fn diff(&self, other: &Game) -> Diff {
+        Diff::new(&self.board, &self.tiles, &other.board, &other.tiles)
+    }
+
+    // estimate the total number of solutions from random dives into the search tree.
+    pub fn estimate_solution_count(&self, samples: usize) -> Estimate {
+        let mut rng = Rng::new(0x5EED);
+        estimate(
+            self.board.len() + self.tiles.len(),
+            &self.rows.to_vecs(),
+            samples,
+            &mut rng,
+        )
+    }
+
+    // matrix columns (1-based, cells then the piece) of a solver row id.
+    pub fn row(&self, row: usize) -> Vec<usize> {
+        match self.row_index(row) {
+            Some(index) => self
+                .rows
+                .get(index)
+                .iter()
+                .map(|c| *c as usize + 1)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn board(&self) -> &Tile {
+        &self.board
+    }
+
+    // number of matrix columns and the rows, as cell and piece column indices.
+    pub(crate) fn matrix(&self) -> (usize, &Rows) {
+        (self.n_cols(), &self.rows)
+    }
+
+    // solver ids of all rows, ascending.
+    pub fn row_ids(&self) -> &[usize] {
+        &self.ids
+    }
+
+    // index into the row tables from a solver row id.
+    fn row_index(&self, row: usize) -> Option<usize> {
+        self.ids.binary_search(&row).ok()
+    }
+
+    // placement (piece, transform and anchor) of a solver row id, None for
+    // the row leaving an optional cell uncovered.
+    pub fn placement(&self, row: usize) -> Option<&Placement> {
+        self.infos.get(self.row_index(row)?)
+    }
+
+    // solver row id of a placement, None when the rules do not allow it.
+    pub fn placement_row(&self, placement: &Placement) -> Option<usize> {
+        let index = self.infos.iter().position(|p| p == placement)?;
+        Some(self.ids[index])
+    }
+
+    // placements of a (partial) solution.
+    pub fn placements(&self, solution: &[usize]) -> Vec<Placement> {
+        solution
+            .iter()
+            .filter_map(|r| self.placement(*r).cloned())
+            .collect()
+    }
+
+    // board cell indices covered by a solver row id, none for the row leaving
+    // an optional cell uncovered.
+    pub fn row_cells(&self, row: usize) -> Vec<usize> {
+        match self.row_index(row) {
+            Some(index) if index < self.infos.len() => {
+                let mut cells = self.rows.to_vec(index);
+                cells.pop();
+                cells
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // labeled squares of a solver row id as board cell indices and labels,
+    // from the labels drawn in the piece or its colors grid.
+    pub fn placement_labels(&self, row: usize) -> Vec<(usize, char)> {
+        let Some(placement) = self.placement(row) else {
+            return Vec::new();
+        };
+        let tile = &self.tiles[placement.piece];
+        let colors = self.rules.square_colors(&tile.name, &tile.points);
+        if colors.is_empty() {
+            return Vec::new();
+        }
+        // transforms keep the order of the points, and so their colors
+        let mut placed = tile.clone();
+        placement.transform.apply(&mut placed);
+        placed.translate(&(placement.anchor.clone() - placed.offset()));
+        placed
+            .points
+            .iter()
+            .zip(colors)
+            .filter(|(_, label)| *label != '.')
+            .filter_map(|(point, label)| Some((self.board.index(&self.wrapped(point))?, label)))
+            .collect()
+    }
+
+    // a point taken back into the board's bounding box along the edges that
+    // wrap around.
+    fn wrapped(&self, point: &Point) -> Point {
+        if self.rules.topology == Topology::Plane || self.board.points.is_empty() {
+            return point.clone();
+        }
+        let (offset, size) = self.board.bounding_box();
+        self.rules.topology.wrap(point, &offset, &size)
+    }
+
+    // label of every board cell under a (partial) solution, None where the
+    // cell is uncovered or its square has no label.
+    pub fn cell_labels(&self, solution: &[usize]) -> Vec<Option<char>> {
+        let mut labels = vec![None; self.board.len()];
+        for r in solution {
+            for (cell, label) in self.placement_labels(*r) {
+                labels[cell] = Some(label);
+            }
+        }
+        labels
+    }
+
+    // the board with the pieces of a (partial) solution, over the bounding box
+    // of the board and its blocked cells.
+    pub fn solution_grid(&self, solution: &[usize]) -> Grid {
+        let mut all = self.board.clone();
+        all.points.extend(self.blocked.points.iter().cloned());
+        if all.points.is_empty() {
+            return Grid::new(0, 0);
+        }
+        let size = all.size();
+        let offset = self.grid_offset();
+        let mut grid = Grid::new(size.width, size.height);
+        let mut set = |point: &Point, cell: Cell| {
+            let x = usize::try_from(point.x - offset.x).unwrap();
+            let y = usize::try_from(point.y - offset.y).unwrap();
+            grid.set(x, y, cell);
+        };
+
+        for point in &self.blocked.points {
+            set(point, Cell::Blocked);
+        }
+        for point in &self.board.points {
+            set(point, Cell::Uncovered);
+        }
+        for r in solution {
+            if let Some(info) = self.placement(*r) {
+                for c in self.row_cells(*r) {
+                    set(&self.board.points[c], Cell::Piece(info.piece));
+                }
+            }
+        }
+        grid
+    }
+
+    // board position of the top left of the solution grids.
+    fn grid_offset(&self) -> Point {
+        let (x, y) = self
+            .board
+            .points
+            .iter()
+            .chain(&self.blocked.points)
+            .fold((isize::MAX, isize::MAX), |(x, y), p| {
+                (x.min(p.x), y.min(p.y))
+            });
+        match x == isize::MAX {
+            true => Point::new(0, 0),
+            false => Point::new(x, y),
+        }
+    }
+
+    // board cell index (as in solutions) of the solution grid position (x, y),
+    // None off the board.
+    pub fn cell_index(&self, x: usize, y: usize) -> Option<usize> {
+        let offset = self.grid_offset();
+        let point = Point::from(&x, &y) + offset;
+        self.board.index(&point)
+    }
+
+    // solution grid position (x, y) of a board cell index.
+    pub fn cell_coords(&self, index: usize) -> (usize, usize) {
+        let point = self.board.points[index].clone() - self.grid_offset();
+        (
+            usize::try_from(point.x).unwrap(),
+            usize::try_from(point.y).unwrap(),
+        )
+    }
+
+    // weight of every board cell, 1 unless given in the puzzle's weights grid
+    // or set.
+    pub fn weights(&self) -> Vec<u32> {
+        self.board
+            .points
+            .iter()
+            .map(|p| self.rules.weights.get(p).copied().unwrap_or(1))
+            .collect()
+    }
+
+    // set the weight of board cells. Solutions stay the same, only their
+    // scores change.
+    pub fn set_weights(&mut self, weights: &[(Point, u32)]) {
+        for (point, weight) in weights {
+            self.rules.weights.insert(point.clone(), *weight);
+        }
+    }
+
+    // total weight of the board cells a (partial) solution covers.
+    pub fn solution_weight(&self, solution: &[usize]) -> u32 {
+        let weights = self.weights();
+        solution
+            .iter()
+            .flat_map(|r| self.row_cells(*r))
+            .map(|cell| weights[cell])
+            .sum()
+    }
+
+    // names of the pieces, by index.
+    pub fn piece_names(&self) -> Vec<&str> {
+        self.tiles.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    // groups of interchangeable pieces: indices of congruent tiles, in order,
+    // for every shape that occurs more than once. Pieces with colored or
+    // labeled squares are told apart and never grouped.
+    pub fn duplicate_tiles(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<(Points, Vec<usize>)> = Vec::new();
+        for (index, tile) in self.tiles.iter().enumerate() {
+            if !self
+                .rules
+                .square_colors(&tile.name, &tile.points)
+                .is_empty()
+            {
+                continue;
+            }
+            let canonical = tile.canonical().points;
+            match groups.iter_mut().find(|(shape, _)| *shape == canonical) {
+                Some((_, group)) => group.push(index),
+                None => groups.push((canonical, vec![index])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(_, group)| group)
+            .filter(|group| group.len() > 1)
+            .collect()
+    }
+
+    // pack `copies` of every piece on a sheet for cutting, `spacing` cells apart.
+    pub fn nest(
+        &self,
+        copies: usize,
+        width: usize,
+        height: usize,
+        spacing: usize,
+    ) -> Option<Nesting> {
+        nesting::nest(&self.tiles, copies, width, height, spacing)
+    }
+
+    // where to write the name of each piece of a (partial) solution: the
+    // piece index and the center of its label anchor cell, in solution grid
+    // coordinates, by piece.
+    pub fn label_positions(&self, solution: &[usize]) -> Vec<(usize, (f64, f64))> {
+        let offset = self.grid_offset();
+        let mut positions = Vec::new();
+        for r in solution {
+            let Some(placement) = self.placement(*r) else {
+                continue;
+            };
+            let mut tile = Tile::new("");
+            for c in self.row_cells(*r) {
+                tile.points.push(self.board.points[c].clone());
+            }
+            let anchor = tile.label_anchor().unwrap();
+            let x = (anchor.x - offset.x) as f64 + 0.5;
+            let y = (anchor.y - offset.y) as f64 + 0.5;
+            positions.push((placement.piece, (x, y)));
+        }
+        positions.sort_by_key(|(piece, _)| *piece);
+        positions
+    }
+
+    // the outline of each piece of a (partial) solution as edge segments
+    // between grid vertices, in solution grid coordinates, by piece. The
+    // segments run around the piece with it on the right, each ending where
+    // the next starts, the outside first and then any holes.
+    pub fn piece_outlines(&self, solution: &[usize]) -> Vec<(usize, Vec<(Point, Point)>)> {
+        let offset = self.grid_offset();
+        let mut outlines = Vec::new();
+        for r in solution {
+            let Some(placement) = self.placement(*r) else {
+                continue;
+            };
+            let mut tile = Tile::new("");
+            for c in self.row_cells(*r) {
+                tile.points
+                    .push(self.board.points[c].clone() - offset.clone());
+            }
+            let mut segments = Vec::new();
+            for corners in analysis::outlines(&tile) {
+                for (i, corner) in corners.iter().enumerate() {
+                    let next = &corners[(i + 1) % corners.len()];
+                    segments.push((corner.clone(), next.clone()));
+                }
+            }
+            outlines.push((placement.piece, segments));
+        }
+        outlines.sort_by_key(|(piece, _)| *piece);
+        outlines
+    }
+
+    pub fn solution_tiles(&self, solution: Vec<usize>) -> Vec<Tile> {
+        let mut tiles: Vec<Tile> = Vec::new();
+
+        for r in solution {
+            let Some(info) = self.placement(r) else {
+                continue;
+            };
+            let name = &self.tiles[info.piece].name;
+            let points: Points = self
+                .row_cells(r)
+                .iter()
+                .map(|i| self.board.points[*i].clone())
+                .collect();
+
+            tiles.push(Tile {
+                name: name.to_string(),
+                points,
+            });
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+    use std::ops::ControlFlow;
+    use std::rc::Rc;
+    use std::sync::atomic::AtomicBool;
+
+    use smallvec::smallvec;
+
+    use super::Algorithm;
+    use super::Anytime;
+    use super::Cell as GridCell;
+    use super::Charset;
+    use super::Game;
+    use super::GameError;
+    use super::Heuristic;
+    use super::Hint;
+    use super::HintStyle;
+    use super::Placement;
+    use super::Point;
+    use super::Rules;
+    use super::SearchObserver;
+    use super::SnapshotError;
+    use super::SolveOptions;
+    use super::Tile;
+    use super::Topology;
+    use super::TraceEvent;
+    use super::Transform;
+    use super::Unsolvable;
+    use super::Warning;
+    use crate::shape::Size;
+
+    // the 2x3 board, an L and a J tromino, which tile it in four ways.
+    fn l_j() -> (Tile, Tile, Tile) {
+        (
+            Tile::from_str("Board", "xxx\nxxx"),
+            Tile::from_str("L", "xx\nx"),
+            Tile::from_str("J", "xx\n x"),
+        )
+    }
+
+    fn l_j_game() -> Game {
+        let (board, l, j) = l_j();
+        Game::build(board, vec![l, j], &Rules::default())
+    }
+
+    #[test]
+    fn new_rect() {
+        assert_eq!(Tile::rect(3, 2), Tile::from_str("Board", "xxx\nxxx"));
+        let tiles = vec![Tile::from_str("L", "xx\nx"), Tile::from_str("J", "xx\n x")];
+        let mut game = Game::new_rect(3, 2, tiles);
+        let mut count = 0;
+        while game.solve().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn label_positions() {
+        let square = Tile::from_str("O", "xx\nxx");
+        assert_eq!(square.centroid(), Some((1.0, 1.0)));
+        assert_eq!(Tile::new("Empty").centroid(), None);
+
+        // the centroid of the L lies outside it, the label goes next to the
+        // corner. That of the U is just below the notch.
+        let l = Tile::from_str("L", "x\nx\nxxx");
+        assert!(!l.points.contains(&Point::new(1, 1)));
+        assert_eq!(l.label_anchor(), Some(Point::new(0, 1)));
+        let u = Tile::from_str("U", "x x\nxxx");
+        let (_, cy) = u.centroid().unwrap();
+        assert!((cy - 1.1).abs() < 1e-9);
+        assert_eq!(u.label_anchor(), Some(Point::new(1, 1)));
+
+        let mut game =
+            Game::from_yaml("Board: |\n    #xxxx\nA: |\n    xxx\nB: |\n    x\n").unwrap();
+        let solution = game.solve().unwrap();
+        let positions = game.label_positions(&solution);
+        let a = game.placements(&solution)[0].anchor.x;
+        let expected = match a {
+            0 => vec![(0, (2.5, 0.5)), (1, (4.5, 0.5))],
+            _ => vec![(0, (3.5, 0.5)), (1, (1.5, 0.5))],
+        };
+        assert_eq!(positions, expected);
+    }
+
+    #[test]
+    fn regions() {
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxx\n",
+            "    xxx\n",
+            "Board 2: |\n",
+            "    xxx\n",
+            "    o\n",
+            "L: |\n",
+            "    xx\n",
+            "    x\n",
+            "J: |\n",
+            "    xx\n",
+            "     x\n",
+            "I: |\n",
+            "    xxx\n",
+        );
+        let mut game = Game::from_yaml(yaml).unwrap();
+        assert_eq!(game.regions().len(), 2);
+        assert_eq!(game.regions()[1], vec![6, 7, 8, 9]);
+        assert_eq!(game.count_by_regions(), game.count_memoized());
+        assert!(game.count_by_regions() > 0);
+
+        let solution = game.solve_by_regions().unwrap();
+        let mut cells: Vec<usize> = solution.iter().flat_map(|r| game.row_cells(*r)).collect();
+        cells.sort();
+        assert_eq!(cells.len(), 9);
+        cells.dedup();
+        assert_eq!(cells.len(), 9);
+        assert_eq!(game.placements(&solution).len(), 3);
+        assert!(std::iter::from_fn(|| game.solve()).any(|s| s == solution));
+
+        // a single region, and a piece spanning two regions
+        let game = Game::from_yaml("Board: |\n    xxx\nI: |\n    xxx\n").unwrap();
+        assert_eq!(game.count_by_regions(), 1);
+        let mut game = Game::from_yaml("Board: |\n    x x\nC: |\n    x x\n").unwrap();
+        assert_eq!(game.regions().len(), 2);
+        assert_eq!(game.count_by_regions(), 1);
+        assert_eq!(game.solve_by_regions(), game.solve());
+    }
+
+    #[test]
+    fn rep_tile() {
+        // the L-tromino is a rep-tile: four of them tile it scaled by two
+        let tile = Tile::from_str("L", "xx\nx");
+        let mut board = tile.scaled(2);
+        board.name = String::from("Board");
+        let tiles: Vec<Tile> = (0..4)
+            .map(|i| Tile::from_str(&format!("L{}", i), "xx\nx"))
+            .collect();
+        let mut game = Game::build(board, tiles, &Rules::default());
+        let solution = game.solve().unwrap();
+        assert_eq!(game.placements(&solution).len(), 4);
+
+        assert_eq!(tile.scaled(0).len(), 0);
+    }
+
+    #[test]
+    fn solve1() {
+        // From string
+        let contents = "xx\nxx";
+        let board = Tile::from_str("Board", &contents);
+
+        let mut tiles: Vec<Tile> = Vec::new();
+        tiles.push(Tile::from_str("T1", "xx\nx"));
+        tiles.push(Tile::from_str("T2", "x"));
+
+        let mut game = Game::build(board, tiles, &Rules::default());
+
+        let solution = game.solve().unwrap();
+        assert_eq!(solution.len(), 2);
+    }
+
+    #[test]
+    fn solve2() {
+        // From string
+        let board = Tile::from_str("Board", "xxxxx\nxxxxx\nxxxxx\nxxxxx");
+
+        let tiles: Vec<Tile> = vec![
+            Tile::from_str("T1", "xxxx\n x  "),
+            Tile::from_str("T2", "xxxx\n x  "),
+            Tile::from_str("P1", "xxx\nxx "),
+            Tile::from_str("P2", "xxx\nxx "),
+        ];
+
+        let mut game = Game::build(board, tiles, &Rules::default());
+        assert_eq!(game.len(), 20);
+
+        let solution = game.solve().unwrap();
+
+        assert_eq!(solution, vec!(25, 457, 997, 1315));
+    }
+
+    #[test]
+    fn orientations() {
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxxx\n",
+            "    xxxx\n",
+            "Orientations: |\n",
+            "    vv..\n",
+            "    vv..\n",
+            "A: |\n",
+            "    xx\n",
+            "B: |\n",
+            "    xx\n",
+            "C: |\n",
+            "    xx\n",
+            "D: |\n",
+            "    xx\n",
+        );
+        let mut game = Game::from_yaml(yaml).unwrap();
+
+        // left square must be covered by vertical dominoes
+        let mut count = 0;
+        while let Some(solution) = game.solve() {
+            for tile in game.solution_tiles(solution) {
+                if tile.points.contains(&Point::new(0, 0)) {
+                    assert!(tile.points.contains(&Point::new(0, 1)));
+                }
+            }
+            count += 1;
+        }
+        // 4 * 3 * 2 orderings of pieces for each of the 2 tilings of the right square
+        assert_eq!(count, 48);
+    }
+
+    #[test]
+    fn orientation_labels() {
+        let mut rules = Rules::default();
+        rules.add_orientations("  xx\n  xx", "  h0\n  .m").unwrap();
+
+        assert_eq!(rules.orientations.len(), 3);
+        assert_eq!(rules.orientations[&Point::new(0, 0)], 0b0101_0101);
+        assert_eq!(rules.orientations[&Point::new(1, 0)], 0b0000_0001);
+        assert_eq!(rules.orientations[&Point::new(1, 1)], 0b1111_0000);
+
+        let points = [Point::new(0, 0), Point::new(1, 0)];
+        assert_eq!(rules.allowed(&points, 0xFF), 0b0000_0001);
+        assert_eq!(rules.allowed(&points, 0b0000_0010), 0);
+    }
+
+    #[test]
+    fn estimate_solution_count() {
+        let board = Tile::from_str("Board", "xxxxx\nxxxxx\nxxxxx\nxxxxx");
+
+        let tiles: Vec<Tile> = vec![
+            Tile::from_str("T1", "xxxx\n x  "),
+            Tile::from_str("T2", "xxxx\n x  "),
+            Tile::from_str("P1", "xxx\nxx "),
+            Tile::from_str("P2", "xxx\nxx "),
+        ];
+
+        let mut game = Game::build(board, tiles, &Rules::default());
+        let estimate = game.estimate_solution_count(2000);
+
+        let mut count = 0;
+        while game.solve().is_some() {
+            count += 1;
+        }
+
+        let error = (estimate.mean - count as f64).abs();
+        assert!(error < 4.0 * estimate.standard_error() + 1.0);
+    }
+
+    #[test]
+    fn observer() {
+        #[derive(Default)]
+        struct Counter {
+            enter: Rc<Cell<usize>>,
+            backtrack: Rc<Cell<usize>>,
+            solutions: Rc<Cell<usize>>,
+        }
+
+        impl SearchObserver for Counter {
+            fn enter(&mut self, _row: usize) {
+                self.enter.set(self.enter.get() + 1);
+            }
+
+            fn backtrack(&mut self, _row: usize) {
+                self.backtrack.set(self.backtrack.get() + 1);
+            }
+
+            fn solution(&mut self, _rows: &[usize]) {
+                self.solutions.set(self.solutions.get() + 1);
+            }
+        }
+
+        let board = Tile::from_str("Board", "xxxxx\nxxxxx\nxxxxx\nxxxxx");
+        let tiles: Vec<Tile> = vec![
+            Tile::from_str("T1", "xxxx\n x  "),
+            Tile::from_str("T2", "xxxx\n x  "),
+            Tile::from_str("P1", "xxx\nxx "),
+            Tile::from_str("P2", "xxx\nxx "),
+        ];
+
+        let counter = Counter::default();
+        let (enter, backtrack, solutions) = (
+            counter.enter.clone(),
+            counter.backtrack.clone(),
+            counter.solutions.clone(),
+        );
+
+        let mut game = Game::build(board.clone(), tiles.clone(), &Rules::default());
+        game.set_observer(Box::new(counter));
+
+        let mut observed = Vec::new();
+        while let Some(solution) = game.solve() {
+            observed.push(solution);
+        }
+        assert!(game.take_observer().is_some());
+
+        // same solutions as the regular solver
+        let mut game = Game::build(board, tiles, &Rules::default());
+        let mut expected = Vec::new();
+        while let Some(solution) = game.solve() {
+            expected.push(solution);
+        }
+        observed.sort();
+        expected.sort();
+        assert_eq!(observed, expected);
+
+        assert_eq!(solutions.get(), expected.len());
+        assert!(enter.get() > 0);
+        assert_eq!(enter.get(), backtrack.get());
+    }
+
+    #[test]
+    fn adjacency_graph() {
+        // three cells in a row, the middle piece touches both others
+        let board = Tile::from_str("Board", "xxx");
+        let tiles: Vec<Tile> = ["A", "B", "C"]
+            .iter()
+            .map(|name| Tile::from_str(name, "x"))
+            .collect();
+        let mut game = Game::build(board, tiles, &Rules::default());
+        let mut solutions = 0;
+        while let Some(solution) = game.solve() {
+            let mut order: Vec<&Placement> = solution
+                .iter()
+                .map(|r| game.placement(*r).unwrap())
+                .collect();
+            order.sort_by_key(|p| p.anchor.x);
+            let edge = |a: usize, b: usize| (a.min(b), a.max(b));
+            let mut expected = vec![
+                edge(order[0].piece, order[1].piece),
+                edge(order[1].piece, order[2].piece),
+            ];
+            expected.sort();
+            assert_eq!(game.adjacency_graph(&solution), expected);
+            solutions += 1;
+        }
+        assert_eq!(solutions, 6);
+        assert_eq!(game.adjacency_graph(&[]), vec![]);
+
+        // on a ring the first and last pieces touch too
+        let board = Tile::from_str("Board", "xxxx");
+        let tiles: Vec<Tile> = ["A", "B", "C", "D"]
+            .iter()
+            .map(|name| Tile::from_str(name, "x"))
+            .collect();
+        let mut game = Game::build(board, tiles, &Rules::default());
+        game.set_topology(Topology::Torus);
+        let solution = game.solve().unwrap();
+        let edges = game.adjacency_graph(&solution);
+        assert_eq!(edges.len(), 4);
+        let first = game.placement(solution[0]).unwrap().piece;
+        assert_eq!(
+            edges
+                .iter()
+                .filter(|(a, b)| *a == first || *b == first)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn no_crossroads() {
+        // four squares meeting in the middle, or two pairs of dominoes
+        let board = Tile::from_str("Board", "xxxx\nxxxx\nxxxx\nxxxx");
+        let tiles: Vec<Tile> = vec![
+            Tile::from_str("A", "xx\nxx"),
+            Tile::from_str("B", "xx\nxx"),
+            Tile::from_str("C", "xx\nxx"),
+            Tile::from_str("D", "xx\nxx"),
+        ];
+
+        let mut game = Game::build(board.clone(), tiles.clone(), &Rules::default());
+        let solution = game.solve().unwrap();
+        assert!(game.has_crossroad(&solution));
+
+        let mut game = Game::build(board, tiles, &Rules::default());
+        game.set_no_crossroads(true);
+        assert_eq!(game.solve(), None);
+
+        // dominoes only meet four at a time when stacked pairs are side by side
+        let board = Tile::from_str("Board", "xxxx\nxxxx");
+        let tiles: Vec<Tile> = vec![
+            Tile::from_str("A", "xx"),
+            Tile::from_str("B", "xx"),
+            Tile::from_str("C", "x\nx"),
+            Tile::from_str("D", "x\nx"),
+        ];
+        let mut game = Game::build(board, tiles, &Rules::default());
+        game.set_no_crossroads(true);
+
+        let mut count = 0;
+        while let Some(solution) = game.solve() {
+            assert!(!game.has_crossroad(&solution));
+            count += 1;
+        }
+        // all 5 domino tilings but the two stacked pairs side by side, times 4! labelings
+        assert_eq!(count, 96);
+
+        // with the domino in the middle column the monominoes meet across
+        // the seam of a torus
+        let board = Tile::from_str("Board", "xxx\nxxx");
+        let mut tiles: Vec<Tile> = ["A", "B", "C", "D"]
+            .iter()
+            .map(|name| Tile::from_str(name, "x"))
+            .collect();
+        tiles.push(Tile::from_str("E", "x\nx"));
+        let mut game = Game::build(board, tiles, &Rules::default());
+        let mut crossroads = Vec::new();
+        while let Some(solution) = game.solve() {
+            crossroads.push(game.has_crossroad(&solution));
+        }
+        assert!(crossroads.contains(&false));
+        game.set_topology(Topology::Torus);
+        while let Some(solution) = game.solve() {
+            assert!(game.has_crossroad(&solution));
+        }
+    }
+
+    #[test]
+    fn multiple_boards() {
+        let yaml = concat!(
+            "Board A: |\n",
+            "    xxx\n",
+            "    xxx\n",
+            "Board B: |\n",
+            "    xx\n",
+            "    xx\n",
+            "    xx\n",
+            "L1: |\n",
+            "    xx\n",
+            "    x\n",
+            "L2: |\n",
+            "    xx\n",
+            "    x\n",
+            "L3: |\n",
+            "    xx\n",
+            "    x\n",
+            "L4: |\n",
+            "    xx\n",
+            "    x\n",
+        );
+        let mut game = Game::from_yaml(yaml).unwrap();
+        assert_eq!(game.len(), 12);
+        assert_eq!(game.boards(), vec![("Board A", 0..6), ("Board B", 6..12)]);
+
+        let mut count = 0;
+        while let Some(solution) = game.solve() {
+            // every piece lies within a single board
+            for tile in game.solution_tiles(solution) {
+                let left = tile.points.iter().filter(|p| p.x < 3).count();
+                assert!(left == 0 || left == tile.len());
+            }
+            count += 1;
+        }
+        // 6 ways to split the pieces, 2 tilings with 2 labelings per board
+        assert_eq!(count, 6 * 4 * 4);
+    }
+
+    #[test]
+    fn placement() {
+        let mut game = l_j_game();
+        let solution = game.solve().unwrap();
+
+        for r in &solution {
+            let info = game.placement(*r).unwrap();
+            let cells = game.row_cells(*r);
+
+            // the matrix row covers the same cells and the piece column
+            let mut row: Vec<usize> = cells.iter().map(|c| c + 1).collect();
+            row.push(game.len() + info.piece + 1);
+            assert_eq!(game.row(*r), row);
+
+            // the placement reproduces the covered cells
+            let tile = info.place(&game.tiles[info.piece]);
+            assert_eq!(game.placement_row(info), Some(*r));
+            let points: Vec<Point> = cells
+                .iter()
+                .map(|c| game.board.points[*c].clone())
+                .collect();
+            assert_eq!(tile.points.to_vec(), points);
+        }
+        assert_eq!(game.placement(0), None);
+        assert_eq!(game.row_cells(0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn tileable_rectangles() {
+        let tiles: Vec<Tile> = (0..4)
+            .map(|i| Tile::from_str(&format!("L{}", i), "xx\nx"))
+            .collect();
+        let game = Game::build(Tile::new("Board"), tiles, &Rules::default());
+
+        assert_eq!(game.tileable_rectangles(100), vec![(2, 6), (3, 4)]);
+        assert_eq!(game.tileable_rectangles(11), vec![]);
+    }
+
+    #[test]
+    fn warnings() {
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxx\n",
+            "    xxx\n",
+            "A: |\n",
+            "    xx\n",
+            "A: |\n",
+            "    x x\n",
+            "B: |\n",
+            "    ..\n",
+        );
+        let game = Game::from_yaml(yaml).unwrap();
+        assert_eq!(
+            game.warnings(),
+            [
+                Warning::DuplicatePiece {
+                    name: String::from("A")
+                },
+                Warning::UnusedKey {
+                    name: String::from("B")
+                },
+                Warning::DisconnectedTile {
+                    name: String::from("A")
+                },
+                Warning::AreaMismatch {
+                    board: 6,
+                    pieces: 4
+                },
+            ]
+        );
+
+        let game = Game::from_yaml("Board: |\n    xx\nA: |\n    xx\n").unwrap();
+        assert_eq!(game.warnings(), []);
+    }
+
+    #[test]
+    fn trim_board() {
+        let mut tile = Tile::from_str("L", "x\nxx");
+        tile.translate(&Point::new(3, 2));
+        let (cropped, offset) = tile.cropped();
+        assert_eq!(cropped, Tile::from_str("L", "x\nxx"));
+        assert_eq!(offset, Point::new(3, 2));
+
+        // cut the left column off the board, leaving a blank margin
+        let mut game = Game::from_yaml("Board: |\n    #xxx\n    #xxx\nI: |\n    xx\n").unwrap();
+        game.remove_cells(&[Point::new(0, 0), Point::new(0, 1)]);
+        game.set_tiles(vec![Tile::from_str("I", "xx"), Tile::from_str("J", "xx")]);
+        let before = game.solve().unwrap();
+        let placements = game.placements(&before);
+        assert_eq!(game.solution_grid(&[]).width, 4);
+
+        assert_eq!(game.trim_board(), Point::new(1, 0));
+        assert_eq!(game.board.offset(), Point::new(0, 0));
+        assert!(game.blocked.points.is_empty());
+        assert_eq!(game.solution_grid(&[]).width, 2);
+        // the same rows, moved
+        for (placement, moved) in placements.iter().zip(game.placements(&before)) {
+            assert_eq!(moved.anchor + Point::new(1, 0), placement.anchor);
+        }
+        assert_eq!(game.count_solutions(), 4);
+    }
+
+    #[test]
+    fn connectivity() {
+        let tile = Tile::from_str("X", "xx x\nx  x");
+        assert!(!tile.is_connected());
+        let components = tile.components();
+        assert_eq!(components.len(), 2);
+        assert_eq!(
+            components[0].points(),
+            Tile::from_str("X", "xx\nx").points()
+        );
+        assert!(components.iter().all(|c| c.is_connected()));
+
+        // a stray cell splits the board
+        let yaml = "Board: |\n    xxx\n    xxx\n\n     x\nI: |\n    xxx\nL: |\n    xx\n    xx\n";
+        let game = Game::from_yaml(yaml).unwrap();
+        assert!(game.warnings().contains(&Warning::DisconnectedBoard {
+            name: String::from("Board"),
+            regions: 2
+        }));
+        // separate boards are fine
+        let yaml = "Board A: |\n    xx\nBoard B: |\n    xx\nI: |\n    xx\nJ: |\n    xx\n";
+        assert!(Game::from_yaml(yaml).unwrap().warnings().is_empty());
+
+        let yaml = "Board: |\n    xxxx\nA: |\n    x x\nB: |\n    x\n    x\n";
+        assert_eq!(
+            Game::from_yaml_strict(yaml).err(),
+            Some(GameError::Rejected(Warning::DisconnectedTile {
+                name: String::from("A")
+            }))
+        );
+        assert!(Game::from_yaml_strict("Board: |\n    xx\nA: |\n    xx\n").is_ok());
+        assert_eq!(
+            Game::from_yaml_strict("Board: |\n    xx\n").err(),
+            Some(GameError::NoPieces)
+        );
+    }
+
+    #[test]
+    fn solve_anytime() {
+        let board = Tile::from_str("Board", "xxxxx\nxxxxx\nxxxxx\nxxxxx");
+        let tiles: Vec<Tile> = vec![
+            Tile::from_str("T1", "xxxx\n x  "),
+            Tile::from_str("T2", "xxxx\n x  "),
+            Tile::from_str("P1", "xxx\nxx "),
+            Tile::from_str("P2", "xxx\nxx "),
+        ];
+        let game = Game::build(board, tiles, &Rules::default());
+
+        match game.solve_anytime(2, None) {
+            Anytime::Partial(rows) => assert_eq!(rows.len(), 2),
+            other => panic!("{:?}", other),
+        }
+
+        let cancel = AtomicBool::new(true);
+        assert_eq!(
+            game.solve_anytime(usize::MAX, Some(&cancel)),
+            Anytime::Partial(vec![])
+        );
+
+        match game.solve_anytime(usize::MAX, None) {
+            Anytime::Solution(rows) => assert_eq!(rows.len(), 4),
+            other => panic!("{:?}", other),
+        }
+
+        let game = Game::build(
+            Tile::from_str("Board", "xxx"),
+            vec![Tile::from_str("A", "xx")],
+            &Rules::default(),
+        );
+        assert_eq!(game.solve_anytime(100, None), Anytime::Unsolvable);
+    }
+
+    #[test]
+    fn diff() {
+        let a = Game::from_yaml(
+            "Board: |\n    xxx\n    xxx\nA: |\n    xx\n    x\nB: |\n    xx\n    x\n",
+        )
+        .unwrap();
+        let b =
+            Game::from_yaml("Board: |\n    xxx\n    xxx\nA: |\n    xxx\nB: |\n    xxx\n").unwrap();
+
+        let diff = a.diff(&b);
+        assert!(diff.added_cells.is_empty());
+        assert_eq!(diff.changed_pieces, vec!["A", "B"]);
+        assert!(a.diff(&a).is_empty());
+
+        assert_eq!(a.count_solutions(), 4);
+        assert_eq!(b.count_solutions(), 2);
+    }
+
+    #[test]
+    fn cell_coordinates() {
+        // the blocked cell widens the grid to the left, the second board
+        // sits right of the first
+        let yaml = concat!(
+            "Board: |\n",
+            "    .xx\n",
+            "    #xx\n",
+            "Board 2: |\n",
+            "    x\n",
+            "    x\n",
+            "O: |\n",
+            "    xx\n",
+            "    xx\n",
+            "I: |\n",
+            "    x\n",
+            "    x\n",
+        );
+        let mut game = Game::from_yaml(yaml).unwrap();
+        assert_eq!(game.cell_index(0, 0), None);
+        assert_eq!(game.cell_index(0, 1), None);
+        assert_eq!(game.cell_index(1, 0), Some(0));
+        assert_eq!(game.cell_coords(0), (1, 0));
+        assert_eq!(game.cell_index(9, 9), None);
+
+        // consistent with the solution grid
+        let solution = game.solve().unwrap();
+        let grid = game.solution_grid(&solution);
+        let mut owners = vec![None; game.len()];
+        for r in &solution {
+            for c in game.row_cells(*r) {
+                owners[c] = game.placement(*r).map(|p| p.piece);
+            }
+        }
+        for (index, owner) in owners.iter().enumerate() {
+            let (x, y) = game.cell_coords(index);
+            assert_eq!(game.cell_index(x, y), Some(index));
+            assert_eq!(grid.get(x, y), GridCell::Piece(owner.unwrap()));
+        }
+    }
+
+    #[test]
+    fn duplicate_tiles() {
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxxx\n",
+            "    xxxx\n",
+            "    xxxx\n",
+            "L: |\n",
+            "    x\n",
+            "    xx\n",
+            "I: |\n",
+            "    xx\n",
+            "J: |\n",
+            "    xx\n",
+            "     x\n",
+            "O: |\n",
+            "    xx\n",
+            "    xx\n",
+            "V: |\n",
+            "    x\n",
+            "    x\n",
+            "S: |\n",
+            "    x\n",
+            "    xx\n",
+            "S colors: |\n",
+            "    a\n",
+            "    ..\n",
+        );
+        let game = Game::from_yaml(yaml).unwrap();
+        // the colored tromino is not interchangeable with the others
+        assert_eq!(game.duplicate_tiles(), vec![vec![0, 2], vec![1, 4]]);
+
+        let game = Game::build(
+            Tile::rect(2, 1),
+            vec![Tile::from_str("A", "xx")],
+            &Rules::default(),
+        );
+        assert!(game.duplicate_tiles().is_empty());
+    }
+
+    #[test]
+    fn pentomino_letters() {
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxxxx\n",
+            "    xxxxx\n",
+            "I:\n",
+            "F:\n",
+            "Q:\n",
+            "Z: |\n",
+            "    xx\n",
+        );
+        let game = Game::from_yaml(yaml).unwrap();
+        assert_eq!(game.piece_names(), vec!["I", "F", "Z"]);
+        assert_eq!(game.tiles[1], Tile::pentomino("F").unwrap());
+        // a drawing overrides the letter
+        assert_eq!(game.tiles[2].len(), 2);
+        assert_eq!(
+            game.warnings()[0],
+            Warning::UnusedKey {
+                name: String::from("Q")
+            }
+        );
+
+        let mut game = Game::from_yaml("Board: |\n    xxxxx\nI:\n").unwrap();
+        assert!(game.solve().is_some());
+        assert!(Tile::pentomino("Q").is_none());
+        assert_eq!(Tile::pentomino("X").unwrap().symmetries().order(), 8);
+    }
+
+    #[test]
+    fn labeled_cells() {
+        // the labels go along with the squares of the placed pieces
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxx\n",
+            "    xxx\n",
+            "L: |\n",
+            "    ab\n",
+            "    x\n",
+            "J: |\n",
+            "    .x\n",
+            "    cx\n",
+        );
+        let mut game = Game::from_yaml(yaml).unwrap();
+        assert!(game.warnings().is_empty());
+        assert_eq!(game.tiles[0].len(), 3);
+        assert_eq!(game.tiles[1].len(), 3);
+
+        let mut count = 0;
+        while let Some(solution) = game.solve() {
+            let labels = game.cell_labels(&solution);
+            let mut found: Vec<char> = labels.iter().flatten().copied().collect();
+            found.sort();
+            assert_eq!(found, vec!['a', 'b', 'c']);
+
+            // 'a' is the corner of the L, next to 'b'
+            let a = labels.iter().position(|l| *l == Some('a')).unwrap();
+            let b = labels.iter().position(|l| *l == Some('b')).unwrap();
+            let (pa, pb) = (&game.board.points[a], &game.board.points[b]);
+            assert_eq!((pa.x - pb.x).abs() + (pa.y - pb.y).abs(), 1);
+            count += 1;
+        }
+        assert!(count > 0);
+
+        // labels are colors, so colored board cells constrain them
+        let colored = yaml.replace("L: |", "Colors: |\n    c..\n    ...\nL: |");
+        let mut game = Game::from_yaml(&colored).unwrap();
+        let mut constrained = 0;
+        while let Some(solution) = game.solve() {
+            let label = game.cell_labels(&solution)[0];
+            assert!(label.is_none() || label == Some('c'));
+            constrained += 1;
+        }
+        assert!(0 < constrained && constrained < count);
+        assert_eq!(game.placement_labels(usize::MAX), vec![]);
+    }
+
+    #[test]
+    fn torus() {
+        // dominoes on a ring of four cells may also cover the last and first
+        let yaml = "Board: |\n    xxxx\nA: |\n    xx\nB: |\n    xx\n";
+        let mut game = Game::from_yaml(yaml).unwrap();
+        assert_eq!(game.topology(), Topology::Plane);
+        assert_eq!(game.count_solutions(), 2);
+        game.set_topology(Topology::Torus);
+        assert_eq!(game.count_solutions(), 4);
+        let mut game = Game::from_yaml(&format!("{}Topology: |\n    torus\n", yaml)).unwrap();
+        assert!(game.warnings().is_empty());
+        assert_eq!(game.count_solutions(), 4);
+        let mut wrapping = false;
+        while let Some(solution) = game.solve() {
+            wrapping |= game.solution_tiles(solution).iter().any(|t| {
+                t.points.contains(&Point::new(0, 0)) && t.points.contains(&Point::new(3, 0))
+            });
+        }
+        assert!(wrapping);
+
+        // the ring grows with the board
+        let mut game = Game::from_yaml("Board: |\n    xx\nA: |\n    xx\nB: |\n    xx\n").unwrap();
+        game.set_topology(Topology::Torus);
+        game.add_cells(&[Point::new(2, 0), Point::new(3, 0)]);
+        assert_eq!(game.count_solutions(), 4);
+
+        // a bar around the whole ring covers it once, not in every position
+        let mut game = Game::from_yaml("Board: |\n    xxx\nI: |\n    xxx\n").unwrap();
+        game.set_topology(Topology::Torus);
+        assert_eq!(game.rows.len(), 1);
+
+        // a piece too large for the box may still wrap onto it
+        let mut game =
+            Game::from_yaml("Board: |\n    xx\n    xx\nS: |\n    x\n    xx\n     x\n").unwrap();
+        assert_eq!(game.count_solutions(), 0);
+        game.set_topology(Topology::Torus);
+        assert_eq!(game.count_solutions(), 1);
+    }
+
+    #[test]
+    fn cylinder() {
+        // only rows wrap around, columns stay open at the top and bottom
+        let row = "Board: |\n    xxxx\nA: |\n    xx\nB: |\n    xx\n";
+        let column = "Board: |\n    x\n    x\n    x\n    x\nA: |\n    xx\nB: |\n    xx\n";
+        let game = Game::from_yaml(&format!("{}Topology: |\n    cylinder-x\n", row)).unwrap();
+        assert!(game.warnings().is_empty());
+        assert_eq!(game.topology(), Topology::CylinderX);
+        assert_eq!(game.count_solutions(), 4);
+        let mut game = Game::from_yaml(column).unwrap();
+        game.set_topology(Topology::CylinderX);
+        assert_eq!(game.count_solutions(), 2);
+        game.set_topology(Topology::Torus);
+        assert_eq!(game.count_solutions(), 4);
+        assert_eq!(Topology::CylinderX.wraps(), (true, false));
+    }
+
+    #[test]
+    fn piece_outlines() {
+        let mut game =
+            Game::from_yaml("Board: |\n    xxx\n    xxx\nA: |\n    xx\n    x\nB: |\n    x\n")
+                .unwrap();
+        game.remove_cells(&[Point::new(2, 1)]);
+        game.set_tiles(vec![
+            Tile::from_str("A", "xx\nx"),
+            Tile::from_str("B", "xx"),
+        ]);
+        let solution = game.solve().unwrap();
+        let outlines = game.piece_outlines(&solution);
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(outlines[0].0, 0);
+        // the segments close around each piece
+        for (_, segments) in &outlines {
+            for (i, (_, end)) in segments.iter().enumerate() {
+                assert_eq!(*end, segments[(i + 1) % segments.len()].0);
+            }
         }
+        let corners: Vec<Point> = outlines[0]
+            .1
+            .iter()
+            .map(|(start, _)| start.clone())
+            .collect();
+        assert_eq!(corners.len(), 6);
+        assert_eq!(outlines[1].1.len(), 4);
     }
-}
 
-// Point
-#[derive(PartialEq, Eq, Hash, Debug, Clone, PartialOrd, Ord)]
-struct Point {
-    x: isize,
-    y: isize,
-}
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn full_yaml() {
+        // an anchor and a quoted key the puzzle format does not know
+        let yaml = "\"Board\": |\n  xxx\n  xxx\nA: &l |\n  xx\n  x\nB: *l\n";
+        let mut game = Game::try_from_yaml(yaml).unwrap();
+        assert_eq!(game.piece_names(), ["A", "B"]);
+        assert!(game.solve().is_some());
+    }
+
+    #[test]
+    fn json() {
+        let json = r#"{ "board": ["xxx", "xxx"], "tiles": { "A": ["xx", "x"], "B": "xx\n x" } }"#;
+        let mut game = Game::from_json(json).unwrap();
+        assert!(game.warnings().is_empty());
+        assert_eq!(game.piece_names(), ["A", "B"]);
+        let yaml = Game::from_yaml(
+            "Board: |\n    xxx\n    xxx\nA: |\n    xx\n    x\nB: |\n    xx\n     x\n",
+        )
+        .unwrap();
+        assert_eq!(game.count_solutions(), yaml.count_solutions());
+        assert!(game.solve().is_some());
+
+        let json =
+            r#"{ "board": ["xxxx"], "tiles": { "A": ["xx"], "B": ["xx"] }, "topology": "torus" }"#;
+        assert_eq!(Game::from_json(json).unwrap().count_solutions(), 4);
+
+        let read = |json: &str| Game::from_json(json).err();
+        assert_eq!(read(r#"{ "tiles": {} }"#), Some(GameError::MissingBoard));
+        assert_eq!(read(r#"{ "board": ["x"] }"#), Some(GameError::NoPieces));
+        assert_eq!(
+            read(r#"{ "board": ["x"], "tiles": { "A": [1] } }"#),
+            Some(GameError::InvalidValue {
+                key: "A".to_string()
+            })
+        );
+        assert!(matches!(read(r#"{ "board": "#), Some(GameError::Parse(_))));
+        assert_eq!(
+            read(r#"{ "board": ["x"], "tiles": { "A": "x" }, "topology": "klein" }"#),
+            Some(GameError::InvalidValue {
+                key: "topology".to_string()
+            })
+        );
 
-impl Point {
-    pub fn new(x: isize, y: isize) -> Self {
-        Point { x: x, y: y }
+        // names are names, whatever they would mean as puzzle keys
+        let json = r#"{ "board": ["xxx"], "tiles": { "Board 2": ["xx"], "Topology": "x" } }"#;
+        let game = Game::from_json(json).unwrap();
+        assert_eq!(game.len(), 3);
+        assert_eq!(game.piece_names(), ["Board 2", "Topology"]);
+        assert_eq!(game.topology(), Topology::Plane);
+
+        // an empty pentomino letter is the standard shape, the next piece stays
+        let json = r#"{ "board": ["xxxxx", "xxxxx"], "tiles": { "F": [], "I": ["xxxxx"] } }"#;
+        let game = Game::from_json(json).unwrap();
+        assert!(game.warnings().is_empty());
+        assert_eq!(game.piece_names(), ["F", "I"]);
     }
 
-    pub fn from(x: &usize, y: &usize) -> Self {
-        Point {
-            x: isize::try_from(*x)
-                .ok()
-                .expect("Could not convert to isize"),
-            y: isize::try_from(*y)
-                .ok()
-                .expect("Could not convert to isize"),
-        }
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml() {
+        let toml = concat!(
+            "Board = \"\"\"\n",
+            "xxx\n",
+            "xxx\n",
+            "\"\"\"\n",
+            "A = [\"xx\", \"x\"]\n",
+            "B = \"\"\"\n",
+            "xx\n",
+            " x\n",
+            "\"\"\"\n",
+        );
+        let game = Game::from_toml(toml).unwrap();
+        assert!(game.warnings().is_empty());
+        assert_eq!(game.piece_names(), ["A", "B"]);
+        assert_eq!(game.count_solutions(), 4);
+
+        let error = Game::from_toml("Board = \"xx\"\nA = \n").err();
+        assert!(matches!(error, Some(GameError::Parse(e)) if e.line() == 2));
+        assert_eq!(
+            Game::from_toml("Board = \"xx\"\nA = 1\n").err(),
+            Some(GameError::InvalidValue {
+                key: "A".to_string()
+            })
+        );
     }
-}
 
-impl ops::Neg for Point {
-    type Output = Point;
+    #[test]
+    fn characters() {
+        let yaml = concat!(
+            "Characters: |\n",
+            "    filled #\n",
+            "    blank .\n",
+            "Board: |\n",
+            "    ###\n",
+            "    ##.\n",
+            "Colors: |\n",
+            "    a..\n",
+            "A: |\n",
+            "    ##\n",
+            "    #\n",
+            "B: |\n",
+            "    ##\n",
+            "B colors: |\n",
+            "    a.\n",
+        );
+        let game = Game::from_yaml(yaml).unwrap();
+        assert!(game.warnings().is_empty());
+        assert_eq!(game.board.len(), 5);
+        assert_eq!(game.piece_names(), ["A", "B"]);
+        // the same puzzle drawn with 'x'
+        let drawn = concat!(
+            "Board: |\n",
+            "    xxx\n",
+            "    xx\n",
+            "Colors: |\n",
+            "    a..\n",
+            "A: |\n",
+            "    xx\n",
+            "    x\n",
+            "B: |\n",
+            "    xx\n",
+            "B colors: |\n",
+            "    a.\n",
+        );
+        let count = game.count_solutions();
+        assert_eq!(count, Game::from_yaml(drawn).unwrap().count_solutions());
+        assert!(count > 0);
 
-    fn neg(self) -> Point {
-        Point {
-            x: -self.x.clone(),
-            y: -self.y.clone(),
-        }
+        let charset = Charset::new("*", "");
+        assert_eq!(
+            Tile::from_str_with("L", "*\n**", &charset),
+            Tile::from_str("L", "x\nxx")
+        );
+        assert_eq!(
+            Game::try_from_yaml("Characters: |\n    full #\nBoard: |\n    ##\nA: |\n    ##\n")
+                .err(),
+            Some(GameError::InvalidValue {
+                key: "Characters".to_string()
+            })
+        );
     }
-}
 
-impl ops::Add for Point {
-    type Output = Point;
+    #[test]
+    fn crlf() {
+        assert_eq!(
+            Tile::from_str("L", "x \r\nxx\r\n"),
+            Tile::from_str("L", "x \nxx\n")
+        );
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxx\n",
+            "\n",
+            "    xxx\n",
+            "Colors: |\n",
+            "    a..\n",
+            "A: |\n",
+            "    xx\n",
+            "    x\n",
+            "B: |\n",
+            "    xxx\n",
+        );
+        // the empty line splits the board in the same place
+        let game = Game::from_yaml(&yaml.replace('\n', "\r\n")).unwrap();
+        let lf = Game::from_yaml(yaml).unwrap();
+        assert_eq!(game.warnings(), lf.warnings());
+        assert_eq!(game.board.len(), 6);
+        assert_eq!(game.count_solutions(), lf.count_solutions());
+    }
 
-    fn add(self, other: Point) -> Point {
-        Point {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
+    #[test]
+    fn tabs() {
+        let spaces = "Board: |\n    xxx\n     xx\nA: |\n    xx\n    x\nB: |\n    xx\n";
+        let tabs = "Board: |\n\txxx\n\t xx\nA: |\n\txx\n\tx\nB: |\n\txx\n";
+        let game = Game::from_yaml(tabs).unwrap();
+        assert_eq!(game.board, Game::from_yaml(spaces).unwrap().board);
+        assert_eq!(game.count_solutions(), 2);
+        let wide = Game::try_from_yaml_with_tab_width(tabs, 8).unwrap();
+        assert_eq!(wide.board, game.board);
     }
-}
 
-impl ops::Sub for Point {
-    type Output = Point;
+    #[test]
+    fn inline_values() {
+        let inline = Game::from_yaml("Board: |\n    xxx\n    xxx\nA: xxx/x\nB: x,x\n").unwrap();
+        let blocks =
+            Game::from_yaml("Board: xxx/xxx\nA: |\n    xxx\n    x\nB: |\n    x\n    x\n").unwrap();
+        assert!(inline.warnings().is_empty());
+        assert_eq!(inline.board, blocks.board);
+        assert_eq!(inline.count_solutions(), blocks.count_solutions());
+        assert!(inline.count_solutions() > 0);
+    }
 
-    fn sub(self, other: Point) -> Point {
-        Point {
-            x: self.x - other.x,
-            y: self.y - other.y,
-        }
+    #[test]
+    fn read_errors() {
+        let read = |yaml: &str| Game::try_from_yaml(yaml).err();
+        assert_eq!(read("A: |\n    xx\n"), Some(GameError::MissingBoard));
+        assert_eq!(read("Board: |\n    xx\n"), Some(GameError::NoPieces));
+        assert_eq!(
+            read("Board: |\n    ..\nA: |\n    xx\n"),
+            Some(GameError::EmptyBoard)
+        );
+        assert!(matches!(
+            read("Board: |\n    xx\nA: [xx\n"),
+            Some(GameError::Parse(_))
+        ));
+        assert!(read("Board: |\n    xx\nA: |\n    xx\n").is_none());
+        assert!(matches!(
+            read("Board: |\n    xx\n# note\n    xx\nA: |\n    xx\n"),
+            Some(GameError::Parse(_))
+        ));
+        assert_eq!(
+            read("Board: |\n    xx\nA: |\n    xx\nOrientations: |\n    x9\n"),
+            Some(GameError::InvalidValue {
+                key: "Orientations".to_string()
+            })
+        );
+        assert_eq!(
+            read("Board: |\n    xx\nA: |\n    xx\nTopology: |\n    klein\n"),
+            Some(GameError::InvalidValue {
+                key: "Topology".to_string()
+            })
+        );
     }
-}
 
-impl ops::AddAssign for Point {
-    fn add_assign(&mut self, other: Point) {
-        *self = Point {
-            x: self.x + other.x,
-            y: self.y + other.y,
+    #[test]
+    fn board_symmetries() {
+        let count = |width, height, topology| {
+            let mut game = Game::new_rect(width, height, vec![]);
+            game.set_topology(topology);
+            game.board_symmetries().len()
         };
+        assert_eq!(count(3, 2, Topology::Plane), 4);
+        assert_eq!(count(3, 3, Topology::Plane), 8);
+        // every shift around a torus, but quarter turns only on a square one
+        assert_eq!(count(3, 2, Topology::Torus), 24);
+        assert_eq!(count(3, 3, Topology::Torus), 72);
+        assert_eq!(count(3, 2, Topology::CylinderX), 12);
+        assert_eq!(count(16, 16, Topology::Torus), 16 * 16 * 8);
+
+        let game = Game::from_yaml("Board: |\n    xxx\n    xx\nA: |\n    x\n").unwrap();
+        assert_eq!(game.board_symmetries(), [(Transform(0), Point::new(0, 0))]);
+        // sliding along a Möbius strip turns the board over as it crosses
+        let mut game = Game::new_rect(3, 2, vec![]);
+        game.set_topology(Topology::Mobius);
+        let symmetries = game.board_symmetries();
+        assert_eq!(symmetries[0], (Transform(0), Point::new(0, 0)));
+        assert!(symmetries.contains(&(Transform(0), Point::new(1, 0))));
     }
-}
 
-#[derive(Eq, Hash, PartialEq, Clone, Ord, PartialOrd, Debug)]
-struct Tile {
-    name: String,
-    points: Vec<Point>,
-}
+    #[test]
+    fn weights() {
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxx\n",
+            "    xx\n",
+            "Weights: |\n",
+            "    9.2\n",
+            "    0\n",
+            "A: |\n",
+            "    xx\n",
+            "B: |\n",
+            "    xxx\n",
+        );
+        let mut game = Game::from_yaml(yaml).unwrap();
+        assert!(game.warnings().is_empty());
+        assert_eq!(game.weights(), [9, 1, 2, 0, 1]);
+        game.set_weights(&[(Point::new(1, 1), 5)]);
+        assert_eq!(game.weights(), [9, 1, 2, 0, 5]);
+        let solution = game.solve().unwrap();
+        assert_eq!(game.solution_weight(&solution), 17);
+        let placed: Vec<usize> = solution.iter().map(|r| game.row_cells(*r).len()).collect();
+        let domino = solution[placed.iter().position(|n| *n == 2).unwrap()];
+        // the domino only fits below the bar
+        assert_eq!(game.solution_weight(&[domino]), 5);
 
-impl Tile {
-    pub fn new(name: &str) -> Self {
-        Self {
-            name: name.to_string(),
-            points: Vec::new(),
-        }
+        // the weights move with the board
+        game.trim_board();
+        assert_eq!(game.weights(), [9, 1, 2, 0, 5]);
     }
 
-    pub fn from_str(name: &str, contents: &str) -> Self {
-        let mut row: usize = 0;
-        let mut col: usize = 0;
+    #[test]
+    fn mobius() {
+        // a domino leaving the right of the top row comes back on the left of
+        // the bottom one
+        let square = Size::new(2, 2);
+        let origin = Point::new(0, 0);
+        let wrap = |x, y| Topology::Mobius.wrap(&Point::new(x, y), &origin, &square);
+        assert_eq!(wrap(2, 0), Point::new(0, 1));
+        assert_eq!(wrap(-1, 1), Point::new(1, 0));
+        assert_eq!(wrap(4, 1), Point::new(0, 1));
 
-        let mut points = Vec::new();
+        let yaml = "Board: |\n    xx\n    xx\nA: |\n    xx\nB: |\n    xx\n";
+        let mut game = Game::from_yaml(&format!("{}Topology: |\n    mobius\n", yaml)).unwrap();
+        assert!(game.warnings().is_empty());
+        assert_eq!(game.topology(), Topology::Mobius);
+        // the diagonal pairs join the rows and columns
+        assert_eq!(game.count_solutions(), 6);
+        game.set_topology(Topology::CylinderX);
+        assert_eq!(game.count_solutions(), 4);
+    }
 
-        for c in contents.chars() {
-            match c {
-                'x' => {
-                    points.push(Point::from(&col, &row));
-                    col += 1;
-                }
-                '\n' => {
-                    row += 1;
-                    col = 0;
-                }
-                _ => col += 1,
-            }
+    #[test]
+    fn anchor_positions() {
+        // the corner of the L must go on the marked cells
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxx\n",
+            "    xxx\n",
+            "L: |\n",
+            "    x\n",
+            "    *x\n",
+            "L positions: |\n",
+            "    ...\n",
+            "    x..\n",
+            "J: |\n",
+            "    xx\n",
+            "    x\n",
+        );
+        let mut game = Game::from_yaml(yaml).unwrap();
+        assert!(game.warnings().is_empty());
+        let mut count = 0;
+        while let Some(solution) = game.solve() {
+            let tiles = game.solution_tiles(solution);
+            let l = tiles.iter().find(|t| t.name == "L").unwrap();
+            assert!(l.points.contains(&Point::new(0, 1)));
+            count += 1;
         }
+        assert!(count > 0);
 
-        let mut tile = Self {
-            name: name.to_string(),
-            points: points,
-        };
-        // move tile top-left to origo
-        tile.translate(&(-tile.offset()));
-        return tile;
-    }
+        // the same from code
+        let mut game =
+            Game::from_yaml(&yaml.replace("L positions: |\n    ...\n    x..\n", "")).unwrap();
+        assert!(count < game.count_solutions());
+        game.restrict_anchor(0, Point::new(0, 1), &[Point::new(0, 1)]);
+        assert_eq!(game.count_solutions(), count);
 
-    pub fn len(&self) -> usize {
-        self.points.len()
+        let yaml = "Board: |\n    xx\nA: |\n    xx\nB positions: |\n    x.\n";
+        assert_eq!(
+            Game::from_yaml(yaml).unwrap().warnings(),
+            [Warning::UnusedKey {
+                name: String::from("B positions")
+            }]
+        );
     }
 
-    // mirror x (along y-axis).
-    pub fn mirror(&mut self) {
-        for p in self.points.iter_mut() {
-            p.x = -p.x;
-        }
-    }
+    #[test]
+    fn colors() {
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxxx\n",
+            "Colors: |\n",
+            "    abba\n",
+            "A: |\n",
+            "    xx\n",
+            "A colors: |\n",
+            "    ab\n",
+            "B: |\n",
+            "    xx\n",
+            "B colors: |\n",
+            "    ab\n",
+        );
+        let mut game = Game::from_yaml(yaml).unwrap();
+        assert!(game.warnings().is_empty());
 
-    // rotate 90 degrees counter-clockwise.
-    pub fn rotate(&mut self) {
-        let mut v: isize;
-        for p in self.points.iter_mut() {
-            v = p.x;
-            p.x = p.y;
-            p.y = -v.clone();
+        // each piece is either on the left as "ab" or turned around on the right
+        let mut count = 0;
+        while let Some(solution) = game.solve() {
+            for tile in game.solution_tiles(solution) {
+                assert!(
+                    tile.points.contains(&Point::new(0, 0))
+                        || tile.points.contains(&Point::new(3, 0))
+                );
+            }
+            count += 1;
         }
-    }
+        assert_eq!(count, 2);
 
-    pub fn translate(&mut self, offset: &Point) {
-        for p in self.points.iter_mut() {
-            p.x += offset.x;
-            p.y += offset.y;
-        }
+        // a single mismatching square rules out all placements
+        let yaml = yaml.replace("B colors: |\n    ab", "B colors: |\n    aa");
+        let mut game = Game::from_yaml(&yaml).unwrap();
+        assert_eq!(game.solve(), None);
+
+        // colors for an unknown piece are reported
+        let yaml = concat!(
+            "Board: |\n",
+            "    xx\n",
+            "A: |\n",
+            "    xx\n",
+            "C colors: |\n",
+            "    ab\n"
+        );
+        let game = Game::from_yaml(yaml).unwrap();
+        assert_eq!(
+            game.warnings(),
+            &[Warning::UnusedKey {
+                name: String::from("C colors")
+            }]
+        );
     }
 
-    // top left offset
-    pub fn offset(&self) -> Point {
-        let mut x: isize = self.points[0].x;
-        let mut y: isize = self.points[0].y;
+    #[test]
+    fn solution_grid() {
+        let yaml = "Board: |\n    xx#x\n     xxx\nD: |\n    xx\nL: |\n    xxx\n      x\n";
+        let mut game = Game::from_yaml(yaml).unwrap();
+        let names = game.piece_names();
+        assert_eq!(game.solution_grid(&[]).to_text(&names), "..#.\n ...\n");
 
-        for p in self.points.iter() {
-            if p.x < x {
-                x = p.x;
-            }
-            if p.y < y {
-                y = p.y;
-            }
-        }
+        let solution = game.solve().unwrap();
+        let grid = game.solution_grid(&solution);
+        assert_eq!(grid.to_text(&game.piece_names()), "DD#L\n LLL\n");
+        assert_eq!(grid.to_tokens(), vec![0, 0, -2, 1, -1, 1, 1, 1]);
 
-        Point::new(x, y)
+        // partial solutions leave uncovered cells
+        let grid = game.solution_grid(&solution[..1]);
+        assert!(grid.cells.contains(&GridCell::Uncovered));
+        assert!(
+            !grid.cells.contains(&GridCell::Piece(0)) || !grid.cells.contains(&GridCell::Piece(1))
+        );
     }
 
-    pub fn index(&self, point: &Point) -> Option<usize> {
-        self.points.iter().position(|r| r == point)
+    #[test]
+    fn optional_cells() {
+        // the 'o' may stay uncovered, the '.' is outside the board
+        let yaml = "Board: |\n    xxo\n    .x.\nL: |\n    xx\n    x\n";
+        let mut game = Game::from_yaml(yaml).unwrap();
+        assert_eq!(game.len(), 4);
+        assert!(game.warnings().is_empty());
+        let solution = game.solve().unwrap();
+        assert_eq!(solution.len(), 2);
+        assert_eq!(game.placements(&solution).len(), 1);
+        let grid = game.solution_grid(&solution);
+        assert_eq!(grid.to_text(&game.piece_names()), "LL.\n L\n");
+        assert_eq!(game.solve(), None);
+
+        // or be covered
+        let yaml = "Board: |\n    xxo\n    .x.\nT: |\n    xxx\n     x\n";
+        let mut game = Game::from_yaml(yaml).unwrap();
+        let solution = game.solve().unwrap();
+        assert_eq!(game.placements(&solution).len(), 1);
+        assert_eq!(game.count_solutions(), 1);
+
+        // the cells to cover must be covered
+        let yaml = "Board: |\n    xxo\n    .x.\nI: |\n    xxx\n";
+        assert_eq!(Game::from_yaml(yaml).unwrap().solve(), None);
+        let yaml = "Board: |\n    xxo\n    .x.\nD: |\n    xx\n";
+        assert!(matches!(
+            Game::from_yaml(yaml).unwrap().unsolvable(),
+            Some(Unsolvable::AreaMismatch { .. })
+        ));
     }
 
-    pub fn size(&self) -> Size {
-        if self.points.len() == 0 {
-            return Size {
-                width: 0,
-                height: 0,
-            };
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn solve_parallel() {
+        let board = Tile::from_str("Board", "xxxxx\nxxxxx\nxxxxx\nxxxxx");
+        let tiles: Vec<Tile> = vec![
+            Tile::from_str("T1", "xxxx\n x  "),
+            Tile::from_str("T2", "xxxx\n x  "),
+            Tile::from_str("P1", "xxx\nxx "),
+            Tile::from_str("P2", "xxx\nxx "),
+        ];
+        let mut game = Game::build(board, tiles, &Rules::default());
+
+        let mut expected = Vec::new();
+        while let Some(solution) = game.solve() {
+            expected.push(solution);
         }
+        let ordered = expected.clone();
+        expected.sort();
+        assert_eq!(game.solve_parallel(), expected);
 
-        let mut xmin = self.points[0].x;
-        let mut xmax = xmin;
-        let mut ymin = self.points[0].y;
-        let mut ymax = ymin;
+        game.set_ordered_parallel(true);
+        assert_ne!(ordered, expected);
+        assert_eq!(game.solve_parallel(), ordered);
+    }
 
-        for p in self.points.iter() {
-            if p.x > xmax {
-                xmax = p.x;
-            }
-            if p.x < xmin {
-                xmin = p.x;
-            }
-            if p.y > ymax {
-                ymax = p.y;
-            }
-            if p.y < ymin {
-                ymin = p.y;
-            }
-        }
-        Size {
-            width: usize::try_from(xmax - xmin + 1).unwrap(),
-            height: usize::try_from(ymax - ymin + 1).unwrap(),
+    #[test]
+    fn set_tiles() {
+        let mut game = l_j_game();
+        assert!(game.solve().is_some());
+        assert_eq!(game.count_solutions(), 4);
+
+        // the matrix is rebuilt and the enumeration restarted
+        game.set_tiles(vec![
+            Tile::from_str("I1", "xxx"),
+            Tile::from_str("I2", "xxx"),
+        ]);
+        let mut count = 0;
+        while game.solve().is_some() {
+            count += 1;
         }
+        assert_eq!(count, 2);
+
+        game.set_board(Tile::from_str("Board", "xxx\nxxx\nxxx"));
+        assert_eq!(game.count_solutions(), 0);
+        assert_eq!(
+            game.warnings(),
+            &[Warning::AreaMismatch {
+                board: 9,
+                pieces: 6
+            }]
+        );
+        game.set_board(Tile::from_str("Board", "xxxxxx"));
+        assert!(game.warnings().is_empty());
+        assert_eq!(game.count_solutions(), 2);
     }
-}
 
-// build matrix structure from the existing board and tiles
-fn build_matrix(board: &Tile, tiles: &Vec<Tile>) -> Matrix {
-    let n_cols = board.len() + tiles.len();
-    let mut m = Matrix::new(n_cols);
+    // placements of a game, independent of the row order.
+    fn placements(game: &Game) -> Vec<(Placement, Vec<Point>)> {
+        let mut placements: Vec<_> = game
+            .rows
+            .iter()
+            .zip(&game.infos)
+            .map(|(row, info)| {
+                let (piece, cells) = row.split_last().unwrap();
+                assert_eq!(*piece as usize, game.len() + info.piece);
+                let mut cells: Vec<Point> = cells
+                    .iter()
+                    .map(|c| game.board.points[*c as usize].clone())
+                    .collect();
+                cells.sort();
+                (info.clone(), cells)
+            })
+            .collect();
+        placements.sort();
+        placements
+    }
 
-    let mut uniqs: HashSet<(Tile, usize)> = HashSet::new();
-    for (index, tile) in tiles.iter().enumerate() {
-        let mut t = tile.clone();
-        for o in 0..8 {
-            t.rotate();
-            if o == 4 {
-                t.mirror();
-            }
-            t.translate(&-t.offset());
-            t.points.sort();
+    #[test]
+    fn count_memoized() {
+        let board = Tile::from_str("Board", "xxxx\nxxxx");
+        let tiles: Vec<Tile> = (1..=4)
+            .map(|i| Tile::from_str(&format!("D{}", i), "xx"))
+            .collect();
+        let game = Game::build(board, tiles, &Rules::default());
 
-            uniqs.insert((t.clone(), index));
-        }
+        // five domino tilings, with the four dominoes in any order
+        assert_eq!(game.count_memoized(), 5 * 24);
+        assert_eq!(game.count_solutions(), 5 * 24);
     }
 
-    // hash set of board points for easy checking
-    let board_points: HashSet<Point> = HashSet::from_iter(board.points.iter().cloned());
-    let size = board.size();
+    #[test]
+    fn solve_into() {
+        let mut game = l_j_game();
 
-    // order tiles predictably
-    let mut uniqs = Vec::from_iter(uniqs.iter());
-    uniqs.sort();
+        let mut rows = 0;
+        assert_eq!(
+            game.solve_into(|solution| {
+                rows += solution.len();
+                ControlFlow::Continue(())
+            }),
+            4
+        );
+        assert_eq!(rows, 8);
 
-    for (tile, index) in uniqs.iter() {
-        let mut t = tile.clone();
-        for i in 0..isize::try_from(size.width).unwrap() {
-            for j in 0..isize::try_from(size.height).unwrap() {
-                t.translate(&(Point::new(i, j) - t.offset()));
-
-                let mut contains = true;
-                for point in &t.points {
-                    if !board_points.contains(&point) {
-                        contains = false;
-                        break;
-                    }
-                }
-                if !contains {
-                    continue;
-                }
+        // stop after the first solution, the enumeration resumes after it
+        let mut game = Game::build(game.board.clone(), game.tiles.clone(), &Rules::default());
+        assert_eq!(game.solve_into(|_| ControlFlow::Break(())), 1);
+        assert_eq!(game.solve_into(|_| ControlFlow::Continue(())), 3);
+    }
 
-                // build row
-                let mut row = Vec::with_capacity(t.points.len() + 1);
-                for point in &t.points {
-                    let p = board.index(&point).unwrap();
-                    row.push(p);
-                }
-                row.push(board.len() + index);
+    #[test]
+    fn save_matrix() {
+        let yaml = "Board: |\n    xxx\n    xxx\nL: |\n    xx\n    x\nJ: |\n    xx\n     x\n";
+        let game = Game::from_yaml(yaml).unwrap();
+        let bytes = game.save_matrix();
 
-                m.add_row(&row);
-            }
+        let loaded = Game::from_yaml_with_matrix(yaml, &bytes).unwrap();
+        assert_eq!(loaded.ids, game.ids);
+        assert_eq!(loaded.infos, game.infos);
+        assert_eq!(loaded.count_solutions(), 4);
+
+        let other = "Board: |\n    xxx\n    xxx\nL: |\n    xxx\nJ: |\n    xxx\n";
+        assert_eq!(
+            Game::from_yaml_with_matrix(other, &bytes).err(),
+            Some(GameError::Snapshot(SnapshotError::Mismatch))
+        );
+
+        // the same board and pieces under other rules have other rows
+        for rules in [
+            "Topology: |\n    torus\n",
+            "Orientations: |\n    00x\n    xxx\n",
+            "Colors: |\n    a..\n    ...\n",
+            "L positions: |\n    x..\n    ...\n",
+        ] {
+            let other = format!("{}{}", yaml, rules);
+            assert_eq!(
+                Game::from_yaml_with_matrix(&other, &bytes).err(),
+                Some(GameError::Snapshot(SnapshotError::Mismatch))
+            );
+            let game = Game::from_yaml(&other).unwrap();
+            let loaded = Game::from_yaml_with_matrix(&other, &game.save_matrix()).unwrap();
+            assert_eq!(loaded.count_solutions(), game.count_solutions());
         }
+        let optional = yaml.replacen("xxx", "oxxx", 1);
+        assert!(Game::from_yaml_with_matrix(&optional, &bytes).is_err());
     }
-    return m;
-}
-
-pub struct Game {
-    board: Tile,
-    tiles: Vec<Tile>,
-    solver: IterativeSolver,
-}
 
-impl Game {
-    pub fn from_yaml(yaml: &str) -> Self {
-        let contents = parse(yaml).unwrap();
-        let mut board: Tile = Tile::new("Board");
-        let mut tiles: Vec<Tile> = Vec::new();
+    #[test]
+    fn heuristic() {
+        let mut game = l_j_game();
 
-        for (name, part) in contents.iter() {
-            let tile = Tile::from_str(name, part);
-            match *name {
-                "Board" => {
-                    board = tile;
-                }
-                _ => {
-                    tiles.push(tile);
-                }
+        // weigh the piece columns, so pieces are placed before cells are filled
+        let mut weights = vec![1.0; game.len()];
+        weights.extend([10.0, 10.0]);
+        for heuristic in [Heuristic::First, Heuristic::Weighted(weights)] {
+            game.set_heuristic(heuristic);
+            let mut count = 0;
+            while game.solve().is_some() {
+                count += 1;
             }
-        }
-        let matrix = build_matrix(&board, &tiles);
-        Self {
-            board: board,
-            tiles: tiles,
-            solver: IterativeSolver::new(matrix),
+            assert_eq!(count, 4);
         }
     }
 
-    pub fn len(&self) -> usize {
-        self.board.points.len()
+    #[test]
+    fn dancing_cells() {
+        let mut game = l_j_game();
+        let mut expected = Vec::new();
+        while let Some(solution) = game.solve() {
+            expected.push(solution);
+        }
+
+        game.set_options(&SolveOptions {
+            algorithm: Algorithm::DancingCells,
+            ..SolveOptions::default()
+        });
+        let mut solutions = Vec::new();
+        while let Some(solution) = game.solve() {
+            solutions.push(solution);
+        }
+        expected.sort();
+        solutions.sort();
+        assert_eq!(solutions, expected);
+        assert_eq!(solutions.len(), 4);
     }
 
-    pub fn solve(&mut self) -> Option<Vec<usize>> {
-        self.solver.next()
+    #[test]
+    fn warm_start() {
+        let (board, l, j) = l_j();
+        let mut game = l_j_game();
+        let solution = game.solve().unwrap();
+        let placed = game.placements(&solution);
+
+        // J renamed and moved to the front: the old solution comes first
+        let k = Tile {
+            name: "K".to_string(),
+            points: j.points.clone(),
+        };
+        game.warm_start(board.clone(), vec![k.clone(), l.clone()], &solution);
+        let fresh = Game::build(board.clone(), vec![k.clone(), l.clone()], &Rules::default());
+        let mut placements = game.infos.clone();
+        let mut expected = fresh.infos.clone();
+        placements.sort();
+        expected.sort();
+        assert_eq!(placements, expected);
+        let first = game.solve().unwrap();
+        let mut moved: Vec<Placement> = game.placements(&first);
+        moved.iter_mut().for_each(|p| p.piece = 1 - p.piece);
+        moved.sort();
+        let mut placed = placed;
+        placed.sort();
+        assert_eq!(moved, placed);
+        assert_eq!(game.count_solutions(), 4);
+
+        // a wider board and a piece more
+        let wide = Tile::from_str("Board", "xxxx\nxxxx\nxx");
+        let tiles = vec![k, l, Tile::from_str("T", "xxx\n x")];
+        let solution = game.solve().unwrap();
+        game.warm_start(wide.clone(), tiles.clone(), &solution);
+        let fresh = Game::build(wide, tiles, &Rules::default());
+        let mut placements = game.infos.clone();
+        let mut expected = fresh.infos.clone();
+        placements.sort();
+        expected.sort();
+        assert!(!placements.is_empty());
+        assert_eq!(placements, expected);
+        assert_eq!(game.count_solutions(), fresh.count_solutions());
     }
 
-    pub fn row(&self, row: usize) -> Vec<usize> {
-        self.solver.row(row)
+    #[test]
+    fn warm_start_copies() {
+        let (board, l, j) = l_j();
+        let mut game = l_j_game();
+
+        // one copy of L to two, and back to one
+        for tiles in [vec![l.clone(), l.clone()], vec![j, l]] {
+            let solution = game.solve().unwrap();
+            game.warm_start(board.clone(), tiles.clone(), &solution);
+            let fresh = Game::build(board.clone(), tiles.clone(), &Rules::default());
+            let mut placements = game.infos.clone();
+            let mut expected = fresh.infos.clone();
+            placements.sort();
+            expected.sort();
+            assert_eq!(game.tiles, tiles);
+            assert_eq!(placements, expected);
+            assert_eq!(game.count_solutions(), fresh.count_solutions());
+        }
     }
 
-    pub fn solution_tiles(&self, solution: Vec<usize>) -> Vec<Tile> {
-        let mut tiles: Vec<Tile> = Vec::new();
+    #[test]
+    fn row_reduction() {
+        // on the 3x4 board many placements leave a cell no other piece fits
+        let yaml = "Board: |\n    xxxx\n    xxxx\n    xxxx\nL: |\n    xxx\n    x\nJ: |\n    xxx\n      x\nI: |\n    xxxx\n";
+        let mut game = Game::from_yaml(yaml).unwrap();
+        let rows = game.rows.len();
+        let count = game.count_solutions();
+        assert!(count > 0);
 
-        for r in solution {
-            let indices = self.solver.row(r);
+        game.set_row_reduction(true);
+        assert!(game.rows.len() < rows);
+        assert_eq!(game.count_solutions(), count);
+        // the kept rows decode to the same placements
+        let solution = game.solve().unwrap();
+        assert_eq!(game.placements(&solution).len(), 3);
+    }
 
-            let tile_idx = indices.last().unwrap() - 1 - self.len();
-            let name = &self.tiles[tile_idx].name;
-            let mut points: Vec<Point> = Vec::new();
-            for i in 0..(indices.len() - 1) {
-                points.push(self.board.points[indices[i] - 1].clone());
+    #[test]
+    fn row_reduction_copies() {
+        // 13 tilings of the 2x6 board, each one for every order of the dominoes
+        let board = Tile::from_str("Board", "xxxxxx\nxxxxxx");
+        let tiles: Vec<Tile> = (0..6)
+            .map(|i| Tile::from_str(&format!("D{i}"), "xx"))
+            .collect();
+        let mut game = Game::build(board, tiles, &Rules::default());
+        let solve_all = |game: &mut Game| {
+            game.reset_metrics();
+            let mut solutions = Vec::new();
+            while let Some(solution) = game.solve() {
+                solutions.push(solution);
             }
+            (solutions, game.metrics().covers)
+        };
+        let (mut all, covers) = solve_all(&mut game);
+        assert_eq!(all.len(), 13 * 720);
 
-            tiles.push(Tile {
-                name: name.to_string(),
-                points: points,
-            });
-        }
-        tiles
+        game.set_row_reduction(true);
+        let (mut collapsed, collapsed_covers) = solve_all(&mut game);
+        assert!(collapsed_covers * 100 < covers);
+        all.sort();
+        collapsed.sort();
+        assert_eq!(collapsed, all);
+        assert_eq!(game.count_solutions(), all.len());
     }
-}
 
-#[cfg(test)]
-mod test {
-    use algox::algox::IterativeSolver;
+    #[test]
+    fn metrics() {
+        let mut game = l_j_game();
+        let built = game.metrics();
+        // four for each shape, the mirror images are drawn apart
+        assert_eq!(built.orientations, 8);
+        assert_eq!(built.rows_built, game.rows.len());
+        assert_eq!(built.covers, 0);
+        assert!(built.peak_bytes > 0);
 
-    use super::Game;
-    use super::Point;
-    use super::Size;
-    use super::Tile;
-    use super::build_matrix;
+        while game.solve().is_some() {}
+        let solved = game.metrics();
+        assert!(solved.covers > 0);
+        assert_eq!(solved.covers, solved.uncovers);
 
-    #[test]
-    fn point() {
-        let point1 = Point::new(1, 2);
-        let point2 = Point::new(3, 4);
+        // pruning restarts with the in-crate search, the counts add up
+        game.set_dead_region_pruning(true);
+        while game.solve().is_some() {}
+        assert!(game.metrics().covers > solved.covers);
 
-        assert_eq!(-point1.clone(), Point::new(-1, -2));
-        assert_eq!(point1 + point2, Point::new(4, 6));
+        game.reset_metrics();
+        assert_eq!(game.metrics().covers, 0);
+        assert_eq!(game.metrics().rows_built, 0);
     }
 
     #[test]
-    fn board() {
-        // New
-        let board = Tile::new("Board");
-        assert_eq!(board.size(), Size::new(0, 0));
+    fn tightness() {
+        let mut game = l_j_game();
 
-        // From string
-        let contents = "xxx-xx\nxxxx-";
-        let board = Tile::from_str("Board", &contents);
+        let tightness = game.tightness();
+        assert_eq!(tightness.len(), game.n_cols());
+        assert!(tightness.windows(2).all(|w| w[0].1 <= w[1].1));
+        // every placement covers one piece column and three cells
+        let cells: usize = tightness
+            .iter()
+            .filter(|(c, _)| *c < 6)
+            .map(|(_, n)| n)
+            .sum();
+        let pieces: usize = tightness
+            .iter()
+            .filter(|(c, _)| *c >= 6)
+            .map(|(_, n)| n)
+            .sum();
+        assert_eq!(cells, 3 * pieces);
 
-        assert!(board.points.contains(&Point::new(0, 0)));
-        assert!(board.points.contains(&Point::new(3, 1)));
-        assert!(!board.points.contains(&Point::new(3, 0)))
+        game.order_by_tightness();
+        let mut count = 0;
+        while game.solve().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 4);
     }
 
     #[test]
-    fn tile() {
-        // Empty
-        let mut tile = Tile::new("X");
-        tile.rotate();
-        tile.mirror();
-        tile.translate(&Point::new(1, 1));
+    fn incremental() {
+        let (board, l, j) = l_j();
+        let mut game = Game::build(board, vec![l.clone()], &Rules::default());
+
+        game.add_tile(j.clone());
+        let fresh = Game::build(
+            game.board.clone(),
+            vec![l.clone(), j.clone()],
+            &Rules::default(),
+        );
+        assert_eq!(placements(&game), placements(&fresh));
+        assert_eq!(game.count_solutions(), 4);
 
-        // From str
-        let contents = "xxx-xx\nxxxx-";
-        let mut tile = Tile::from_str("X", &contents);
+        assert_eq!(game.remove_tile(0).name, "L");
+        let fresh = Game::build(game.board.clone(), vec![j.clone()], &Rules::default());
+        assert_eq!(placements(&game), placements(&fresh));
+        game.add_tile(l.clone());
 
-        assert_eq!(tile.points[0], Point::new(0, 0));
-        assert_eq!(tile.points[1], Point::new(1, 0));
-        assert_eq!(tile.points[8], Point::new(3, 1));
+        // cut off the right column, then grow it back
+        let column = [Point::new(2, 0), Point::new(2, 1)];
+        game.remove_cells(&column);
+        assert_eq!(game.len(), 4);
+        assert_eq!(game.boards()[0].1, 0..4);
+        let fresh = Game::build(
+            game.board.clone(),
+            vec![j.clone(), l.clone()],
+            &Rules::default(),
+        );
+        assert_eq!(placements(&game), placements(&fresh));
 
-        tile.mirror();
-        assert_eq!(tile.points[0], Point::new(0, 0));
-        assert_eq!(tile.points[1], Point::new(-1, 0));
-        assert_eq!(tile.points[8], Point::new(-3, 1));
+        game.add_cells(&column);
+        assert_eq!(game.len(), 6);
+        let fresh = Game::build(
+            game.board.clone(),
+            vec![j.clone(), l.clone()],
+            &Rules::default(),
+        );
+        assert_eq!(placements(&game), placements(&fresh));
+        assert_eq!(game.count_solutions(), 4);
+        assert!(game.warnings().is_empty());
+    }
 
-        tile.rotate();
-        assert_eq!(tile.points[0], Point::new(0, 0));
-        assert_eq!(tile.points[1], Point::new(0, 1));
-        assert_eq!(tile.points[8], Point::new(1, 3));
+    #[test]
+    fn hints() {
+        let mut game = l_j_game();
 
-        tile.translate(&Point::new(1, 2));
-        assert_eq!(tile.points[0], Point::new(1, 2));
-        assert_eq!(tile.points[1], Point::new(1, 3));
-        assert_eq!(tile.points[8], Point::new(2, 5));
+        // each of the four solutions has two rows, used by one solution each
+        let hints = game.hints(&[], HintStyle::Safe);
+        assert_eq!(hints.len(), 8);
+        assert!(hints.iter().all(|h| h.solutions == 1));
 
-        let point = tile.offset();
-        assert_eq!(point, Point::new(1, 2));
+        // a placed row leaves only its partner
+        let solution = game.solve().unwrap();
+        let hints = game.hints(&solution[..1], HintStyle::Decisive);
+        assert_eq!(
+            hints,
+            vec![Hint {
+                row: solution[1],
+                solutions: 1
+            }]
+        );
 
-        tile.translate(&-point);
-        assert_eq!(tile.points[0], Point::new(0, 0));
-        assert_eq!(tile.points[1], Point::new(0, 1));
-        assert_eq!(tile.points[8], Point::new(1, 3));
+        // overlapping rows have no hints
+        assert!(
+            game.hints(&[solution[0], solution[0]], HintStyle::Safe)
+                .is_empty()
+        );
     }
 
     #[test]
-    fn solve1() {
-        // From string
-        let contents = "xx\nxx";
-        let board = Tile::from_str("Board", &contents);
+    fn parity() {
+        let board = Tile::from_str("Board", "xxxx\nxxxx\nxxxx\nxxxx");
+        let tiles: Vec<Tile> = vec![
+            Tile::from_str("T", "xxx\n x"),
+            Tile::from_str("I", "xxxx"),
+            Tile::from_str("O", "xx\nxx"),
+            Tile::from_str("L", "xxx\nx"),
+        ];
+        let mut game = Game::build(board, tiles, &Rules::default());
+        assert!(!game.is_parity_feasible());
+        assert_eq!(game.solve(), None);
 
-        let mut tiles: Vec<Tile> = Vec::new();
-        tiles.push(Tile::from_str("T1", "xx\nx"));
-        tiles.push(Tile::from_str("T2", "x"));
+        let game = Game::from_yaml(
+            "Board: |\n    xxx\n    xxx\nA: |\n    xx\n    x\nB: |\n    xx\n    x\n",
+        )
+        .unwrap();
+        assert!(game.is_parity_feasible());
+    }
+
+    #[test]
+    fn unsolvable() {
+        let board = Tile::from_str("Board", "xxx\nxxx");
+        let tiles: Vec<Tile> = vec![Tile::from_str("I", "xxxx"), Tile::from_str("D", "xx")];
+        let mut game = Game::build(board, tiles, &Rules::default());
+        assert_eq!(
+            game.unsolvable(),
+            Some(&Unsolvable::TooLarge {
+                name: "I".to_string()
+            })
+        );
+        assert!(game.rows.is_empty());
+        assert_eq!(game.solve(), None);
+
+        // the bar fits the bounding box, but not the board
+        let board = Tile::from_str("Board", "xx\nx\n\nxx");
+        let tiles: Vec<Tile> = vec![Tile::from_str("I", "xxx"), Tile::from_str("D", "xx")];
+        let game = Game::build(board, tiles, &Rules::default());
+        assert_eq!(
+            game.unsolvable(),
+            Some(&Unsolvable::UnplaceablePiece {
+                name: "I".to_string()
+            })
+        );
 
-        let matrix = build_matrix(&board, &tiles);
+        // the lone cell is out of reach of both pieces
+        let board = Tile::from_str("Board", "xx\nxx\n\nx");
+        let tiles: Vec<Tile> = vec![Tile::from_str("L", "xx\nx"), Tile::from_str("D", "xx")];
+        let game = Game::build(board, tiles.clone(), &Rules::default());
+        assert_eq!(
+            game.unsolvable(),
+            Some(&Unsolvable::UncoverableCell { cell: 4 })
+        );
 
-        let mut game = Game {
-            board: board,
-            tiles: tiles,
-            solver: IterativeSolver::new(matrix),
-        };
+        let board = Tile::from_str("Board", "xx\nxx\nx");
+        let game = Game::build(board, tiles, &Rules::default());
+        assert_eq!(game.unsolvable(), None);
+    }
 
+    #[test]
+    fn trace() {
+        let mut game = Game::from_yaml(
+            "Board: |\n    xxx\n    xxx\nA: |\n    xx\n    x\nB: |\n    xx\n    x\n",
+        )
+        .unwrap();
+        game.set_trace_capacity(2);
         let solution = game.solve().unwrap();
-        assert_eq!(solution.len(), 2);
+        let trace = game.trace_tail();
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace[1], TraceEvent::Solution { depth: 2 });
+        assert!(matches!(trace[0], TraceEvent::Enter { row, .. } if solution.contains(&row)));
+
+        // an exhausted search ends backtracking out of the root, in the
+        // search as in dancing links
+        for pruning in [false, true] {
+            game.set_dead_region_pruning(pruning);
+            game.set_trace_capacity(1);
+            while game.solve().is_some() {}
+            let trace = game.trace_tail();
+            assert!(matches!(trace[0], TraceEvent::Backtrack { depth: 1, .. }));
+        }
+        game.set_trace_capacity(0);
+        assert!(game.trace_tail().is_empty());
     }
 
     #[test]
-    fn solve2() {
-        // From string
-        let board = Tile::from_str("Board", "xxxxx\nxxxxx\nxxxxx\nxxxxx");
+    fn dead_region_pruning() {
+        // regions of 5 and 4 cells cannot be filled with straight trominoes
+        let board = Tile::from_str("Board", "xxxxx xxxx");
+        let tiles: Vec<Tile> = (0..3)
+            .map(|i| Tile::from_str(&format!("I{}", i), "xxx"))
+            .collect();
+        let mut game = Game::build(board, tiles, &Rules::default());
+        let entered = |game: &mut Game| {
+            game.set_trace_capacity(usize::MAX);
+            assert_eq!(game.solve(), None);
+            game.trace_tail()
+                .iter()
+                .filter(|e| matches!(e, TraceEvent::Enter { .. }))
+                .count()
+        };
+        assert!(entered(&mut game) > 0);
+
+        game.set_dead_region_pruning(true);
+        assert_eq!(entered(&mut game), 0);
 
+        // pruning keeps every solution
+        let board = Tile::from_str("Board", "xxxxx\nxxxxx\nxxxxx\nxxxxx");
         let tiles: Vec<Tile> = vec![
             Tile::from_str("T1", "xxxx\n x  "),
             Tile::from_str("T2", "xxxx\n x  "),
             Tile::from_str("P1", "xxx\nxx "),
             Tile::from_str("P2", "xxx\nxx "),
         ];
+        let mut game = Game::build(board, tiles, &Rules::default());
+        let count = game.count_solutions();
 
-        let matrix = build_matrix(&board, &tiles);
+        game.set_dead_region_pruning(true);
+        assert_eq!(game.count_solutions(), count);
 
-        let mut game = Game {
-            board: board,
-            tiles: tiles,
-            solver: IterativeSolver::new(matrix),
-        };
-        assert_eq!(game.len(), 20);
+        let mut solutions = 0;
+        while game.solve().is_some() {
+            solutions += 1;
+        }
+        assert_eq!(solutions, count);
+    }
 
-        let solution = game.solve().unwrap();
+    #[cfg(feature = "scripting")]
+    #[test]
+    fn script() {
+        let yaml = concat!(
+            "Board: |\n",
+            "    xxx\n",
+            "    xxx\n",
+            "    xxx\n",
+            "A: |\n",
+            "    xxx\n",
+            "B: |\n",
+            "    xxx\n",
+            "C: |\n",
+            "    xxx\n",
+            "Row rule: |\n",
+            "    piece != \"A\" || cells.all(|c| c[0] == 0)\n",
+            "Solution rule: |\n",
+            "    !touching(cells[\"A\"], cells[\"B\"])\n",
+        );
+        let mut game = Game::from_yaml(yaml).unwrap();
 
-        assert_eq!(solution, vec!(25, 457, 997, 1315));
+        // A down the left column, and C separating it from B
+        let mut count = 0;
+        while let Some(solution) = game.solve() {
+            for tile in game.solution_tiles(solution) {
+                match tile.name.as_str() {
+                    "A" => assert!(tile.points.iter().all(|p| p.x == 0)),
+                    "C" => assert!(tile.points.iter().all(|p| p.x == 1)),
+                    _ => {}
+                }
+            }
+            count += 1;
+        }
+        assert_eq!(count, 1);
     }
 
     #[test]
@@ -471,13 +4716,7 @@ mod test {
             Tile::from_str("P2", "xxx\nxx "),
         ];
 
-        let matrix = build_matrix(&board, &tiles);
-
-        let mut game = Game {
-            board: board,
-            tiles: tiles,
-            solver: IterativeSolver::new(matrix),
-        };
+        let mut game = Game::build(board, tiles, &Rules::default());
 
         let solution = game.solve().unwrap();
         let solution_tiles = game.solution_tiles(solution);
@@ -487,7 +4726,7 @@ mod test {
             vec![
                 Tile {
                     name: String::from("P1"),
-                    points: vec![
+                    points: smallvec![
                         Point { x: 0, y: 0 },
                         Point { x: 0, y: 1 },
                         Point { x: 0, y: 2 },
@@ -497,7 +4736,7 @@ mod test {
                 },
                 Tile {
                     name: String::from("P2"),
-                    points: vec![
+                    points: smallvec![
                         Point { x: 2, y: 0 },
                         Point { x: 2, y: 1 },
                         Point { x: 2, y: 2 },
@@ -507,7 +4746,7 @@ mod test {
                 },
                 Tile {
                     name: String::from("T1"),
-                    points: vec![
+                    points: smallvec![
                         Point { x: 0, y: 3 },
                         Point { x: 1, y: 2 },
                         Point { x: 1, y: 3 },
@@ -517,7 +4756,7 @@ mod test {
                 },
                 Tile {
                     name: String::from("T2"),
-                    points: vec![
+                    points: smallvec![
                         Point { x: 3, y: 2 },
                         Point { x: 4, y: 0 },
                         Point { x: 4, y: 1 },
@@ -0,0 +1,137 @@
+// Dead-region pruning for the search.
+//
+// After every placement the uncovered board cells split into connected
+// regions. A region whose size cannot be written as a sum of remaining piece
+// sizes can never be filled, so the branch is abandoned right away.
+use crate::search::Prune;
+use crate::shape::Point;
+use crate::shape::SIDES;
+use crate::shape::Tile;
+use crate::shape::Topology;
+use std::collections::HashMap;
+
+pub struct DeadRegions {
+    // 4-neighbors of each board cell, by cell index
+    neighbors: Vec<Vec<usize>>,
+    // size of each piece, by piece index
+    sizes: Vec<usize>,
+}
+
+impl DeadRegions {
+    // regions connect across the edges the topology wraps around.
+    pub fn new(board: &Tile, tiles: &[Tile], topology: Topology) -> Self {
+        let index: HashMap<&Point, usize> = board
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p, i))
+            .collect();
+
+        let neighbors = board
+            .points
+            .iter()
+            .map(|p| {
+                board
+                    .adjacent(p, &SIDES, topology, &|q| index.contains_key(q))
+                    .iter()
+                    .map(|q| index[q])
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            neighbors,
+            sizes: tiles.iter().map(|t| t.len()).collect(),
+        }
+    }
+
+    // sizes of the connected regions of uncovered cells.
+    fn regions(&self, covered: &[bool]) -> Vec<usize> {
+        let n = self.neighbors.len();
+        let mut seen: Vec<bool> = covered[..n].to_vec();
+        let mut regions = Vec::new();
+        let mut stack = Vec::new();
+
+        for start in 0..n {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            stack.push(start);
+            let mut size = 0;
+            while let Some(cell) = stack.pop() {
+                size += 1;
+                for next in &self.neighbors[cell] {
+                    if !seen[*next] {
+                        seen[*next] = true;
+                        stack.push(*next);
+                    }
+                }
+            }
+            regions.push(size);
+        }
+        regions
+    }
+}
+
+impl Prune for DeadRegions {
+    fn is_dead(&self, covered: &[bool]) -> bool {
+        let n = self.neighbors.len();
+        let regions = self.regions(covered);
+        let largest = match regions.iter().max() {
+            Some(largest) => *largest,
+            None => return false,
+        };
+
+        // region sizes reachable as sums of the remaining pieces
+        let mut reachable = vec![false; largest + 1];
+        reachable[0] = true;
+        for (piece, size) in self.sizes.iter().enumerate() {
+            if covered[n + piece] {
+                continue;
+            }
+            for total in (*size..=largest).rev() {
+                if reachable[total - size] {
+                    reachable[total] = true;
+                }
+            }
+        }
+
+        regions.iter().any(|r| !reachable[*r])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DeadRegions;
+    use crate::search::Prune;
+    use crate::shape::Tile;
+    use crate::shape::Topology;
+
+    #[test]
+    fn basics() {
+        // 1x5 strip with two dominoes and a monomino
+        let board = Tile::from_str("Board", "xxxxx");
+        let tiles = vec![
+            Tile::from_str("A", "xx"),
+            Tile::from_str("B", "xx"),
+            Tile::from_str("C", "x"),
+        ];
+        let pruner = DeadRegions::new(&board, &tiles, Topology::Plane);
+
+        let covered = [false; 8];
+        assert!(!pruner.is_dead(&covered));
+
+        // domino on cells 1 and 2 leaves regions of 1 and 2 cells: fine
+        let covered = [false, true, true, false, false, true, false, false];
+        assert!(!pruner.is_dead(&covered));
+
+        // monomino on cell 1 leaves a region of 1 cell that only dominoes remain for
+        let covered = [false, true, false, false, false, false, false, true];
+        assert!(pruner.is_dead(&covered));
+
+        // around a ring the cells either side of it stay joined
+        let pruner = DeadRegions::new(&board, &tiles, Topology::Torus);
+        assert!(!pruner.is_dead(&covered));
+    }
+}
@@ -0,0 +1,276 @@
+// Removing rows that cannot be part of any exact cover, before the search.
+//
+// A row is dropped when an earlier row covers the same columns (it would only
+// repeat solutions), or when it is dominated: choosing it conflicts with every
+// row of some other column, which could then never be covered. Dropping rows
+// can make others dominated, so the passes repeat until nothing changes.
+//
+// Copies of a piece, pieces with the same placements, are interchangeable:
+// every tiling comes back once for each way of swapping them. `Copies` keeps
+// the rows of the first copy only, its column needing one row per copy, and
+// expands each solution of that matrix back into the rows of every copy.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::dlx::Matrix;
+use crate::rows::Rows;
+
+// indices of the rows to keep, ascending.
+pub fn reduce(n_cols: usize, rows: &Rows) -> Vec<usize> {
+    let mut seen: HashSet<&[u32]> = HashSet::new();
+    let mut alive: Vec<bool> = rows.iter().map(|row| seen.insert(row)).collect();
+
+    let mut by_col: Vec<Vec<usize>> = vec![Vec::new(); n_cols];
+    for (r, row) in rows.iter().enumerate() {
+        for c in row {
+            by_col[*c as usize].push(r);
+        }
+    }
+
+    // rows conflicting with the row under test are stamped with its index + 1
+    let mut stamp = vec![0; rows.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for r in 0..rows.len() {
+            if !alive[r] {
+                continue;
+            }
+            let row = rows.get(r);
+            for c in row {
+                for other in &by_col[*c as usize] {
+                    stamp[*other] = r + 1;
+                }
+            }
+            let dominated = (0..n_cols).any(|c| {
+                !row.contains(&(c as u32))
+                    && by_col[c]
+                        .iter()
+                        .all(|other| !alive[*other] || stamp[*other] == r + 1)
+            });
+            if dominated {
+                alive[r] = false;
+                changed = true;
+            }
+        }
+    }
+    (0..rows.len()).filter(|r| alive[*r]).collect()
+}
+
+pub struct Copies {
+    // the matrix without the rows of the later copies
+    matrix: Matrix,
+    // solver id in `matrix` and index in the full rows of each kept row
+    ids: Vec<usize>,
+    rows: Vec<usize>,
+    // for each group of copies, the rows of every copy by the row of the
+    // first one with the same cells
+    groups: Vec<HashMap<usize, Vec<usize>>>,
+}
+
+impl Copies {
+    // rows cover board cells followed by one piece column, or just cells.
+    // None when no two pieces have the same placements.
+    pub fn new(n_cells: usize, n_pieces: usize, rows: &Rows) -> Option<Self> {
+        let mut placements: Vec<Vec<(&[u32], usize)>> = vec![Vec::new(); n_pieces];
+        for (r, row) in rows.iter().enumerate() {
+            if let Some((piece, cells)) = row.split_last().filter(|(c, _)| **c as usize >= n_cells)
+            {
+                placements[*piece as usize - n_cells].push((cells, r));
+            }
+        }
+        for placement in &mut placements {
+            placement.sort();
+        }
+
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for piece in 0..n_pieces {
+            let same = |other: &usize| {
+                let (a, b) = (&placements[*other], &placements[piece]);
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.0 == b.0)
+            };
+            match groups.iter_mut().find(|group| same(&group[0])) {
+                Some(group) => group.push(piece),
+                None => groups.push(vec![piece]),
+            }
+        }
+        groups.retain(|group| group.len() > 1 && !placements[group[0]].is_empty());
+        if groups.is_empty() {
+            return None;
+        }
+
+        let mut dropped = vec![false; rows.len()];
+        let mut matrix = Matrix::new(n_cells + n_pieces);
+        let groups: Vec<HashMap<usize, Vec<usize>>> = groups
+            .iter()
+            .map(|group| {
+                matrix.set_multiplicity(n_cells + group[0], group.len());
+                for piece in &group[1..] {
+                    matrix.set_multiplicity(n_cells + piece, 0);
+                    for (_, r) in &placements[*piece] {
+                        dropped[*r] = true;
+                    }
+                }
+                (0..placements[group[0]].len())
+                    .map(|i| {
+                        let copies = group.iter().map(|piece| placements[*piece][i].1);
+                        (placements[group[0]][i].1, copies.collect())
+                    })
+                    .collect()
+            })
+            .collect();
+        let kept: Vec<usize> = (0..rows.len()).filter(|r| !dropped[*r]).collect();
+        let ids = kept
+            .iter()
+            .map(|r| matrix.add_row(&rows.to_vec(*r)))
+            .collect();
+        Some(Self {
+            matrix,
+            ids,
+            rows: kept,
+            groups,
+        })
+    }
+
+    pub fn matrix(&self) -> &Matrix {
+        &self.matrix
+    }
+
+    // index in the full rows of the row with the solver id in `matrix`.
+    pub fn row(&self, id: usize) -> usize {
+        self.rows[self.ids.binary_search(&id).unwrap()]
+    }
+
+    // the solutions of the full rows behind a solution of `matrix`, given by
+    // index in the full rows: one for each way of assigning the copies.
+    pub fn expand(&self, solution: &[usize]) -> Expansion {
+        let mut fixed = solution.to_vec();
+        let choices: Vec<Vec<Vec<usize>>> = self
+            .groups
+            .iter()
+            .map(|group| {
+                fixed.retain(|r| !group.contains_key(r));
+                solution
+                    .iter()
+                    .filter_map(|r| group.get(r).cloned())
+                    .collect()
+            })
+            .collect();
+        Expansion {
+            fixed,
+            orders: choices
+                .iter()
+                .map(|slots| (0..slots.len()).collect())
+                .collect(),
+            choices,
+            done: false,
+        }
+    }
+
+    pub fn bytes(&self) -> usize {
+        let groups: usize = self
+            .groups
+            .iter()
+            .flat_map(|group| group.values())
+            .map(|copies| (copies.capacity() + 2) * std::mem::size_of::<usize>())
+            .sum();
+        self.matrix.bytes()
+            + (self.ids.capacity() + self.rows.capacity()) * std::mem::size_of::<usize>()
+            + groups
+    }
+}
+
+// The solutions behind one collapsed solution, the copies of each group
+// going through their permutations like the digits of an odometer.
+pub struct Expansion {
+    // rows of pieces without copies
+    fixed: Vec<usize>,
+    // per group and row of the first copy, the rows of every copy
+    choices: Vec<Vec<Vec<usize>>>,
+    // the copy taking each of those rows
+    orders: Vec<Vec<usize>>,
+    done: bool,
+}
+
+impl Iterator for Expansion {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        let mut solution = self.fixed.clone();
+        for (slots, order) in self.choices.iter().zip(&self.orders) {
+            solution.extend(slots.iter().zip(order).map(|(copies, copy)| copies[*copy]));
+        }
+        self.done = !self
+            .orders
+            .iter_mut()
+            .rev()
+            .any(|order| next_permutation(order));
+        Some(solution)
+    }
+}
+
+// step to the next permutation in lexicographic order, or back to the first
+// returning false.
+fn next_permutation(order: &mut [usize]) -> bool {
+    let Some(i) = (1..order.len()).rev().find(|i| order[i - 1] < order[*i]) else {
+        order.reverse();
+        return false;
+    };
+    let j = (i..order.len())
+        .rev()
+        .find(|j| order[*j] > order[i - 1])
+        .unwrap();
+    order.swap(i - 1, j);
+    order[i..].reverse();
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::Copies;
+    use super::reduce;
+    use crate::rows::Rows;
+
+    #[test]
+    fn reductions() {
+        let mut rows = Rows::new();
+        rows.push(&[0, 1]);
+        rows.push(&[2, 3]);
+        rows.push(&[0, 1]);
+        rows.push(&[0, 2]);
+        rows.push(&[1, 3]);
+        assert_eq!(reduce(4, &rows), vec![0, 1, 3, 4]);
+
+        // after [0, 1] only [1, 2] could cover column 2
+        let mut rows = Rows::new();
+        rows.push(&[0]);
+        rows.push(&[1, 2]);
+        rows.push(&[0, 1]);
+        assert_eq!(reduce(3, &rows), vec![0, 1]);
+    }
+
+    #[test]
+    fn copies() {
+        // three monominoes on three cells, the last one differs
+        let mut rows = Rows::new();
+        for piece in 3..6 {
+            for cell in 0..3 {
+                if piece < 5 || cell == 2 {
+                    rows.push(&[cell, piece]);
+                }
+            }
+        }
+        assert!(Copies::new(3, 2, &Rows::new()).is_none());
+        let copies = Copies::new(3, 3, &rows).unwrap();
+        assert_eq!(copies.rows, vec![0, 1, 2, 6]);
+
+        let mut solutions: Vec<Vec<usize>> = copies.expand(&[0, 1, 6]).collect();
+        assert_eq!(solutions, vec![vec![6, 0, 4], vec![6, 3, 1]]);
+        solutions = copies.expand(&[6]).collect();
+        assert_eq!(solutions, vec![vec![6]]);
+    }
+}
@@ -0,0 +1,251 @@
+// Solving boards that fall apart into separate regions, one region at a time.
+//
+// Every piece goes to one region, in all ways that fit the regions' areas,
+// and each region is tiled with its own pieces as a small matrix of its cells
+// and those pieces. The tilings of a region only depend on its set of pieces,
+// so they are found once per set and combined: counts multiply over the
+// regions and add up over the ways to hand out the pieces.
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::count;
+use crate::dancing_cells::DancingCells;
+use crate::exact_cover::ExactCover;
+use crate::rows::Rows;
+
+// Pieces handed out to the regions, by region.
+type Assignment = Vec<Vec<usize>>;
+
+pub struct Regions<'a> {
+    n_cells: usize,
+    rows: &'a Rows,
+    // cells of each region, and the index of every cell within its region
+    cells: Vec<Vec<usize>>,
+    local: Vec<usize>,
+    // how many cells of each region must be covered
+    must: Vec<usize>,
+    // area of each piece
+    sizes: Vec<usize>,
+    // rows of each region, and whether a piece has a row in a region
+    by_region: Vec<Vec<usize>>,
+    fits: Vec<Vec<bool>>,
+}
+
+impl<'a> Regions<'a> {
+    // the rows split over the regions (board cell indices), None when a row
+    // covers cells of several regions, as rows of disconnected pieces may.
+    // Piece columns follow the `n_cells` cell columns.
+    pub fn new(
+        n_cells: usize,
+        cells: Vec<Vec<usize>>,
+        must: Vec<usize>,
+        sizes: Vec<usize>,
+        rows: &'a Rows,
+    ) -> Option<Self> {
+        let mut region = vec![0; n_cells];
+        let mut local = vec![0; n_cells];
+        for (r, region_cells) in cells.iter().enumerate() {
+            for (i, c) in region_cells.iter().enumerate() {
+                region[*c] = r;
+                local[*c] = i;
+            }
+        }
+
+        let mut by_region = vec![Vec::new(); cells.len()];
+        let mut fits = vec![vec![false; cells.len()]; sizes.len()];
+        for (index, row) in rows.iter().enumerate() {
+            let (first, _) = row.split_first()?;
+            let r = region[*first as usize];
+            for c in row.iter().map(|c| *c as usize) {
+                match c < n_cells {
+                    true if region[c] != r => return None,
+                    true => {}
+                    false => fits[c - n_cells][r] = true,
+                }
+            }
+            by_region[r].push(index);
+        }
+        Some(Self {
+            n_cells,
+            rows,
+            cells,
+            local,
+            must,
+            sizes,
+            by_region,
+            fits,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // number of tilings of the whole board.
+    pub fn count(&self) -> u128 {
+        let mut known: HashMap<(usize, Vec<usize>), u128> = HashMap::new();
+        let mut total = 0;
+        let _ = self.assignments(&mut |assignment| {
+            let mut product = 1;
+            for (r, pieces) in assignment.iter().enumerate() {
+                let count = *known
+                    .entry((r, pieces.clone()))
+                    .or_insert_with(|| self.count_region(r, pieces));
+                product *= count;
+                if product == 0 {
+                    break;
+                }
+            }
+            total += product;
+            ControlFlow::Continue(())
+        });
+        total
+    }
+
+    // a tiling of the whole board, as row indices, ascending.
+    pub fn solve(&self) -> Option<Vec<usize>> {
+        let mut known: HashMap<(usize, Vec<usize>), Option<Vec<usize>>> = HashMap::new();
+        let mut found = None;
+        let _ = self.assignments(&mut |assignment| {
+            let mut solution = Vec::new();
+            for (r, pieces) in assignment.iter().enumerate() {
+                let tiling = known
+                    .entry((r, pieces.clone()))
+                    .or_insert_with(|| self.solve_region(r, pieces));
+                match tiling {
+                    Some(rows) => solution.extend(rows.iter().copied()),
+                    None => return ControlFlow::Continue(()),
+                }
+            }
+            solution.sort();
+            found = Some(solution);
+            ControlFlow::Break(())
+        });
+        found
+    }
+
+    // call `visit` with every way to hand out the pieces so that each region
+    // gets at least the area it must cover and no more than it has.
+    fn assignments(
+        &self,
+        visit: &mut dyn FnMut(&Assignment) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        let mut assignment = vec![Vec::new(); self.len()];
+        let mut area = vec![0; self.len()];
+        let remaining: usize = self.sizes.iter().sum();
+        self.assign(0, remaining, &mut area, &mut assignment, visit)
+    }
+
+    fn assign(
+        &self,
+        piece: usize,
+        remaining: usize,
+        area: &mut Vec<usize>,
+        assignment: &mut Assignment,
+        visit: &mut dyn FnMut(&Assignment) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        // the pieces left must make up for the regions short of their area
+        let short: usize = (0..self.len())
+            .map(|r| self.must[r].saturating_sub(area[r]))
+            .sum();
+        if short > remaining {
+            return ControlFlow::Continue(());
+        }
+        if piece == self.sizes.len() {
+            return visit(assignment);
+        }
+        let size = self.sizes[piece];
+        for r in 0..self.len() {
+            if !self.fits[piece][r] || area[r] + size > self.cells[r].len() {
+                continue;
+            }
+            area[r] += size;
+            assignment[r].push(piece);
+            let flow = self.assign(piece + 1, remaining - size, area, assignment, visit);
+            assignment[r].pop();
+            area[r] -= size;
+            flow?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    // the rows of a region with the given pieces, over the region's cells
+    // followed by the pieces, and the index of each in all rows.
+    fn matrix(&self, region: usize, pieces: &[usize]) -> (usize, Rows, Vec<usize>) {
+        let n_cells = self.cells[region].len();
+        let mut rows = Rows::new();
+        let mut indices = Vec::new();
+        for index in &self.by_region[region] {
+            let row: Option<Vec<usize>> = self
+                .rows
+                .get(*index)
+                .iter()
+                .map(|c| match (*c as usize).checked_sub(self.n_cells) {
+                    None => Some(self.local[*c as usize]),
+                    Some(piece) => pieces.iter().position(|p| *p == piece).map(|p| n_cells + p),
+                })
+                .collect();
+            if let Some(row) = row {
+                rows.push(&row);
+                indices.push(*index);
+            }
+        }
+        (n_cells + pieces.len(), rows, indices)
+    }
+
+    fn count_region(&self, region: usize, pieces: &[usize]) -> u128 {
+        let (n_cols, rows, _) = self.matrix(region, pieces);
+        count::count(n_cols, &rows)
+    }
+
+    fn solve_region(&self, region: usize, pieces: &[usize]) -> Option<Vec<usize>> {
+        let (n_cols, rows, indices) = self.matrix(region, pieces);
+        let mut cells = Box::new(DancingCells::new(n_cols));
+        for row in rows.to_vecs() {
+            cells.add_row(&row);
+        }
+        let solution = cells.solve_iter().next()?;
+        Some(solution.iter().map(|r| indices[*r]).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Regions;
+    use crate::rows::Rows;
+
+    #[test]
+    fn split() {
+        // cells 0, 1 and 2, 3 are two regions, piece 4 is a domino and 5, 6
+        // are monominoes
+        let mut rows = Rows::new();
+        rows.push(&[0, 1, 4]);
+        rows.push(&[2, 3, 4]);
+        for piece in [5, 6] {
+            for cell in 0..4 {
+                rows.push(&[cell, piece]);
+            }
+        }
+        let cells = vec![vec![0, 1], vec![2, 3]];
+        let regions = Regions::new(4, cells.clone(), vec![2, 2], vec![2, 1, 1], &rows).unwrap();
+        assert_eq!(regions.len(), 2);
+        // the domino on either side, the monominoes in either order
+        assert_eq!(regions.count(), 4);
+        let solution = regions.solve().unwrap();
+        let mut covered: Vec<u32> = solution
+            .iter()
+            .flat_map(|r| rows.get(*r).iter().copied())
+            .collect();
+        covered.sort();
+        assert_eq!(covered, [0, 1, 2, 3, 4, 5, 6]);
+
+        // a row across the regions
+        rows.push(&[1, 2, 4]);
+        assert!(Regions::new(4, cells, vec![2, 2], vec![2, 1, 1], &rows).is_none());
+    }
+}
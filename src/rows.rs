@@ -0,0 +1,107 @@
+// Matrix rows stored back to back in one buffer, with u32 column indices.
+//
+// Puzzles with big pieces have hundreds of thousands of rows, a vector per
+// row and usize columns would double the memory they take.
+
+use std::mem::size_of;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Rows {
+    cols: Vec<u32>,
+    // end of each row in `cols`
+    ends: Vec<u32>,
+}
+
+impl Rows {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, row: &[usize]) {
+        self.cols
+            .extend(row.iter().map(|c| u32::try_from(*c).unwrap()));
+        self.ends.push(u32::try_from(self.cols.len()).unwrap());
+    }
+
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cols.clear();
+        self.ends.clear();
+    }
+
+    fn start(&self, index: usize) -> usize {
+        match index {
+            0 => 0,
+            _ => self.ends[index - 1] as usize,
+        }
+    }
+
+    pub fn get(&self, index: usize) -> &[u32] {
+        &self.cols[self.start(index)..self.ends[index] as usize]
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut [u32] {
+        let start = self.start(index);
+        &mut self.cols[start..self.ends[index] as usize]
+    }
+
+    // columns of a row as indices.
+    pub fn to_vec(&self, index: usize) -> Vec<usize> {
+        self.get(index).iter().map(|c| *c as usize).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[u32]> {
+        (0..self.len()).map(|i| self.get(i))
+    }
+
+    // all rows as vectors of indices, for the solvers working on those.
+    pub fn to_vecs(&self) -> Vec<Vec<usize>> {
+        (0..self.len()).map(|i| self.to_vec(i)).collect()
+    }
+
+    // the columns of all rows and the end of each row.
+    pub(crate) fn parts(&self) -> (&[u32], &[u32]) {
+        (&self.cols, &self.ends)
+    }
+
+    pub(crate) fn from_parts(cols: Vec<u32>, ends: Vec<u32>) -> Self {
+        Self { cols, ends }
+    }
+
+    // bytes of the buffers.
+    pub fn bytes(&self) -> usize {
+        (self.cols.capacity() + self.ends.capacity()) * size_of::<u32>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rows;
+
+    #[test]
+    fn rows() {
+        let mut rows = Rows::new();
+        rows.push(&[0, 3, 5]);
+        rows.push(&[]);
+        rows.push(&[1, 2]);
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows.get(0), &[0, 3, 5]);
+        assert!(rows.get(1).is_empty());
+        assert_eq!(rows.to_vec(2), vec![1, 2]);
+
+        rows.get_mut(2)[1] += 4;
+        assert_eq!(
+            rows.iter().map(|r| r.len()).collect::<Vec<_>>(),
+            vec![3, 0, 2]
+        );
+        assert_eq!(rows.to_vecs(), vec![vec![0, 3, 5], vec![], vec![1, 6]]);
+    }
+}
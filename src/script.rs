@@ -0,0 +1,149 @@
+// Custom rules written in rhai, embedded in the puzzle file.
+//
+// A row rule is evaluated for every placement with `piece` (name),
+// `transform`, `x`, `y` (anchor) and `cells` (array of `[x, y]`) in scope, and
+// the placement is skipped unless it returns true. A solution rule is evaluated
+// for every solution with `cells` mapping piece names to their cells, and the
+// `touching(a, b)` function telling whether two cell arrays share an edge.
+use crate::shape::Point;
+use rhai::AST;
+use rhai::Array;
+use rhai::Dynamic;
+use rhai::Engine;
+use rhai::Map;
+use rhai::Scope;
+use std::fmt;
+
+pub struct Script {
+    engine: Engine,
+    row: Option<AST>,
+    solution: Option<AST>,
+    // the row rule as written, it decides which rows there are
+    row_source: String,
+}
+
+impl fmt::Debug for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Script")
+            .field("row", &self.row.is_some())
+            .field("solution", &self.solution.is_some())
+            .finish()
+    }
+}
+
+fn coords(cell: &Dynamic) -> Option<(i64, i64)> {
+    let cell = cell.clone().into_array().ok()?;
+    Some((cell.first()?.as_int().ok()?, cell.get(1)?.as_int().ok()?))
+}
+
+// whether two arrays of cells share an edge.
+fn touching(a: Array, b: Array) -> bool {
+    let b: Vec<(i64, i64)> = b.iter().filter_map(coords).collect();
+    a.iter()
+        .filter_map(coords)
+        .any(|(x, y)| b.iter().any(|(u, v)| (x - u).abs() + (y - v).abs() == 1))
+}
+
+fn cells(points: &[Point]) -> Array {
+    points
+        .iter()
+        .map(|p| {
+            let cell: Array = vec![Dynamic::from(p.x as i64), Dynamic::from(p.y as i64)];
+            Dynamic::from(cell)
+        })
+        .collect()
+}
+
+impl Script {
+    // compile the rules, empty sources are skipped.
+    pub fn new(row: &str, solution: &str) -> Result<Self, String> {
+        let mut engine = Engine::new();
+        engine.register_fn("touching", touching);
+
+        let compile = |source: &str| -> Result<Option<AST>, String> {
+            if source.trim().is_empty() {
+                return Ok(None);
+            }
+            engine
+                .compile_expression(source.trim())
+                .map(Some)
+                .map_err(|e| e.to_string())
+        };
+        let row_source = row.trim().to_string();
+        let row = compile(&row_source)?;
+        let solution = compile(solution)?;
+
+        Ok(Self {
+            engine,
+            row,
+            solution,
+            row_source,
+        })
+    }
+
+    pub fn row_source(&self) -> &str {
+        &self.row_source
+    }
+
+    fn eval(&self, ast: &AST, scope: &mut Scope) -> bool {
+        self.engine
+            .eval_ast_with_scope::<bool>(scope, ast)
+            .unwrap_or(false)
+    }
+
+    // whether the row rule accepts the placement.
+    pub fn allows_row(&self, piece: &str, transform: u8, anchor: &Point, points: &[Point]) -> bool {
+        let ast = match &self.row {
+            Some(ast) => ast,
+            None => return true,
+        };
+        let mut scope = Scope::new();
+        scope.push("piece", piece.to_string());
+        scope.push("transform", transform as i64);
+        scope.push("x", anchor.x as i64);
+        scope.push("y", anchor.y as i64);
+        scope.push("cells", cells(points));
+        self.eval(ast, &mut scope)
+    }
+
+    // whether the solution rule accepts the pieces placed on their cells.
+    pub fn allows_solution(&self, placed: &[(&str, Vec<Point>)]) -> bool {
+        let ast = match &self.solution {
+            Some(ast) => ast,
+            None => return true,
+        };
+        let mut map = Map::new();
+        for (name, points) in placed {
+            map.insert((*name).into(), Dynamic::from(cells(points)));
+        }
+        let mut scope = Scope::new();
+        scope.push("cells", map);
+        self.eval(ast, &mut scope)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Script;
+    use crate::shape::Point;
+
+    #[test]
+    fn rules() {
+        let script = Script::new(
+            "piece != \"A\" || y == 0",
+            "!touching(cells[\"A\"], cells[\"B\"])",
+        )
+        .unwrap();
+
+        let points = [Point::new(0, 1)];
+        assert!(!script.allows_row("A", 0, &Point::new(0, 1), &points));
+        assert!(script.allows_row("B", 0, &Point::new(0, 1), &points));
+
+        let apart = [("A", vec![Point::new(0, 0)]), ("B", vec![Point::new(2, 0)])];
+        let touching = [("A", vec![Point::new(0, 0)]), ("B", vec![Point::new(1, 0)])];
+        assert!(script.allows_solution(&apart));
+        assert!(!script.allows_solution(&touching));
+
+        assert!(Script::new("piece ==", "").is_err());
+    }
+}
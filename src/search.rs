@@ -0,0 +1,517 @@
+// Resumable Algorithm X search over the placement rows, with event hooks.
+//
+// Unlike the external solver this exposes every step of the search, which makes
+// it possible to observe (and later steer) what the algorithm is doing.
+
+use std::collections::VecDeque;
+
+// Callbacks for search events. Rows are identified by their row index.
+pub trait SearchObserver {
+    // a row was added to the partial solution.
+    fn enter(&mut self, _row: usize) {}
+
+    // a row was removed from the partial solution.
+    fn backtrack(&mut self, _row: usize) {}
+
+    // a complete solution was found.
+    fn solution(&mut self, _rows: &[usize]) {}
+}
+
+// Check run after every placement that can reject the partial solution early.
+pub trait Prune {
+    // whether the partial solution, given by its covered columns, cannot be completed.
+    fn is_dead(&self, covered: &[bool]) -> bool;
+}
+
+// Observer that ignores all events.
+pub struct NoObserver;
+
+impl SearchObserver for NoObserver {}
+
+// A recorded search decision, with the depth of the partial solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    // entered a row, choosing among `options` candidates for the column.
+    Enter {
+        row: usize,
+        depth: usize,
+        options: usize,
+    },
+    // removed a row again.
+    Backtrack {
+        row: usize,
+        depth: usize,
+    },
+    // a column without any available rows, forcing a backtrack.
+    DeadEnd {
+        depth: usize,
+    },
+    // found a solution.
+    Solution {
+        depth: usize,
+    },
+}
+
+// The last search decisions, at most `capacity` of them.
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    events: VecDeque<TraceEvent>,
+    capacity: usize,
+}
+
+impl Trace {
+    // keep the last `capacity` decisions, zero disables tracing.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.events.len() > capacity {
+            self.events.pop_front();
+        }
+    }
+
+    // the recorded decisions, oldest first.
+    pub fn tail(&self) -> Vec<TraceEvent> {
+        self.events.iter().cloned().collect()
+    }
+
+    pub fn record(&mut self, event: TraceEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+}
+
+// a branching point: the candidate rows for a column and the next one to try.
+struct Frame {
+    candidates: Vec<usize>,
+    next: usize,
+    row: Option<usize>,
+}
+
+pub struct Search {
+    rows: Vec<Vec<usize>>,
+    by_col: Vec<Vec<usize>>,
+    covered: Vec<bool>,
+    // number of covered columns a row intersects, the row is available at zero
+    blocked: Vec<usize>,
+    // available rows per column, and the number of uncovered columns without any
+    available: Vec<usize>,
+    starved: usize,
+    stack: Vec<Frame>,
+    started: bool,
+    done: bool,
+    // stopped before finishing, resume without advancing
+    interrupted: bool,
+    // number of rows entered so far
+    steps: usize,
+    // columns covered and uncovered so far
+    covers: usize,
+    uncovers: usize,
+    // number of covered columns, and the most covering partial solution seen
+    covered_count: usize,
+    best: (usize, Vec<usize>),
+    // the most recent decisions
+    trace: Trace,
+    pruners: Vec<Box<dyn Prune>>,
+}
+
+impl Search {
+    pub fn new(n_cols: usize, rows: Vec<Vec<usize>>) -> Self {
+        let mut by_col: Vec<Vec<usize>> = vec![Vec::new(); n_cols];
+        for (r, row) in rows.iter().enumerate() {
+            for c in row {
+                by_col[*c].push(r);
+            }
+        }
+        let available: Vec<usize> = by_col.iter().map(|rows| rows.len()).collect();
+        Self {
+            blocked: vec![0; rows.len()],
+            starved: available.iter().filter(|n| **n == 0).count(),
+            available,
+            rows,
+            by_col,
+            covered: vec![false; n_cols],
+            stack: Vec::new(),
+            started: false,
+            done: false,
+            interrupted: false,
+            steps: 0,
+            covers: 0,
+            uncovers: 0,
+            covered_count: 0,
+            best: (0, Vec::new()),
+            trace: Trace::default(),
+            pruners: Vec::new(),
+        }
+    }
+
+    // reject partial solutions the pruner proves cannot be completed.
+    pub fn add_pruner(&mut self, pruner: Box<dyn Prune>) {
+        self.pruners.push(pruner);
+    }
+
+    // keep the last `capacity` search decisions, zero disables tracing.
+    pub fn set_trace_capacity(&mut self, capacity: usize) {
+        self.trace.set_capacity(capacity);
+    }
+
+    // the most recent search decisions, oldest first.
+    pub fn trace_tail(&self) -> Vec<TraceEvent> {
+        self.trace.tail()
+    }
+
+    fn record(&mut self, event: TraceEvent) {
+        self.trace.record(event);
+    }
+
+    // number of rows entered so far.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // columns covered and uncovered so far, since the last reset.
+    pub fn covers(&self) -> (usize, usize) {
+        (self.covers, self.uncovers)
+    }
+
+    pub fn reset_covers(&mut self) {
+        self.covers = 0;
+        self.uncovers = 0;
+    }
+
+    // whether the last call stopped before finding a solution or exhausting the search.
+    pub fn interrupted(&self) -> bool {
+        self.interrupted
+    }
+
+    // the partial solution covering the most columns seen so far.
+    pub fn best_partial(&self) -> &[usize] {
+        &self.best.1
+    }
+
+    // rows of the current partial solution.
+    pub fn partial(&self) -> Vec<usize> {
+        self.stack.iter().filter_map(|f| f.row).collect()
+    }
+
+    fn select(&mut self, row: usize) {
+        self.covered_count += self.rows[row].len();
+        self.covers += self.rows[row].len();
+        for i in 0..self.rows[row].len() {
+            let c = self.rows[row][i];
+            self.covered[c] = true;
+            if self.available[c] == 0 {
+                self.starved -= 1;
+            }
+            for j in 0..self.by_col[c].len() {
+                let r = self.by_col[c][j];
+                self.blocked[r] += 1;
+                if self.blocked[r] == 1 {
+                    self.block(r);
+                }
+            }
+        }
+    }
+
+    fn deselect(&mut self, row: usize) {
+        self.covered_count -= self.rows[row].len();
+        self.uncovers += self.rows[row].len();
+        for i in (0..self.rows[row].len()).rev() {
+            let c = self.rows[row][i];
+            for j in 0..self.by_col[c].len() {
+                let r = self.by_col[c][j];
+                self.blocked[r] -= 1;
+                if self.blocked[r] == 0 {
+                    self.unblock(r);
+                }
+            }
+            self.covered[c] = false;
+            if self.available[c] == 0 {
+                self.starved += 1;
+            }
+        }
+    }
+
+    // the row is no longer available to its columns.
+    fn block(&mut self, row: usize) {
+        for c in &self.rows[row] {
+            self.available[*c] -= 1;
+            if self.available[*c] == 0 && !self.covered[*c] {
+                self.starved += 1;
+            }
+        }
+    }
+
+    fn unblock(&mut self, row: usize) {
+        for c in &self.rows[row] {
+            if self.available[*c] == 0 && !self.covered[*c] {
+                self.starved -= 1;
+            }
+            self.available[*c] += 1;
+        }
+    }
+
+    // uncovered column with the fewest available rows, and those rows.
+    fn choose(&self) -> Option<Vec<usize>> {
+        let col = (0..self.by_col.len())
+            .filter(|c| !self.covered[*c])
+            .min_by_key(|c| self.available[*c])?;
+        let candidates = self.by_col[col]
+            .iter()
+            .filter(|r| self.blocked[**r] == 0)
+            .cloned()
+            .collect();
+        Some(candidates)
+    }
+
+    // try the next candidate of the top frame, popping exhausted frames.
+    fn advance(&mut self, observer: &mut dyn SearchObserver) -> bool {
+        while !self.stack.is_empty() {
+            let depth = self.stack.len();
+            let frame = self.stack.last_mut().unwrap();
+            if let Some(row) = frame.row.take() {
+                self.deselect(row);
+                observer.backtrack(row);
+                self.record(TraceEvent::Backtrack { row, depth });
+            }
+            let frame = self.stack.last_mut().unwrap();
+            if frame.next < frame.candidates.len() {
+                let row = frame.candidates[frame.next];
+                let options = frame.candidates.len();
+                frame.next += 1;
+                frame.row = Some(row);
+                self.select(row);
+                observer.enter(row);
+                self.record(TraceEvent::Enter {
+                    row,
+                    depth,
+                    options,
+                });
+
+                self.steps += 1;
+                if self.covered_count > self.best.0 {
+                    self.best = (self.covered_count, self.partial());
+                }
+                return true;
+            }
+            self.stack.pop();
+        }
+        false
+    }
+
+    // find the next solution.
+    pub fn next(&mut self, observer: &mut dyn SearchObserver) -> Option<Vec<usize>> {
+        self.next_until(observer, &mut |_| false)
+    }
+
+    // find the next solution, stopping early when `stop` returns true for the
+    // number of steps taken. An interrupted search resumes on the next call.
+    pub fn next_until(
+        &mut self,
+        observer: &mut dyn SearchObserver,
+        stop: &mut dyn FnMut(usize) -> bool,
+    ) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        if self.started && !self.interrupted && !self.advance(observer) {
+            self.done = true;
+            return None;
+        }
+        self.started = true;
+        self.interrupted = false;
+
+        loop {
+            if stop(self.steps) {
+                self.interrupted = true;
+                return None;
+            }
+            // a column no available row covers any more ends the branch right
+            // away, without choosing it first
+            if self.starved > 0 || self.pruners.iter().any(|p| p.is_dead(&self.covered)) {
+                self.record(TraceEvent::DeadEnd {
+                    depth: self.stack.len(),
+                });
+                if !self.advance(observer) {
+                    self.done = true;
+                    return None;
+                }
+                continue;
+            }
+            match self.choose() {
+                None => {
+                    let solution = self.partial();
+                    observer.solution(&solution);
+                    self.record(TraceEvent::Solution {
+                        depth: solution.len(),
+                    });
+                    return Some(solution);
+                }
+                Some(candidates) => {
+                    self.stack.push(Frame {
+                        candidates,
+                        next: 0,
+                        row: None,
+                    });
+                    if !self.advance(observer) {
+                        self.done = true;
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NoObserver;
+    use super::Prune;
+    use super::Search;
+    use super::SearchObserver;
+    use super::TraceEvent;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl SearchObserver for Recorder {
+        fn enter(&mut self, row: usize) {
+            self.events.push(format!("+{}", row));
+        }
+
+        fn backtrack(&mut self, row: usize) {
+            self.events.push(format!("-{}", row));
+        }
+
+        fn solution(&mut self, rows: &[usize]) {
+            self.events.push(format!("{:?}", rows));
+        }
+    }
+
+    #[test]
+    fn solutions() {
+        let rows = vec![vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3], vec![0]];
+        let mut search = Search::new(4, rows);
+
+        assert_eq!(search.next(&mut NoObserver), Some(vec![0, 1]));
+        assert_eq!(search.next(&mut NoObserver), Some(vec![3, 2]));
+        assert_eq!(search.next(&mut NoObserver), None);
+        assert_eq!(search.next(&mut NoObserver), None);
+    }
+
+    #[test]
+    fn interrupted() {
+        let rows = vec![vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3], vec![0]];
+        let mut search = Search::new(4, rows);
+
+        // stop after the first step and keep the deepest partial solution
+        assert_eq!(
+            search.next_until(&mut NoObserver, &mut |steps| steps >= 1),
+            None
+        );
+        assert!(search.interrupted());
+        assert_eq!(search.steps(), 1);
+        assert_eq!(search.best_partial(), [0]);
+
+        // resuming continues where it stopped
+        assert_eq!(search.next(&mut NoObserver), Some(vec![0, 1]));
+        assert!(!search.interrupted());
+        assert_eq!(search.best_partial(), [0, 1]);
+        assert_eq!(search.next(&mut NoObserver), Some(vec![3, 2]));
+        assert_eq!(search.next(&mut NoObserver), None);
+    }
+
+    #[test]
+    fn trace() {
+        let rows = vec![vec![0, 1], vec![1], vec![0]];
+        let mut search = Search::new(2, rows);
+        search.set_trace_capacity(3);
+
+        while search.next(&mut NoObserver).is_some() {}
+
+        assert_eq!(
+            search.trace_tail(),
+            vec![
+                TraceEvent::Solution { depth: 2 },
+                TraceEvent::Backtrack { row: 1, depth: 2 },
+                TraceEvent::Backtrack { row: 2, depth: 1 },
+            ]
+        );
+
+        search.set_trace_capacity(1);
+        assert_eq!(
+            search.trace_tail(),
+            vec![TraceEvent::Backtrack { row: 2, depth: 1 }]
+        );
+    }
+
+    #[test]
+    fn prune() {
+        // rejects the partial solution with just the first row
+        struct NoFirstRow;
+
+        impl Prune for NoFirstRow {
+            fn is_dead(&self, covered: &[bool]) -> bool {
+                covered == [true, true, false, false]
+            }
+        }
+
+        let rows = vec![vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3], vec![0]];
+        let mut search = Search::new(4, rows);
+        search.add_pruner(Box::new(NoFirstRow));
+
+        assert_eq!(search.next(&mut NoObserver), Some(vec![3, 2]));
+        assert_eq!(search.next(&mut NoObserver), None);
+    }
+
+    #[test]
+    fn starved() {
+        // after row 0 both rows for column 1 are blocked
+        let rows = vec![vec![0, 2], vec![0, 1], vec![1, 2], vec![2]];
+        let mut search = Search::new(3, rows);
+        search.set_trace_capacity(usize::MAX);
+
+        assert_eq!(search.next(&mut NoObserver), Some(vec![1, 3]));
+        assert_eq!(
+            search.trace_tail()[..2],
+            vec![
+                TraceEvent::Enter {
+                    row: 0,
+                    depth: 1,
+                    options: 2
+                },
+                TraceEvent::DeadEnd { depth: 1 },
+            ]
+        );
+        assert_eq!(search.next(&mut NoObserver), None);
+
+        // the counts are restored after the search
+        assert_eq!(search.available, vec![2, 2, 3]);
+        assert_eq!(search.starved, 0);
+
+        // a column without rows fails before the first choice
+        let mut search = Search::new(2, vec![vec![0]]);
+        search.set_trace_capacity(1);
+        assert_eq!(search.next(&mut NoObserver), None);
+        assert_eq!(search.trace_tail(), vec![TraceEvent::DeadEnd { depth: 0 }]);
+    }
+
+    #[test]
+    fn events() {
+        let rows = vec![vec![0, 1], vec![1], vec![0]];
+        let mut search = Search::new(2, rows);
+        let mut recorder = Recorder::default();
+
+        while search.next(&mut recorder).is_some() {}
+
+        assert_eq!(
+            recorder.events,
+            vec!["+0", "[0]", "-0", "+2", "+1", "[2, 1]", "-1", "-2"]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+// Standard piece libraries: the tetrominoes, pentominoes and hexominoes.
+//
+// Free sets hold every shape once, up to rotation and reflection. One-sided
+// sets, for pieces that may not be flipped, add the mirror image of every
+// piece without mirror symmetry, named after the piece with a "'" (the
+// tetrominoes keep their usual J and Z).
+
+use crate::shape::PENTOMINOES;
+use crate::shape::Tile;
+
+// The five free tetrominoes, with the name of the mirror image of the chiral
+// ones.
+const TETROMINOES: [(&str, &str, Option<&str>); 5] = [
+    ("I", "xxxx", None),
+    ("O", "xx\nxx", None),
+    ("T", "xxx\n x", None),
+    ("S", " xx\nxx", Some("Z")),
+    ("L", "xxx\nx", Some("J")),
+];
+
+// The 35 free hexominoes, in the order `free_polyominoes(6)` finds them.
+const HEXOMINOES: [&str; 35] = [
+    "x\nx\nx\nx\nx\nx",
+    "xx\nx\nx\nx\nx",
+    "x\nxx\nx\nx\nx",
+    "x\nx\nxx\nx\nx",
+    "xx\nxx\nx\nx",
+    "xx\nx\nxx\nx",
+    "xx\nx\nx\nxx",
+    "xxx\nx\nx\nx",
+    "x\nxx\nxx\nx",
+    "x\nxxx\nx\nx",
+    "x\nx\nx\nxx\n x",
+    "xx\nxx\nxx",
+    "xxx\nxx\nx",
+    "xx\nxxx\nx",
+    "xx\nx\nxx\n x",
+    "xxx\nx\nxx",
+    "x\nxx\nxx\n x",
+    "x x\nxxx\nx",
+    "x\nxxxx\nx",
+    "x\nx\nxx\n x\n x",
+    "x\nx\nxxx\n x",
+    "x\nx\nxx\n xx",
+    "x\nx\nxxx\n  x",
+    "xx\nxxx\n x",
+    "xx\nxx\n xx",
+    "x\nxxx\n x\n x",
+    "x\nxx\n xx\n x",
+    "x\nxx\n x\n xx",
+    "x x\nxxx\n x",
+    "x\nxxxx\n x",
+    "x\nxx\n xx\n  x",
+    "x\nxxxx\n  x",
+    "x\nxxxx\n   x",
+    " x\nxxx\n x\n x",
+    " x\nxx\n xx\n x",
+];
+
+// the five tetrominoes, in one-sided sets with J and Z.
+pub fn tetrominoes(one_sided: bool) -> Vec<Tile> {
+    let mut tiles = Vec::new();
+    for (name, drawing, mirror) in TETROMINOES {
+        let tile = Tile::from_str(name, drawing);
+        let mirrored = Tile::from_points(mirror.unwrap_or(name), &cells(&tile.mirrored()));
+        tiles.push(tile);
+        if one_sided && mirror.is_some() {
+            tiles.push(mirrored);
+        }
+    }
+    tiles
+}
+
+// the twelve pentominoes in Conway's letters.
+pub fn pentominoes(one_sided: bool) -> Vec<Tile> {
+    let tiles = PENTOMINOES
+        .iter()
+        .map(|(name, drawing)| Tile::from_str(name, drawing))
+        .collect();
+    with_mirrors(tiles, one_sided)
+}
+
+// the 35 hexominoes, named "H1" to "H35".
+pub fn hexominoes(one_sided: bool) -> Vec<Tile> {
+    let tiles = HEXOMINOES
+        .iter()
+        .enumerate()
+        .map(|(i, drawing)| Tile::from_str(&format!("H{}", i + 1), drawing))
+        .collect();
+    with_mirrors(tiles, one_sided)
+}
+
+// the tiles, followed in one-sided sets by the mirror image of the chiral
+// ones.
+fn with_mirrors(tiles: Vec<Tile>, one_sided: bool) -> Vec<Tile> {
+    let mut all = Vec::new();
+    for tile in tiles {
+        let chiral = one_sided && !tile.symmetries().is_mirror_symmetric();
+        let mirror = format!("{}'", tile.name());
+        all.push(tile.clone());
+        if chiral {
+            all.push(Tile::from_points(&mirror, &cells(&tile.mirrored())));
+        }
+    }
+    all
+}
+
+// (x, y) cells of a tile, for `Tile::from_points`.
+fn cells(tile: &Tile) -> Vec<(i32, i32)> {
+    tile.points
+        .iter()
+        .map(|p| (p.x as i32, p.y as i32))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::cells;
+    use super::hexominoes;
+    use super::pentominoes;
+    use super::tetrominoes;
+    use crate::enumerate::free_polyominoes;
+    use crate::shape::Tile;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(tetrominoes(false).len(), 5);
+        assert_eq!(tetrominoes(true).len(), 7);
+        assert_eq!(pentominoes(false).len(), 12);
+        assert_eq!(pentominoes(true).len(), 18);
+        assert_eq!(hexominoes(false).len(), 35);
+        assert_eq!(hexominoes(true).len(), 60);
+        assert!(hexominoes(false).iter().all(|t| t.len() == 6));
+    }
+
+    #[test]
+    fn hexomino_table() {
+        // the table holds the enumerated hexominoes, in the same order
+        let table = hexominoes(false);
+        let found = free_polyominoes(6);
+        assert_eq!(table.len(), found.len());
+        for (a, b) in table.iter().zip(&found) {
+            assert_eq!(cells(a), cells(b));
+        }
+    }
+
+    #[test]
+    fn one_sided() {
+        let names: Vec<String> = tetrominoes(true)
+            .iter()
+            .map(|t| t.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["I", "O", "T", "S", "Z", "L", "J"]);
+
+        // every mirror image is congruent to its piece, yet distinct from
+        // all pieces when flipping is not allowed
+        let tiles = pentominoes(true);
+        assert_eq!(tiles[1].name(), "F'");
+        assert!(tiles[0].congruent_to(&tiles[1]));
+        let turns = |tile: &Tile| {
+            let mut turned = tile.clone();
+            (0..4)
+                .map(|_| {
+                    turned = turned.rotated();
+                    Tile::from_points("", &cells(&turned)).points
+                })
+                .collect::<Vec<_>>()
+        };
+        for (i, a) in tiles.iter().enumerate() {
+            let b = Tile::from_points("", &cells(a)).points;
+            assert!(tiles[i + 1..].iter().all(|t| !turns(t).contains(&b)));
+        }
+    }
+}
@@ -0,0 +1,1020 @@
+// Shapes on the square grid: cells, tiles and how board edges connect.
+//
+// A tile is a set of cells with a name; the geometry here knows nothing of
+// puzzles, which live in `polyomino`.
+
+use crate::descriptors;
+use crate::descriptors::Descriptors;
+use crate::orientation::OrientationCache;
+use crate::parser::Charset;
+use crate::placement::SymmetryGroup;
+use smallvec::SmallVec;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ops;
+
+// Size
+#[derive(Debug, PartialEq, Eq)]
+pub struct Size {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+}
+
+impl Size {
+    pub fn new(width: usize, height: usize) -> Self {
+        Size {
+            width: width,
+            height: height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+// The twelve free pentominoes, in Conway's letters.
+pub(crate) const PENTOMINOES: [(&str, &str); 12] = [
+    ("F", " xx\nxx\n x"),
+    ("I", "xxxxx"),
+    ("L", "xxxx\nx"),
+    ("N", "xxx\n  xx"),
+    ("P", "xxx\nxx"),
+    ("T", "xxx\n x\n x"),
+    ("U", "x x\nxxx"),
+    ("V", "xxx\nx\nx"),
+    ("W", "x\nxx\n xx"),
+    ("X", " x\nxxx\n x"),
+    ("Y", "xxxx\n x"),
+    ("Z", "xx\n x\n xx"),
+];
+
+// drawing of a pentomino by its letter.
+pub(crate) fn pentomino_drawing(letter: &str) -> Option<&'static str> {
+    PENTOMINOES
+        .iter()
+        .find(|(name, _)| *name == letter)
+        .map(|(_, drawing)| *drawing)
+}
+
+// Point
+#[derive(PartialEq, Eq, Hash, Debug, Clone, PartialOrd, Ord)]
+pub struct Point {
+    pub(crate) x: isize,
+    pub(crate) y: isize,
+}
+
+impl Point {
+    pub fn new(x: isize, y: isize) -> Self {
+        Point { x: x, y: y }
+    }
+
+    pub fn x(&self) -> isize {
+        self.x
+    }
+
+    pub fn y(&self) -> isize {
+        self.y
+    }
+
+    pub fn from(x: &usize, y: &usize) -> Self {
+        Point {
+            x: isize::try_from(*x)
+                .ok()
+                .expect("Could not convert to isize"),
+            y: isize::try_from(*y)
+                .ok()
+                .expect("Could not convert to isize"),
+        }
+    }
+}
+
+impl ops::Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point {
+            x: -self.x.clone(),
+            y: -self.y.clone(),
+        }
+    }
+}
+
+impl ops::Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl ops::Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl ops::AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        };
+    }
+}
+
+// Cells of a tile. Pieces have a handful of cells, which stay inline.
+pub type Points = SmallVec<[Point; 12]>;
+
+#[derive(Eq, Hash, PartialEq, Clone, Ord, PartialOrd, Debug)]
+pub struct Tile {
+    pub(crate) name: String,
+    pub(crate) points: Points,
+}
+
+impl Tile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            points: Points::new(),
+        }
+    }
+
+    // rectangular board of the given size.
+    pub fn rect(width: usize, height: usize) -> Self {
+        let mut board = Tile::new("Board");
+        for y in 0..height {
+            for x in 0..width {
+                board.points.push(Point::from(&x, &y));
+            }
+        }
+        board
+    }
+
+    // rectangular board with shapes cut out, each with the top left of its
+    // bounding box at the given position. Cells outside the rectangle are
+    // ignored.
+    pub fn rect_minus(width: usize, height: usize, cutouts: &[(Tile, Point)]) -> Self {
+        let mut board = Tile::rect(width, height);
+        for (tile, position) in cutouts {
+            let (mut cutout, _) = tile.cropped();
+            cutout.translate(position);
+            board = board.difference(&cutout);
+        }
+        board
+    }
+
+    // tile drawn in other characters than 'x'.
+    pub fn from_str_with(name: &str, contents: &str, charset: &Charset) -> Self {
+        Self::from_str(name, &charset.normalize(contents))
+    }
+
+    pub fn from_str(name: &str, contents: &str) -> Self {
+        let mut row: usize = 0;
+        let mut col: usize = 0;
+
+        let mut points = Points::new();
+
+        for c in contents.chars() {
+            match c {
+                'x' => {
+                    points.push(Point::from(&col, &row));
+                    col += 1;
+                }
+                '\n' => {
+                    row += 1;
+                    col = 0;
+                }
+                // the first half of a Windows line ending
+                '\r' => {}
+                _ => col += 1,
+            }
+        }
+
+        let mut tile = Self {
+            name: name.to_string(),
+            points: points,
+        };
+        // move tile top-left to origo
+        if !tile.points.is_empty() {
+            tile.translate(&(-tile.offset()));
+        }
+        return tile;
+    }
+
+    // tile with the given (x, y) cells, y pointing down as in `from_str`.
+    // Moved to the origin like `from_str`, repeated cells count once.
+    pub fn from_points(name: &str, cells: &[(i32, i32)]) -> Self {
+        let mut points: Points = cells
+            .iter()
+            .map(|(x, y)| Point::new(*x as isize, *y as isize))
+            .collect();
+        // row by row, the order `from_str` reads them in
+        points.sort_by_key(|p| (p.y, p.x));
+        points.dedup();
+
+        let mut tile = Self {
+            name: name.to_string(),
+            points,
+        };
+        if !tile.points.is_empty() {
+            tile.translate(&(-tile.offset()));
+        }
+        tile
+    }
+
+    // one of the twelve free pentominoes by its letter, as drawn in
+    // `PENTOMINOES`.
+    pub fn pentomino(letter: &str) -> Option<Self> {
+        Some(Tile::from_str(letter, pentomino_drawing(letter)?))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    // the 4-connected regions of the tile, named after it, in the order of
+    // their first cell.
+    pub fn components(&self) -> Vec<Tile> {
+        let points: HashSet<&Point> = self.points.iter().collect();
+        let mut seen: HashSet<&Point> = HashSet::new();
+        let mut components = Vec::new();
+
+        for start in &self.points {
+            if seen.contains(start) {
+                continue;
+            }
+            let mut component = Tile::new(&self.name);
+            let mut stack = vec![start];
+            while let Some(p) = stack.pop() {
+                if !seen.insert(p) {
+                    continue;
+                }
+                component.points.push(p.clone());
+                for d in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                    if let Some(q) = points.get(&Point::new(p.x + d.0, p.y + d.1)) {
+                        stack.push(q);
+                    }
+                }
+            }
+            component.points.sort_by_key(|p| (p.y, p.x));
+            components.push(component);
+        }
+        components
+    }
+
+    // whether all points are 4-connected.
+    pub fn is_connected(&self) -> bool {
+        let points: HashSet<&Point> = self.points.iter().collect();
+        let mut seen: HashSet<&Point> = HashSet::new();
+        let mut stack: Vec<&Point> = self.points.iter().take(1).collect();
+
+        while let Some(p) = stack.pop() {
+            if !seen.insert(p) {
+                continue;
+            }
+            for d in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let q = Point::new(p.x + d.0, p.y + d.1);
+                if let Some(q) = points.get(&q) {
+                    stack.push(q);
+                }
+            }
+        }
+        seen.len() == points.len()
+    }
+
+    // every cell replaced by a k by k block of cells. As a board it asks
+    // whether the shape is a rep-tile, tiled by k * k copies of itself.
+    pub fn scaled(&self, k: usize) -> Tile {
+        let k = isize::try_from(k).unwrap();
+        let mut points = Points::with_capacity(self.points.len() * (k * k) as usize);
+        for p in &self.points {
+            for dy in 0..k {
+                for dx in 0..k {
+                    points.push(Point::new(p.x * k + dx, p.y * k + dy));
+                }
+            }
+        }
+        points.sort();
+        Tile {
+            name: self.name.clone(),
+            points,
+        }
+    }
+
+    // inverse of `scaled`: one cell per k by k block. None when the cells do
+    // not split into complete blocks aligned with the origin.
+    pub fn downsample(&self, k: usize) -> Option<Tile> {
+        if k == 0 {
+            return None;
+        }
+        let k = isize::try_from(k).unwrap();
+        let mut blocks: HashMap<Point, isize> = HashMap::new();
+        for p in &self.points {
+            let block = Point::new(p.x.div_euclid(k), p.y.div_euclid(k));
+            *blocks.entry(block).or_insert(0) += 1;
+        }
+        if blocks.values().any(|n| *n != k * k) || blocks.len() * ((k * k) as usize) != self.len() {
+            return None;
+        }
+        let mut points: Points = blocks.into_keys().collect();
+        points.sort();
+        Some(Tile {
+            name: self.name.clone(),
+            points,
+        })
+    }
+
+    // mirror x (along y-axis).
+    pub fn mirror(&mut self) {
+        for p in self.points.iter_mut() {
+            p.x = -p.x;
+        }
+    }
+
+    // rotate 90 degrees counter-clockwise.
+    pub fn rotate(&mut self) {
+        let mut v: isize;
+        for p in self.points.iter_mut() {
+            v = p.x;
+            p.x = p.y;
+            p.y = -v.clone();
+        }
+    }
+
+    // the shape in a standard position: of its rotations and reflections
+    // moved to the origin, the one with the smallest sorted cells. Congruent
+    // tiles have the same canonical cells.
+    pub fn canonical(&self) -> Tile {
+        let points = OrientationCache::new()
+            .orientations(self)
+            .iter()
+            .map(|(points, _)| points)
+            .min()
+            .cloned()
+            .unwrap_or_default();
+        Tile {
+            name: self.name.clone(),
+            points,
+        }
+    }
+
+    // whether the tiles have the same shape, up to rotation, reflection and
+    // translation. Names are ignored.
+    pub fn congruent_to(&self, other: &Tile) -> bool {
+        self.len() == other.len() && self.canonical().points == other.canonical().points
+    }
+
+    // the rotations and reflections mapping the shape onto itself.
+    pub fn symmetries(&self) -> SymmetryGroup {
+        // the first orientation is the shape itself, made by the identity
+        // and every other transform in the group
+        let mask = OrientationCache::new()
+            .orientations(self)
+            .first()
+            .map(|(_, mask)| *mask)
+            .unwrap_or(u8::MAX);
+        SymmetryGroup(mask)
+    }
+
+    // number of distinct orientations of the shape, 1 to 8, or 1 to 4 for
+    // `one_sided` pieces that may not be flipped.
+    pub fn orientation_count(&self, one_sided: bool) -> usize {
+        let symmetries = self.symmetries();
+        match one_sided {
+            true => 4 / symmetries.rotations(),
+            false => 8 / symmetries.order(),
+        }
+    }
+
+    // cells in either tile, named after this one. Like the other set
+    // operations the cells keep their positions, row by row.
+    pub fn union(&self, other: &Tile) -> Tile {
+        self.with_cells(self.points.iter().chain(&other.points))
+    }
+
+    // cells of this tile that are not in `other`.
+    pub fn difference(&self, other: &Tile) -> Tile {
+        let others: HashSet<&Point> = other.points.iter().collect();
+        self.with_cells(self.points.iter().filter(|p| !others.contains(p)))
+    }
+
+    // cells in both tiles.
+    pub fn intersection(&self, other: &Tile) -> Tile {
+        let others: HashSet<&Point> = other.points.iter().collect();
+        self.with_cells(self.points.iter().filter(|p| others.contains(p)))
+    }
+
+    fn with_cells<'a>(&self, cells: impl Iterator<Item = &'a Point>) -> Tile {
+        let mut points: Points = cells.cloned().collect();
+        points.sort_by_key(|p| (p.y, p.x));
+        points.dedup();
+        Tile {
+            name: self.name.clone(),
+            points,
+        }
+    }
+
+    // mirrored copy, see `mirror`.
+    pub fn mirrored(&self) -> Tile {
+        let mut tile = self.clone();
+        tile.mirror();
+        tile
+    }
+
+    // copy rotated 90 degrees counter-clockwise, see `rotate`. The copy is
+    // not moved back to the origin, like the placement transforms.
+    pub fn rotated(&self) -> Tile {
+        let mut tile = self.clone();
+        tile.rotate();
+        tile
+    }
+
+    // copy moved to the origin, and the offset it was moved by: the cells of
+    // the tile are those of the copy plus the offset.
+    pub fn cropped(&self) -> (Tile, Point) {
+        let (offset, _) = self.bounding_box();
+        let mut tile = self.clone();
+        tile.translate(&-offset.clone());
+        (tile, offset)
+    }
+
+    pub fn translate(&mut self, offset: &Point) {
+        for p in self.points.iter_mut() {
+            p.x += offset.x;
+            p.y += offset.y;
+        }
+    }
+
+    // top left offset
+    pub fn offset(&self) -> Point {
+        let mut x: isize = self.points[0].x;
+        let mut y: isize = self.points[0].y;
+
+        for p in self.points.iter() {
+            if p.x < x {
+                x = p.x;
+            }
+            if p.y < y {
+                y = p.y;
+            }
+        }
+
+        Point::new(x, y)
+    }
+
+    pub fn index(&self, point: &Point) -> Option<usize> {
+        self.points.iter().position(|r| r == point)
+    }
+
+    pub fn size(&self) -> Size {
+        if self.points.len() == 0 {
+            return Size {
+                width: 0,
+                height: 0,
+            };
+        }
+
+        let mut xmin = self.points[0].x;
+        let mut xmax = xmin;
+        let mut ymin = self.points[0].y;
+        let mut ymax = ymin;
+
+        for p in self.points.iter() {
+            if p.x > xmax {
+                xmax = p.x;
+            }
+            if p.x < xmin {
+                xmin = p.x;
+            }
+            if p.y > ymax {
+                ymax = p.y;
+            }
+            if p.y < ymin {
+                ymin = p.y;
+            }
+        }
+        Size {
+            width: usize::try_from(xmax - xmin + 1).unwrap(),
+            height: usize::try_from(ymax - ymin + 1).unwrap(),
+        }
+    }
+
+    // top left corner and size of the smallest rectangle around the cells,
+    // the origin and no size when there are none.
+    pub fn bounding_box(&self) -> (Point, Size) {
+        match self.points.is_empty() {
+            true => (Point::new(0, 0), Size::new(0, 0)),
+            false => (self.offset(), self.size()),
+        }
+    }
+
+    // number of cell edges on the outline, holes included: every cell has four
+    // edges, and every pair of neighbours hides two of them.
+    pub fn perimeter(&self) -> usize {
+        let points: HashSet<&Point> = self.points.iter().collect();
+        let neighbours = points
+            .iter()
+            .flat_map(|p| [Point::new(p.x + 1, p.y), Point::new(p.x, p.y + 1)])
+            .filter(|q| points.contains(q))
+            .count();
+        4 * points.len() - 2 * neighbours
+    }
+
+    // the cells of the tile sharing an edge with `point`, which need not be a
+    // cell itself, when the tile is a board with the given topology. Right,
+    // left, down, up.
+    pub fn neighbors4(&self, point: &Point, topology: Topology) -> Vec<Point> {
+        self.adjacent(point, &SIDES, topology, &|q| self.points.contains(q))
+    }
+
+    // the cells of the tile sharing an edge or a corner with `point`.
+    pub fn neighbors8(&self, point: &Point, topology: Topology) -> Vec<Point> {
+        let around = [SIDES, CORNERS].concat();
+        self.adjacent(point, &around, topology, &|q| self.points.contains(q))
+    }
+
+    // the cells with a side on the outline of the board, holes included. None
+    // on a full torus.
+    pub fn boundary_cells(&self, topology: Topology) -> Vec<Point> {
+        let points: HashSet<&Point> = self.points.iter().collect();
+        let (offset, size) = self.bounding_box();
+        self.points
+            .iter()
+            .filter(|p| {
+                SIDES.iter().any(|(dx, dy)| {
+                    let q = Point::new(p.x + dx, p.y + dy);
+                    !points.contains(&topology.wrap(&q, &offset, &size))
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    // the distinct cells, by `contains`, at the steps from `point` taken back
+    // into the bounding box along the edges the topology wraps around.
+    pub(crate) fn adjacent(
+        &self,
+        point: &Point,
+        steps: &[(isize, isize)],
+        topology: Topology,
+        contains: &dyn Fn(&Point) -> bool,
+    ) -> Vec<Point> {
+        let mut adjacent: Vec<Point> = Vec::new();
+        if self.points.is_empty() {
+            return adjacent;
+        }
+        let (offset, size) = self.bounding_box();
+        for (dx, dy) in steps {
+            let q = topology.wrap(&Point::new(point.x + dx, point.y + dy), &offset, &size);
+            if q != *point && contains(&q) && !adjacent.contains(&q) {
+                adjacent.push(q);
+            }
+        }
+        adjacent
+    }
+
+    // the enclosed empty regions: cells outside the tile that cannot reach the
+    // outside through empty neighbours, row by row, in the order of their
+    // first cell.
+    pub fn holes(&self) -> Vec<Vec<Point>> {
+        if self.points.is_empty() {
+            return Vec::new();
+        }
+        let points: HashSet<&Point> = self.points.iter().collect();
+        let (offset, size) = self.bounding_box();
+        let (width, height) = (size.width as isize, size.height as isize);
+        let inside = |p: &Point| {
+            (offset.x..offset.x + width).contains(&p.x)
+                && (offset.y..offset.y + height).contains(&p.y)
+        };
+
+        // empty cells reachable from the frame one cell around the box
+        let mut outside: HashSet<Point> = HashSet::new();
+        let mut stack: Vec<Point> = Vec::new();
+        for x in offset.x - 1..=offset.x + width {
+            stack.push(Point::new(x, offset.y - 1));
+            stack.push(Point::new(x, offset.y + height));
+        }
+        for y in offset.y..offset.y + height {
+            stack.push(Point::new(offset.x - 1, y));
+            stack.push(Point::new(offset.x + width, y));
+        }
+        while let Some(p) = stack.pop() {
+            if points.contains(&p) || !outside.insert(p.clone()) {
+                continue;
+            }
+            for d in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let q = Point::new(p.x + d.0, p.y + d.1);
+                if inside(&q) {
+                    stack.push(q);
+                }
+            }
+        }
+
+        let mut enclosed = Tile::new(&self.name);
+        for y in offset.y..offset.y + height {
+            for x in offset.x..offset.x + width {
+                let p = Point::new(x, y);
+                if !points.contains(&p) && !outside.contains(&p) {
+                    enclosed.points.push(p);
+                }
+            }
+        }
+        enclosed
+            .components()
+            .into_iter()
+            .map(|hole| hole.points.into_vec())
+            .collect()
+    }
+
+    // center of mass of the cells, each cell a unit square with its top left
+    // corner at its point. None for an empty tile.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        if self.points.is_empty() {
+            return None;
+        }
+        let n = self.points.len() as f64;
+        let x: f64 = self.points.iter().map(|p| p.x as f64 + 0.5).sum();
+        let y: f64 = self.points.iter().map(|p| p.y as f64 + 0.5).sum();
+        Some((x / n, y / n))
+    }
+
+    // area, aspect ratio, convexity, corners and other measures of the
+    // shape. None for an empty tile.
+    pub fn descriptors(&self) -> Option<Descriptors> {
+        descriptors::describe(self)
+    }
+
+    // the cell to put a label in: the one closest to the centroid, which may
+    // itself lie outside the tile. The first such cell on ties.
+    pub fn label_anchor(&self) -> Option<Point> {
+        let (cx, cy) = self.centroid()?;
+        let distance = |p: &Point| {
+            let (dx, dy) = (p.x as f64 + 0.5 - cx, p.y as f64 + 0.5 - cy);
+            dx * dx + dy * dy
+        };
+        self.points
+            .iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .cloned()
+    }
+
+    // whether copies of the tile, all in the same orientation, can cover the
+    // plane. Tiles that are disconnected or have holes never do.
+    pub fn tiles_plane_by_translation(&self) -> bool {
+        crate::analysis::tiles_plane(self)
+    }
+}
+
+// steps to the cells sharing a side, and only a corner, with a cell
+pub(crate) const SIDES: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const CORNERS: [(isize, isize); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+
+// How the edges of the board connect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Topology {
+    #[default]
+    Plane,
+    // the bounding box of the board with its opposite edges glued together,
+    // so pieces leaving on one side come back on the other
+    Torus,
+    // only the left and right edges glued together
+    CylinderX,
+    // the left and right edges glued together with a half twist, so pieces
+    // leaving on one side come back upside down on the other
+    Mobius,
+}
+
+impl Topology {
+    // the topology named in a puzzle: plane, torus, cylinder-x or mobius.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "plane" => Some(Topology::Plane),
+            "torus" => Some(Topology::Torus),
+            "cylinder-x" => Some(Topology::CylinderX),
+            "mobius" => Some(Topology::Mobius),
+            _ => None,
+        }
+    }
+
+    // whether pieces wrap around the left and right, and the top and bottom.
+    pub fn wraps(&self) -> (bool, bool) {
+        match self {
+            Topology::Plane => (false, false),
+            Topology::Torus => (true, true),
+            Topology::CylinderX | Topology::Mobius => (true, false),
+        }
+    }
+
+    // a point taken back into the box at `offset` of `size` along the edges
+    // that wrap around.
+    pub fn wrap(&self, point: &Point, offset: &Point, size: &Size) -> Point {
+        let (width, height) = (size.width as isize, size.height as isize);
+        let (wrap_x, wrap_y) = self.wraps();
+        let mut point = point.clone();
+        if wrap_x {
+            let turns = (point.x - offset.x).div_euclid(width);
+            point.x = offset.x + (point.x - offset.x).rem_euclid(width);
+            if *self == Topology::Mobius && turns % 2 != 0 {
+                point.y = 2 * offset.y + height - 1 - point.y;
+            }
+        }
+        if wrap_y {
+            point.y = offset.y + (point.y - offset.y).rem_euclid(height);
+        }
+        point
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Point;
+    use super::Size;
+    use super::Tile;
+    use super::Topology;
+    use crate::placement::Transform;
+
+    #[test]
+    fn point() {
+        let point1 = Point::new(1, 2);
+        let point2 = Point::new(3, 4);
+
+        assert_eq!(-point1.clone(), Point::new(-1, -2));
+        assert_eq!(point1 + point2, Point::new(4, 6));
+    }
+
+    #[test]
+    fn board() {
+        // New
+        let board = Tile::new("Board");
+        assert_eq!(board.size(), Size::new(0, 0));
+
+        // From string
+        let contents = "xxx-xx\nxxxx-";
+        let board = Tile::from_str("Board", &contents);
+
+        assert!(board.points.contains(&Point::new(0, 0)));
+        assert!(board.points.contains(&Point::new(3, 1)));
+        assert!(!board.points.contains(&Point::new(3, 0)))
+    }
+
+    #[test]
+    fn tile() {
+        // Empty
+        let mut tile = Tile::new("X");
+        tile.rotate();
+        tile.mirror();
+        tile.translate(&Point::new(1, 1));
+
+        // From str
+        let contents = "xxx-xx\nxxxx-";
+        let mut tile = Tile::from_str("X", &contents);
+
+        assert_eq!(tile.points[0], Point::new(0, 0));
+        assert_eq!(tile.points[1], Point::new(1, 0));
+        assert_eq!(tile.points[8], Point::new(3, 1));
+
+        tile.mirror();
+        assert_eq!(tile.points[0], Point::new(0, 0));
+        assert_eq!(tile.points[1], Point::new(-1, 0));
+        assert_eq!(tile.points[8], Point::new(-3, 1));
+
+        tile.rotate();
+        assert_eq!(tile.points[0], Point::new(0, 0));
+        assert_eq!(tile.points[1], Point::new(0, 1));
+        assert_eq!(tile.points[8], Point::new(1, 3));
+
+        tile.translate(&Point::new(1, 2));
+        assert_eq!(tile.points[0], Point::new(1, 2));
+        assert_eq!(tile.points[1], Point::new(1, 3));
+        assert_eq!(tile.points[8], Point::new(2, 5));
+
+        let point = tile.offset();
+        assert_eq!(point, Point::new(1, 2));
+
+        tile.translate(&-point);
+        assert_eq!(tile.points[0], Point::new(0, 0));
+        assert_eq!(tile.points[1], Point::new(0, 1));
+        assert_eq!(tile.points[8], Point::new(1, 3));
+    }
+
+    #[test]
+    fn congruence() {
+        let l = Tile::from_str("L", "x\nx\nxx");
+        let mut moved = Tile::from_str("J", "xxx\n  x").mirrored();
+        moved.translate(&Point::new(5, -2));
+        assert!(l.congruent_to(&moved));
+        assert_eq!(l.canonical().points(), moved.canonical().points());
+        assert_eq!(l.canonical().name(), "L");
+        assert_eq!(l.canonical().canonical(), l.canonical());
+
+        assert!(!l.congruent_to(&Tile::from_str("T", "xxx\n x")));
+        assert!(!l.congruent_to(&Tile::from_str("I", "xxx")));
+        assert!(Tile::new("A").congruent_to(&Tile::new("B")));
+    }
+
+    #[test]
+    fn symmetries() {
+        let square = Tile::from_str("O", "xx\nxx").symmetries();
+        assert_eq!(square.order(), 8);
+        let s = Tile::from_str("S", " xx\nxx").symmetries();
+        assert_eq!(s.transforms(), vec![Transform(0), Transform(2)]);
+        assert!(!s.is_mirror_symmetric());
+        let t = Tile::from_str("T", "xxx\n x").symmetries();
+        assert_eq!((t.order(), t.rotations()), (2, 1));
+        assert!(t.is_mirror_symmetric());
+        assert_eq!(Tile::from_str("F", " xx\nxx\n x").symmetries().order(), 1);
+    }
+
+    #[test]
+    fn orientation_count() {
+        let counts = |tile: &Tile| (tile.orientation_count(false), tile.orientation_count(true));
+        assert_eq!(counts(&Tile::from_str("O", "xx\nxx")), (1, 1));
+        assert_eq!(counts(&Tile::from_str("I", "xxx")), (2, 2));
+        assert_eq!(counts(&Tile::from_str("S", " xx\nxx")), (4, 2));
+        assert_eq!(counts(&Tile::from_str("T", "xxx\n x")), (4, 4));
+        assert_eq!(counts(&Tile::from_str("F", " xx\nxx\n x")), (8, 4));
+    }
+
+    #[test]
+    fn set_operations() {
+        let board = Tile::rect(4, 3);
+        let mut blob = Tile::from_str("Blob", "xx\nx");
+        blob.translate(&Point::new(1, 1));
+        let cut = board.difference(&blob);
+        assert_eq!(cut.name(), "Board");
+        assert_eq!(cut, Tile::from_str("Board", "xxxx\nx  x\nx xx"));
+        assert_eq!(cut.union(&blob), board);
+        assert_eq!(board.intersection(&blob).points(), blob.points());
+        assert_eq!(cut.intersection(&blob).len(), 0);
+    }
+
+    #[test]
+    fn transformed_copies() {
+        let tile = Tile::from_str("L", "xx\nx");
+        let mut rotated = tile.clone();
+        rotated.rotate();
+        assert_eq!(tile.rotated(), rotated);
+        assert_eq!(tile.mirrored().points()[1], Point::new(-1, 0));
+        assert_eq!(tile.rotated().rotated().rotated().rotated(), tile);
+
+        // the same cells as the solver's orientation
+        let mut oriented = tile.clone();
+        Transform(5).apply(&mut oriented);
+        assert_eq!(tile.mirrored().rotated(), oriented);
+
+        let size = Tile::from_str("I", "xxx").rotated().size();
+        assert_eq!((size.width(), size.height()), (1, 3));
+    }
+
+    #[test]
+    fn from_points() {
+        let tile = Tile::from_points("L", &[(5, 3), (5, 1), (6, 1), (5, 2), (5, 1)]);
+        assert_eq!(tile, Tile::from_str("L", "xx\nx\nx"));
+        assert_eq!(tile.name(), "L");
+        assert_eq!(tile.points()[1].x(), 1);
+        assert_eq!(tile.points()[3].y(), 2);
+        assert_eq!(Tile::from_points("Empty", &[]).len(), 0);
+    }
+
+    #[test]
+    fn rect_minus() {
+        let square = Tile::from_str("O", "xx\nxx");
+        let board = Tile::rect_minus(8, 8, &[(square.clone(), Point::new(3, 3))]);
+        assert_eq!(board.len(), 60);
+        assert!(!board.points.contains(&Point::new(4, 4)));
+        assert_eq!(board.holes().len(), 1);
+
+        // cutouts may overlap and stick out of the rectangle
+        let corner = Tile::from_str("L", "x\nxx");
+        let board = Tile::rect_minus(
+            3,
+            2,
+            &[(corner, Point::new(2, 0)), (square, Point::new(-1, -1))],
+        );
+        assert_eq!(board, Tile::from_str("Board", " x\nxx"));
+    }
+
+    #[test]
+    fn scaled() {
+        let tile = Tile::from_str("L", "xx\nx");
+        let scaled = tile.scaled(2);
+        assert_eq!(scaled.len(), 12);
+        assert_eq!(scaled.size(), Size::new(4, 4));
+        assert!(scaled.points.contains(&Point::new(3, 1)));
+        assert!(!scaled.points.contains(&Point::new(2, 2)));
+
+        let mut original = tile.clone();
+        original.points.sort();
+        assert_eq!(scaled.downsample(2), Some(original));
+        assert_eq!(tile.scaled(1).downsample(1).map(|t| t.len()), Some(3));
+
+        // incomplete or misaligned blocks
+        let mut broken = scaled.clone();
+        broken.points.pop();
+        assert_eq!(broken.downsample(2), None);
+        let mut shifted = scaled.clone();
+        shifted.translate(&Point::new(1, 0));
+        assert_eq!(shifted.downsample(2), None);
+        assert_eq!(tile.downsample(0), None);
+    }
+
+    #[test]
+    fn holes() {
+        let ring = Tile::from_str("Ring", "xxxx\nx  x\nxxxx\nx xx\nxxxx");
+        assert_eq!(
+            ring.holes(),
+            vec![
+                vec![Point::new(1, 1), Point::new(2, 1)],
+                vec![Point::new(1, 3)]
+            ]
+        );
+        // a gap open to the side is no hole, a cell open only at a corner is
+        assert!(Tile::from_str("U", "x x\nxxx").holes().is_empty());
+        assert!(Tile::from_str("C", "xxx\nx\nxxx").holes().is_empty());
+        assert_eq!(
+            Tile::from_str("Corner", "xxx\nx x\nxx").holes(),
+            vec![vec![Point::new(1, 1)]]
+        );
+        assert!(Tile::new("Empty").holes().is_empty());
+    }
+
+    #[test]
+    fn plane_tilings() {
+        let tiles = |drawing: &str| Tile::from_str("T", drawing).tiles_plane_by_translation();
+        assert!(tiles("x"));
+        assert!(tiles("xx\nx"));
+        assert!(tiles("xxx\n x"));
+        assert!(tiles(" x\nxxx\n x"));
+        // the notch of the U can only be filled by a turned copy
+        assert!(!tiles("x x\nxxx"));
+        // holes and gaps never tile
+        assert!(!tiles("xxx\nx x\nxxx"));
+        assert!(!tiles("x x"));
+        assert!(!Tile::new("Empty").tiles_plane_by_translation());
+    }
+
+    #[test]
+    fn shape_metrics() {
+        let mut tile = Tile::from_str("L", "x\nx\nxx");
+        tile.translate(&Point::new(2, -1));
+        assert_eq!(tile.len(), 4);
+        assert_eq!(tile.bounding_box(), (Point::new(2, -1), Size::new(2, 3)));
+        assert_eq!(tile.perimeter(), 10);
+        assert_eq!(Tile::rect(3, 3).perimeter(), 12);
+        // the hole has an outline of its own
+        let mut hole = Tile::from_points("C", &[(0, 0)]);
+        hole.translate(&Point::new(1, 1));
+        assert_eq!(Tile::rect(3, 3).difference(&hole).perimeter(), 16);
+        assert_eq!(
+            Tile::new("E").bounding_box(),
+            (Point::new(0, 0), Size::new(0, 0))
+        );
+        assert_eq!(Tile::new("E").perimeter(), 0);
+    }
+
+    #[test]
+    fn neighbors() {
+        let board = Tile::from_str("Board", "xxx\nx x\nxxx");
+        let corner = Point::new(0, 0);
+        assert_eq!(
+            board.neighbors4(&corner, Topology::Plane),
+            [Point::new(1, 0), Point::new(0, 1)]
+        );
+        assert_eq!(board.neighbors8(&corner, Topology::Plane).len(), 2);
+        assert_eq!(
+            board.neighbors8(&Point::new(1, 1), Topology::Plane).len(),
+            8
+        );
+        // the corner wraps around to the opposite sides
+        assert_eq!(board.neighbors4(&corner, Topology::Torus).len(), 4);
+        assert_eq!(board.neighbors4(&corner, Topology::CylinderX).len(), 3);
+
+        // every cell of the ring touches the outside or the hole
+        assert_eq!(board.boundary_cells(Topology::Plane).len(), 8);
+        assert_eq!(board.boundary_cells(Topology::Torus).len(), 4);
+        assert!(Tile::rect(3, 2).boundary_cells(Topology::Torus).is_empty());
+        assert_eq!(Tile::rect(3, 3).boundary_cells(Topology::Plane).len(), 8);
+    }
+}
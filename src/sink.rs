@@ -0,0 +1,230 @@
+// Destinations for enumerated solutions.
+//
+// Enumerations can be huge, so instead of always collecting solutions in
+// memory a sink decides what to keep: all of them, all of them packed as
+// bitsets, only their number, a stream of text lines, or the distinct ones.
+
+use std::collections::HashSet;
+use std::io;
+use std::io::Write;
+
+// Receives solutions as sorted solver row ids.
+pub trait SolutionSink {
+    fn push(&mut self, solution: Vec<usize>);
+
+    // number of solutions kept (or seen, for sinks not keeping them).
+    fn count(&self) -> usize;
+}
+
+impl SolutionSink for Vec<Vec<usize>> {
+    fn push(&mut self, solution: Vec<usize>) {
+        Vec::push(self, solution);
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+}
+
+// Only counts the solutions.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Counter {
+    pub count: usize,
+}
+
+impl SolutionSink for Counter {
+    fn push(&mut self, _solution: Vec<usize>) {
+        self.count += 1;
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+}
+
+// Writes every solution as a line of space separated row ids. Writing stops
+// at the first error, which is kept.
+pub struct Writer<W: Write> {
+    out: W,
+    count: usize,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            count: 0,
+            error: None,
+        }
+    }
+
+    // flush the output, returning the first error met.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+impl<W: Write> SolutionSink for Writer<W> {
+    fn push(&mut self, solution: Vec<usize>) {
+        if self.error.is_some() {
+            return;
+        }
+        let line: Vec<String> = solution.iter().map(|r| r.to_string()).collect();
+        match writeln!(self.out, "{}", line.join(" ")) {
+            Ok(()) => self.count += 1,
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+}
+
+// Keeps each distinct solution once, for enumerations that may repeat them.
+#[derive(Debug, Default, Clone)]
+pub struct Dedup {
+    pub seen: HashSet<Vec<usize>>,
+}
+
+impl SolutionSink for Dedup {
+    fn push(&mut self, solution: Vec<usize>) {
+        self.seen.insert(solution);
+    }
+
+    fn count(&self) -> usize {
+        self.seen.len()
+    }
+}
+
+// Keeps all solutions as bitsets over the rows, in one buffer with a fixed
+// number of words per solution. Solutions are decoded to row ids on access.
+#[derive(Debug, Default, Clone)]
+pub struct Bitsets {
+    // solver id of each row, ascending
+    ids: Vec<usize>,
+    words: usize,
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl Bitsets {
+    // an empty store for solutions over the rows with the given solver ids.
+    pub fn new(ids: Vec<usize>) -> Self {
+        Self {
+            words: ids.len().div_ceil(64),
+            ids,
+            bits: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // the solution at `index`, as sorted solver row ids.
+    pub fn get(&self, index: usize) -> Option<Vec<usize>> {
+        if index >= self.len {
+            return None;
+        }
+        let block = self
+            .bits
+            .get(index * self.words..(index + 1) * self.words)?;
+        let mut solution = Vec::new();
+        for (w, word) in block.iter().enumerate() {
+            let mut word = *word;
+            while word != 0 {
+                solution.push(self.ids[w * 64 + word.trailing_zeros() as usize]);
+                word &= word - 1;
+            }
+        }
+        Some(solution)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Vec<usize>> + '_ {
+        (0..self.len()).filter_map(|index| self.get(index))
+    }
+
+    // bytes of the packed solutions and the row ids.
+    pub fn bytes(&self) -> usize {
+        (self.bits.capacity() * size_of::<u64>()) + (self.ids.capacity() * size_of::<usize>())
+    }
+}
+
+impl SolutionSink for Bitsets {
+    fn push(&mut self, solution: Vec<usize>) {
+        let start = self.bits.len();
+        self.bits.resize(start + self.words, 0);
+        for id in solution {
+            let index = self.ids.binary_search(&id).expect("unknown row id");
+            self.bits[start + index / 64] |= 1 << (index % 64);
+        }
+        self.len += 1;
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Bitsets;
+    use super::Counter;
+    use super::Dedup;
+    use super::SolutionSink;
+    use super::Writer;
+
+    #[test]
+    fn sinks() {
+        let solutions = [vec![1, 5], vec![2, 3], vec![1, 5]];
+
+        let mut all: Vec<Vec<usize>> = Vec::new();
+        let mut counter = Counter::default();
+        let mut dedup = Dedup::default();
+        let mut writer = Writer::new(Vec::new());
+        for solution in &solutions {
+            for sink in [
+                &mut all as &mut dyn SolutionSink,
+                &mut counter,
+                &mut dedup,
+                &mut writer,
+            ] {
+                sink.push(solution.clone());
+            }
+        }
+
+        assert_eq!(all.count(), 3);
+        assert_eq!(counter.count, 3);
+        assert_eq!(dedup.count(), 2);
+        assert_eq!(writer.count(), 3);
+        let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(text, "1 5\n2 3\n1 5\n");
+    }
+
+    #[test]
+    fn bitsets() {
+        let ids: Vec<usize> = (0..100).map(|i| 10 + 3 * i).collect();
+        let mut bitsets = Bitsets::new(ids);
+        assert!(bitsets.is_empty());
+        bitsets.push(vec![10, 13, 307]);
+        bitsets.push(vec![208]);
+        assert_eq!(bitsets.count(), 2);
+        assert_eq!(bitsets.get(0), Some(vec![10, 13, 307]));
+        assert_eq!(bitsets.get(1), Some(vec![208]));
+        assert_eq!(bitsets.get(2), None);
+        assert_eq!(bitsets.iter().count(), 2);
+        // two words per solution
+        assert!(bitsets.bytes() >= 4 * 8);
+    }
+}
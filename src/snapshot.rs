@@ -0,0 +1,282 @@
+// Binary snapshots of the placement rows of a game, so big fixed puzzles can
+// ship their matrix and skip generating it at startup.
+//
+// Layout, little endian: the magic "PLYM", the version (u32), a fingerprint of
+// the board, the pieces and the rules deciding the rows (u64), the number of columns and of rows (u32), the end
+// of each row (u32), the number of column entries (u32) and the columns (u32),
+// then for each row the piece (u32), the transform (u8) and the anchor (two i32).
+
+use std::fmt;
+
+use crate::placement::Placement;
+use crate::placement::Transform;
+use crate::polyomino::Rules;
+use crate::rows::Rows;
+use crate::shape::Point;
+use crate::shape::Tile;
+
+const MAGIC: &[u8; 4] = b"PLYM";
+const VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    // not a snapshot
+    BadMagic,
+    // written by another version of the format
+    Version(u32),
+    // the data ends early or has trailing bytes
+    Truncated,
+    // made for another board or other pieces
+    Mismatch,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::BadMagic => write!(f, "Not a matrix snapshot"),
+            SnapshotError::Version(version) => {
+                write!(f, "Unsupported matrix snapshot version {}", version)
+            }
+            SnapshotError::Truncated => write!(f, "Matrix snapshot is truncated"),
+            SnapshotError::Mismatch => write!(f, "Matrix snapshot is for another puzzle"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+pub struct Snapshot {
+    pub fingerprint: u64,
+    pub n_cols: usize,
+    pub rows: Rows,
+    pub placements: Vec<Placement>,
+}
+
+// FNV-1a hash, stable across builds and platforms.
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    pub fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    pub fn number(&mut self, n: i64) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    // text with its length, so consecutive texts cannot run into each other.
+    pub fn text(&mut self, text: &str) {
+        self.number(text.len() as i64);
+        self.bytes(text.as_bytes());
+    }
+
+    pub fn point(&mut self, p: &Point) {
+        self.number(p.x as i64);
+        self.number(p.y as i64);
+    }
+
+    pub fn tile(&mut self, tile: &Tile) {
+        self.text(&tile.name);
+        self.number(tile.points.len() as i64);
+        for p in &tile.points {
+            self.point(p);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Fingerprint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// hash of what decides the rows: the board with its optional cells, the
+// pieces and the placement rules.
+pub(crate) fn fingerprint(board: &Tile, optional: &Tile, tiles: &[Tile], rules: &Rules) -> u64 {
+    let mut hash = Fingerprint::new();
+    for tile in [board, optional].into_iter().chain(tiles) {
+        hash.tile(tile);
+    }
+    rules.fingerprint(&mut hash);
+    hash.finish()
+}
+
+fn put_u32(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend(u32::try_from(value).unwrap().to_le_bytes());
+}
+
+pub fn encode(snapshot: &Snapshot) -> Vec<u8> {
+    let (cols, ends) = snapshot.rows.parts();
+    let mut bytes = Vec::with_capacity(32 + 4 * (cols.len() + ends.len()) + 13 * ends.len());
+    bytes.extend(MAGIC);
+    bytes.extend(VERSION.to_le_bytes());
+    bytes.extend(snapshot.fingerprint.to_le_bytes());
+    put_u32(&mut bytes, snapshot.n_cols);
+    put_u32(&mut bytes, ends.len());
+    for end in ends {
+        bytes.extend(end.to_le_bytes());
+    }
+    put_u32(&mut bytes, cols.len());
+    for col in cols {
+        bytes.extend(col.to_le_bytes());
+    }
+    for placement in &snapshot.placements {
+        put_u32(&mut bytes, placement.piece);
+        bytes.push(placement.transform.0);
+        bytes.extend(i32::try_from(placement.anchor.x).unwrap().to_le_bytes());
+        bytes.extend(i32::try_from(placement.anchor.y).unwrap().to_le_bytes());
+    }
+    bytes
+}
+
+// Reads the snapshot fields in order.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        if self.bytes.len() < N {
+            return Err(SnapshotError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(N);
+        self.bytes = tail;
+        Ok(head.try_into().unwrap())
+    }
+
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, SnapshotError> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    fn u32s(&mut self, n: usize) -> Result<Vec<u32>, SnapshotError> {
+        if self.bytes.len() < 4 * n {
+            return Err(SnapshotError::Truncated);
+        }
+        (0..n).map(|_| self.u32()).collect()
+    }
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Snapshot, SnapshotError> {
+    let mut reader = Reader { bytes };
+    if &reader.take::<4>().map_err(|_| SnapshotError::BadMagic)? != MAGIC {
+        return Err(SnapshotError::BadMagic);
+    }
+    let version = reader.u32()?;
+    if version != VERSION {
+        return Err(SnapshotError::Version(version));
+    }
+    let fingerprint = u64::from_le_bytes(reader.take()?);
+    let n_cols = reader.u32()? as usize;
+    let n_rows = reader.u32()? as usize;
+    let ends = reader.u32s(n_rows)?;
+    let n_entries = reader.u32()? as usize;
+    let cols = reader.u32s(n_entries)?;
+
+    // rows must be in order and within the columns
+    let ordered = ends.windows(2).all(|w| w[0] <= w[1]);
+    if !ordered || ends.last().is_some_and(|e| *e as usize != cols.len()) {
+        return Err(SnapshotError::Truncated);
+    }
+    if cols.iter().any(|c| *c as usize >= n_cols) {
+        return Err(SnapshotError::Truncated);
+    }
+
+    let mut placements = Vec::with_capacity(n_rows);
+    for _ in 0..n_rows {
+        let piece = reader.u32()? as usize;
+        let [transform] = reader.take::<1>()?;
+        let x = reader.i32()?;
+        let y = reader.i32()?;
+        placements.push(Placement {
+            piece,
+            transform: Transform(transform),
+            anchor: Point::new(x as isize, y as isize),
+        });
+    }
+    if !reader.bytes.is_empty() {
+        return Err(SnapshotError::Truncated);
+    }
+
+    Ok(Snapshot {
+        fingerprint,
+        n_cols,
+        rows: Rows::from_parts(cols, ends),
+        placements,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::Snapshot;
+    use super::SnapshotError;
+    use super::decode;
+    use super::encode;
+    use super::fingerprint;
+    use crate::placement::Placement;
+    use crate::placement::Transform;
+    use crate::polyomino::Rules;
+    use crate::rows::Rows;
+    use crate::shape::Point;
+    use crate::shape::Tile;
+
+    #[test]
+    fn round_trip() {
+        let mut rows = Rows::new();
+        rows.push(&[0, 2]);
+        rows.push(&[1, 2]);
+        let placements = vec![
+            Placement {
+                piece: 0,
+                transform: Transform(0),
+                anchor: Point::new(0, 0),
+            },
+            Placement {
+                piece: 0,
+                transform: Transform(6),
+                anchor: Point::new(1, -2),
+            },
+        ];
+        let board = Tile::from_str("Board", "xx");
+        let optional = Tile::new("Optional");
+        let tiles = [Tile::from_str("I", "x")];
+        let rules = Rules::default();
+        let snapshot = Snapshot {
+            fingerprint: fingerprint(&board, &optional, &tiles, &rules),
+            n_cols: 3,
+            rows,
+            placements,
+        };
+
+        let bytes = encode(&snapshot);
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.fingerprint, snapshot.fingerprint);
+        assert_eq!(decoded.n_cols, 3);
+        assert_eq!(decoded.rows, snapshot.rows);
+        assert_eq!(decoded.placements, snapshot.placements);
+
+        assert_ne!(
+            snapshot.fingerprint,
+            fingerprint(&board, &optional, &[], &rules)
+        );
+        assert_eq!(
+            decode(&bytes[..bytes.len() - 1]).err(),
+            Some(SnapshotError::Truncated)
+        );
+        assert_eq!(decode(b"PLY").err(), Some(SnapshotError::BadMagic));
+    }
+}
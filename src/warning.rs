@@ -0,0 +1,62 @@
+use std::fmt;
+
+// Problems found while reading or building a game that do not prevent solving.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    // several pieces share a name.
+    DuplicatePiece { name: String },
+    // a piece is not 4-connected.
+    DisconnectedTile { name: String },
+    // a board falls apart into several 4-connected regions.
+    DisconnectedBoard { name: String, regions: usize },
+    // the pieces do not cover exactly the board area.
+    AreaMismatch { board: usize, pieces: usize },
+    // a key without any cells, which is ignored.
+    UnusedKey { name: String },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::DuplicatePiece { name } => write!(f, "Duplicate piece {:?}", name),
+            Warning::DisconnectedTile { name } => write!(f, "Piece {:?} is disconnected", name),
+            Warning::DisconnectedBoard { name, regions } => {
+                write!(f, "Board {:?} splits into {} regions", name, regions)
+            }
+            Warning::AreaMismatch { board, pieces } => write!(
+                f,
+                "Board area {} differs from total piece area {}",
+                board, pieces
+            ),
+            Warning::UnusedKey { name } => write!(f, "Key {:?} has no cells and is ignored", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Warning;
+
+    #[test]
+    fn display() {
+        let warning = Warning::AreaMismatch {
+            board: 10,
+            pieces: 12,
+        };
+        assert_eq!(
+            warning.to_string(),
+            "Board area 10 differs from total piece area 12"
+        );
+
+        let warning = Warning::UnusedKey {
+            name: String::from("Z"),
+        };
+        assert_eq!(warning.to_string(), "Key \"Z\" has no cells and is ignored");
+
+        let warning = Warning::DisconnectedBoard {
+            name: String::from("Board"),
+            regions: 2,
+        };
+        assert_eq!(warning.to_string(), "Board \"Board\" splits into 2 regions");
+    }
+}
@@ -0,0 +1,11 @@
+// Headless conformance harness for the wasm bundle, run with
+// `wasm-pack test --headless --firefox`.
+use polyomino::JsGame;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn conformance() {
+    assert_eq!(JsGame::conformance(), Vec::<String>::new());
+}